@@ -11,7 +11,7 @@ fn init(_: Url, orders: &mut impl Orders<Msg>) -> Model {
     orders
         .send_msg(Msg::SetViewportWidth)
         .stream(streams::window_event(Ev::Resize, |_| Msg::SetViewportWidth))
-        .after_next_render(Msg::Rendered);
+        .stream(streams::animation_frame(Msg::OnAnimationFrame));
 
     Model::default()
 }
@@ -69,15 +69,15 @@ impl Default for Car {
 // ------ ------
 
 enum Msg {
-    Rendered(RenderInfo),
+    OnAnimationFrame(FrameInfo),
     SetViewportWidth,
 }
 
 #[allow(clippy::needless_pass_by_value)]
 fn update(msg: Msg, model: &mut Model, orders: &mut impl Orders<Msg>) {
     match msg {
-        Msg::Rendered(render_info) => {
-            let delta = render_info.timestamp_delta.unwrap_or_default();
+        Msg::OnAnimationFrame(frame) => {
+            let delta = frame.delta();
             if delta > 0. {
                 // Move car at least 1px to the right.
                 model.car.x += f64::max(1., delta / 1000. * model.car.speed);
@@ -87,7 +87,6 @@ fn update(msg: Msg, model: &mut Model, orders: &mut impl Orders<Msg>) {
                     model.car = Car::default();
                 }
             }
-            orders.after_next_render(Msg::Rendered);
         }
         Msg::SetViewportWidth => {
             model.viewport_width = f64::from(body().client_width());
@@ -80,7 +80,10 @@ fn view(model: &Model) -> impl IntoNodes<Msg> {
         hr![],
         div![
             "sl-input",
-            sl_input![sl_input::on_input(Msg::InputChanged)],
+            sl_input![
+                props! {"value" => model.input_value.clone()},
+                sl_input::on_input(Msg::InputChanged),
+            ],
             &model.input_value,
         ],
     ]
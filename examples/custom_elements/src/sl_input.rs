@@ -7,7 +7,7 @@ macro_rules! sl_input {
         {
             custom![
                 Tag::from("sl-input"),
-                $( $part )*
+                $( $part, )*
             ]
         }
     };
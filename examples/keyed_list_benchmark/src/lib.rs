@@ -0,0 +1,275 @@
+//! Benchmark of keyed list reordering.
+//! See README.md for more details.
+
+// Some Clippy linter rules are ignored for the sake of simplicity.
+#![allow(clippy::needless_pass_by_value, clippy::trivially_copy_pass_by_ref)]
+
+use rand::{rngs::SmallRng, seq::SliceRandom, SeedableRng};
+use seed::{app::RenderStats, prelude::*, *};
+
+#[cfg(test)]
+mod previous_diff;
+
+const SHUFFLE_SEED: u64 = 42;
+
+// ------ ------
+//     Init
+// ------ ------
+
+fn init(_: Url, _: &mut impl Orders<Msg>) -> Model {
+    Model {
+        rows: Vec::new(),
+        next_row_id: 0,
+        running: None,
+        results: Vec::new(),
+    }
+}
+
+// ------ ------
+//     Model
+// ------ ------
+
+struct Model {
+    rows: Vec<Row>,
+    next_row_id: usize,
+    running: Option<Operation>,
+    results: Vec<BenchmarkResult>,
+}
+
+struct Row {
+    id: usize,
+    label: String,
+}
+
+struct BenchmarkResult {
+    operation: Operation,
+    rows: usize,
+    stats: RenderStats,
+}
+
+#[derive(Copy, Clone, Debug)]
+enum Operation {
+    Create(usize),
+    MoveLastToFront,
+    MoveFirstToEnd,
+    SwapRows,
+    Reverse,
+    Shuffle,
+}
+
+impl Operation {
+    const ALL: [Self; 7] = [
+        Self::Create(1_000),
+        Self::Create(10_000),
+        Self::MoveLastToFront,
+        Self::MoveFirstToEnd,
+        Self::SwapRows,
+        Self::Reverse,
+        Self::Shuffle,
+    ];
+
+    fn label(self) -> String {
+        match self {
+            Self::Create(count) => format!("Create {} rows", count),
+            Self::MoveLastToFront => "Move last to front".to_owned(),
+            Self::MoveFirstToEnd => "Move first to end".to_owned(),
+            Self::SwapRows => "Swap rows".to_owned(),
+            Self::Reverse => "Reverse".to_owned(),
+            Self::Shuffle => "Shuffle".to_owned(),
+        }
+    }
+
+    fn apply(self, model: &mut Model) {
+        let rows = &mut model.rows;
+        match self {
+            Self::Create(count) => {
+                let first_id = model.next_row_id;
+                model.next_row_id += count;
+                *rows = (first_id..model.next_row_id)
+                    .map(|id| Row {
+                        id,
+                        label: format!("Row {}", id),
+                    })
+                    .collect();
+            }
+            Self::MoveLastToFront if !rows.is_empty() => rows.rotate_right(1),
+            Self::MoveFirstToEnd if !rows.is_empty() => rows.rotate_left(1),
+            // The same rows as in js-framework-benchmark.
+            Self::SwapRows if rows.len() > 998 => rows.swap(1, 998),
+            Self::Reverse => rows.reverse(),
+            // The same order for every run.
+            Self::Shuffle => rows.shuffle(&mut SmallRng::seed_from_u64(SHUFFLE_SEED)),
+            _ => (),
+        }
+    }
+}
+
+// ------ ------
+//    Update
+// ------ ------
+
+enum Msg {
+    Run(Operation),
+    Rendered(RenderInfo),
+    ClearResults,
+}
+
+fn update(msg: Msg, model: &mut Model, orders: &mut impl Orders<Msg>) {
+    match msg {
+        Msg::Run(operation) => {
+            operation.apply(model);
+            model.running = Some(operation);
+            orders.after_next_render(Msg::Rendered);
+        }
+        Msg::Rendered(render_info) => {
+            if let (Some(operation), Some(stats)) = (model.running.take(), render_info.stats) {
+                model.results.push(BenchmarkResult {
+                    operation,
+                    rows: model.rows.len(),
+                    stats,
+                });
+            }
+        }
+        Msg::ClearResults => model.results.clear(),
+    }
+}
+
+// ------ ------
+//     View
+// ------ ------
+
+fn view(model: &Model) -> Node<Msg> {
+    div![
+        div![
+            Operation::ALL.iter().map(|operation| {
+                let operation = *operation;
+                button![
+                    operation.label(),
+                    ev(Ev::Click, move |_| Msg::Run(operation))
+                ]
+            }),
+            button!["Clear results", ev(Ev::Click, |_| Msg::ClearResults)],
+        ],
+        view_results(&model.results),
+        view_rows(&model.rows),
+    ]
+}
+
+fn view_results(results: &[BenchmarkResult]) -> Node<Msg> {
+    table![
+        tr![
+            th!["Operation"],
+            th!["Rows"],
+            th!["Patch (ms)"],
+            th!["DOM operations"],
+            th!["Operations by kind"],
+        ],
+        results.iter().map(|result| {
+            let counts = result.stats.patch_counts;
+            tr![
+                td![result.operation.label()],
+                td![result.rows],
+                td![format!("{:.1}", result.stats.patch_duration)],
+                td![counts.total()],
+                td![format!("{:?}", counts)],
+            ]
+        })
+    ]
+}
+
+fn view_rows(rows: &[Row]) -> Node<Msg> {
+    table![rows.iter().map(|row| {
+        tr![
+            el_key(&row.id),
+            td![row.id],
+            td![&row.label],
+            // Type into an input and reorder rows to check that focus is preserved.
+            td![input![]],
+        ]
+    })]
+}
+
+// ------ ------
+//     Start
+// ------ ------
+
+#[wasm_bindgen(start)]
+pub fn start() {
+    App::start("app", init, update, view).set_instrumentation(true);
+}
+
+// ------ ------
+//     Tests
+// ------ ------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use seed::app::{OrdersContainer, PatchCounts};
+    use std::{cell::RefCell, rc::Rc};
+    use wasm_bindgen_test::*;
+
+    wasm_bindgen_test_configure!(run_in_browser);
+
+    fn format_counts(operation: Operation, diff: &str, counts: PatchCounts) -> String {
+        format!(
+            "{:<20} {:<8} {:>6} {:?}",
+            operation.label(),
+            diff,
+            counts.total(),
+            counts
+        )
+    }
+
+    fn row_keys(model: &Model) -> Vec<usize> {
+        model.rows.iter().map(|row| row.id).collect()
+    }
+
+    /// Runs all operations and prints DOM operations of the current diff and of the previous one
+    /// (see `previous_diff`) - see `cargo make compare` in README.md.
+    #[wasm_bindgen_test]
+    pub fn patch_counts() {
+        let results = Rc::new(RefCell::new(Vec::new()));
+        let record_results = {
+            let results = Rc::clone(&results);
+            let previous_counts = Rc::new(RefCell::new(None));
+            move |msg: Msg,
+                  model: &mut Model,
+                  orders: &mut OrdersContainer<Msg, Model, Node<Msg>>| {
+                if let (Msg::Rendered(render_info), Some(operation)) = (&msg, model.running) {
+                    if let (Some(stats), Some(previous_counts)) =
+                        (render_info.stats, previous_counts.take())
+                    {
+                        let mut results = results.borrow_mut();
+                        results.push(format_counts(operation, "current", stats.patch_counts));
+                        results.push(format_counts(operation, "previous", previous_counts));
+                    }
+                }
+                let old_keys = row_keys(model);
+                let is_run = matches!(msg, Msg::Run(_));
+                update(msg, model, orders);
+                if is_run {
+                    previous_counts.replace(Some(previous_diff::patch_counts(
+                        &old_keys,
+                        &row_keys(model),
+                    )));
+                }
+            }
+        };
+
+        let root = document().create_element("div").unwrap();
+        body().append_child(&root).unwrap();
+        // Only rows are rendered - the previous diff is simulated for `view_rows`.
+        let app = App::builder(init, record_results, |model: &Model| view_rows(&model.rows))
+            .render_mode(RenderMode::Sync)
+            .start(root);
+        app.set_instrumentation(true);
+
+        for operation in Operation::ALL.iter().copied() {
+            app.update(Msg::Run(operation));
+        }
+
+        assert_eq!(results.borrow().len(), 2 * Operation::ALL.len());
+        console_log!("{}", results.borrow().join("\n"));
+    }
+}
@@ -0,0 +1,206 @@
+//! The keyed diff used by Seed before the longest increasing subsequence diff,
+//! reduced to row keys - it counts DOM operations the previous diff would make for `view_rows`.
+//!
+//! The old algorithm takes children from both lists in turn until it finds a key seen
+//! in the other list. Children before the matching pair are replaced, inserted or removed,
+//! so reordered rows are recreated instead of moved.
+
+use seed::app::PatchCounts;
+use std::collections::{BTreeSet, VecDeque};
+use std::iter::{Copied, Peekable};
+use std::slice;
+
+/// Operations done by patching a row with the same key - its 3 cells, 2 texts and the input.
+const ROW_CHILDREN_PATCH_EL: u32 = 4;
+const ROW_CHILDREN_PATCH_TEXT: u32 = 2;
+
+/// DOM operations of the previous diff for rendering `new_keys` rows over `old_keys` rows.
+pub fn patch_counts(old_keys: &[usize], new_keys: &[usize]) -> PatchCounts {
+    let mut counts = PatchCounts {
+        // The table itself.
+        patch_el: 1,
+        ..PatchCounts::default()
+    };
+    for command in PreviousDiff::new(old_keys, new_keys) {
+        match command {
+            Command::Append => counts.append_el += 1,
+            Command::Insert => counts.insert_el += 1,
+            Command::Patch => {
+                counts.patch_el += 1 + ROW_CHILDREN_PATCH_EL;
+                counts.patch_text += ROW_CHILDREN_PATCH_TEXT;
+            }
+            Command::Replace => counts.replace_el_by_el += 1,
+            Command::Remove => counts.remove_el += 1,
+        }
+    }
+    counts
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum Command {
+    Append,
+    Insert,
+    Patch,
+    Replace,
+    Remove,
+}
+
+/// `PatchGen` of the previous diff - all children are keyed rows.
+struct PreviousDiff<'a> {
+    old_iter: Peekable<Copied<slice::Iter<'a, usize>>>,
+    new_iter: Peekable<Copied<slice::Iter<'a, usize>>>,
+    /// Children are pushed to the front and taken from the back.
+    old_queue: VecDeque<usize>,
+    new_queue: VecDeque<usize>,
+    matching_old: Option<usize>,
+    matching_new: Option<usize>,
+    matching_key: Option<usize>,
+    keyed_mode: bool,
+}
+
+impl<'a> PreviousDiff<'a> {
+    fn new(old_keys: &'a [usize], new_keys: &'a [usize]) -> Self {
+        Self {
+            old_iter: old_keys.iter().copied().peekable(),
+            new_iter: new_keys.iter().copied().peekable(),
+            old_queue: VecDeque::new(),
+            new_queue: VecDeque::new(),
+            matching_old: None,
+            matching_new: None,
+            matching_key: None,
+            keyed_mode: false,
+        }
+    }
+
+    fn next_command(&mut self) -> Option<Command> {
+        if !self.keyed_mode {
+            return self.yield_keyless();
+        }
+        if self.matching_key.is_some() {
+            return self.yield_keyed();
+        }
+        if self.old_iter.peek().is_some() || self.new_iter.peek().is_some() {
+            self.matching_key = self.find_matching();
+            if self.matching_key.is_some() {
+                return self.yield_keyed();
+            }
+        }
+        match (self.old_queue.pop_back(), self.new_queue.pop_back()) {
+            (Some(old), Some(new)) => Some(patch_or_replace(old, new)),
+            (Some(_), None) => Some(Command::Remove),
+            (None, Some(_)) => Some(Command::Append),
+            (None, None) => None,
+        }
+    }
+
+    fn yield_keyless(&mut self) -> Option<Command> {
+        let old = self.old_queue.pop_back().or_else(|| self.old_iter.next());
+        match (old, self.new_iter.next()) {
+            (Some(old), Some(new)) => {
+                // Permanent switch to keyed mode.
+                self.keyed_mode = true;
+                if old == new {
+                    self.matching_key = Some(new);
+                }
+                self.old_queue.push_back(old);
+                self.new_queue.push_back(new);
+                self.next_command()
+            }
+            (None, Some(_)) => Some(Command::Append),
+            (Some(_), None) => Some(Command::Remove),
+            (None, None) => None,
+        }
+    }
+
+    fn yield_keyed(&mut self) -> Option<Command> {
+        let matching_key = self.matching_key;
+        match (self.matching_old, self.matching_new) {
+            (None, None) => {
+                let old = self.old_queue.pop_back().expect("old child from the queue");
+                let new = self.new_queue.pop_back().expect("new child from the queue");
+                match (Some(old) == matching_key, Some(new) == matching_key) {
+                    (true, true) => {
+                        self.matching_old = Some(old);
+                        self.matching_new = Some(new);
+                        self.yield_keyed()
+                    }
+                    (true, false) => {
+                        self.matching_old = Some(old);
+                        Some(Command::Insert)
+                    }
+                    (false, true) => {
+                        self.matching_new = Some(new);
+                        Some(Command::Remove)
+                    }
+                    (false, false) => Some(patch_or_replace(old, new)),
+                }
+            }
+            (Some(_), None) => {
+                let new = self.new_queue.pop_back().expect("node with a matching key");
+                if Some(new) == matching_key {
+                    self.matching_new = Some(new);
+                    return self.yield_keyed();
+                }
+                Some(Command::Insert)
+            }
+            (None, Some(_)) => {
+                let old = self.old_queue.pop_back().expect("node with a matching key");
+                if Some(old) == matching_key {
+                    self.matching_old = Some(old);
+                    return self.yield_keyed();
+                }
+                Some(Command::Remove)
+            }
+            (Some(old), Some(new)) => {
+                self.matching_key = None;
+                self.matching_old = None;
+                self.matching_new = None;
+                Some(patch_or_replace(old, new))
+            }
+        }
+    }
+
+    /// Moves children from the source iterators to the queues until a key is in both of them.
+    fn find_matching(&mut self) -> Option<usize> {
+        let mut seen_old_keys: BTreeSet<_> = self.old_queue.iter().copied().collect();
+        let mut seen_new_keys: BTreeSet<_> = self.new_queue.iter().copied().collect();
+
+        while self.old_iter.peek().is_some() || self.new_iter.peek().is_some() {
+            let should_pick_old_child = self.old_iter.peek().is_some()
+                && (self.new_iter.peek().is_none() || self.new_queue.len() > self.old_queue.len());
+
+            if should_pick_old_child {
+                let key = self.old_iter.next().expect("old child");
+                self.old_queue.push_front(key);
+                if seen_new_keys.contains(&key) {
+                    return Some(key);
+                }
+                seen_old_keys.insert(key);
+            } else {
+                let key = self.new_iter.next().expect("new child");
+                self.new_queue.push_front(key);
+                if seen_old_keys.contains(&key) {
+                    return Some(key);
+                }
+                seen_new_keys.insert(key);
+            }
+        }
+        None
+    }
+}
+
+impl Iterator for PreviousDiff<'_> {
+    type Item = Command;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_command()
+    }
+}
+
+fn patch_or_replace(old: usize, new: usize) -> Command {
+    if old == new {
+        Command::Patch
+    } else {
+        Command::Replace
+    }
+}
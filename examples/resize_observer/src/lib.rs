@@ -7,38 +7,15 @@ use seed::{prelude::*, *};
 // ------ ------
 
 fn init(_: Url, orders: &mut impl Orders<Msg>) -> Model {
-    let svg_container = ElRef::new();
-    let msg_sender = orders.msg_sender();
-
-    orders.after_next_render({
-        let svg_container = svg_container.clone();
-        move |_| {
-            let element = svg_container.get().expect("get svg_container element");
-            let callback = move |width, height| msg_sender(Some(Msg::Resized(width, height)));
-
-            let closure = Closure::wrap(Box::new(callback) as Box<dyn Fn(f64, f64)>);
-            let closure_as_js_value = closure.as_ref().clone();
-            closure.forget();
-
-            observeElementSize(&element, &closure_as_js_value);
-        }
-    });
+    orders.after_next_render(|_| Msg::Rendered);
 
     Model {
-        svg_container,
+        svg_container: ElRef::new(),
         svg_container_size: None,
+        resize_observer: None,
     }
 }
 
-// ------ ------
-//    Extern
-// ------ ------
-
-#[wasm_bindgen]
-extern "C" {
-    fn observeElementSize(element: &web_sys::Element, callback: &JsValue);
-}
-
 // ------ ------
 //     Model
 // ------ ------
@@ -46,6 +23,8 @@ extern "C" {
 struct Model {
     svg_container: ElRef<web_sys::Element>,
     svg_container_size: Option<(f64, f64)>,
+    // The observer is disconnected on the handle drop.
+    resize_observer: Option<StreamHandle>,
 }
 
 // ------ ------
@@ -53,11 +32,19 @@ struct Model {
 // ------ ------
 
 enum Msg {
+    Rendered,
     Resized(f64, f64),
 }
 
-fn update(msg: Msg, model: &mut Model, _: &mut impl Orders<Msg>) {
+fn update(msg: Msg, model: &mut Model, orders: &mut impl Orders<Msg>) {
     match msg {
+        Msg::Rendered => {
+            model.resize_observer = Some(
+                orders.stream_with_handle(streams::resize_observer(&model.svg_container, |rect| {
+                    Msg::Resized(rect.width(), rect.height())
+                })),
+            );
+        }
         Msg::Resized(width, height) => model.svg_container_size = Some((width, height)),
     }
 }
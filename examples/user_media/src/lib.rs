@@ -1,6 +1,6 @@
 use seed::{prelude::*, *};
 use wasm_bindgen_futures::JsFuture;
-use web_sys::{HtmlMediaElement, MediaStream, MediaStreamConstraints};
+use web_sys::{MediaStream, MediaStreamConstraints};
 
 // ------ ------
 //     Init
@@ -35,7 +35,7 @@ async fn user_media() -> Msg {
 
 #[derive(Default)]
 struct Model {
-    video: ElRef<HtmlMediaElement>,
+    media_stream: Option<MediaStream>,
 }
 
 // ------ ------
@@ -49,11 +49,7 @@ enum Msg {
 fn update(msg: Msg, model: &mut Model, _: &mut impl Orders<Msg>) {
     match msg {
         Msg::UserMedia(Ok(media_stream)) => {
-            model
-                .video
-                .get()
-                .expect("get video element")
-                .set_src_object(Some(&media_stream));
+            model.media_stream = Some(media_stream);
         }
         Msg::UserMedia(Err(error)) => {
             log!(error);
@@ -67,11 +63,14 @@ fn update(msg: Msg, model: &mut Model, _: &mut impl Orders<Msg>) {
 
 fn view(model: &Model) -> impl IntoNodes<Msg> {
     video![
-        el_ref(&model.video),
         attrs! {
             At::Width => 320,
             At::Height => 240,
             At::AutoPlay => AtValue::None,
+        },
+        // `srcObject` is a property without an attribute counterpart.
+        props! {
+            "srcObject" => model.media_stream.clone(),
         }
     ]
 }
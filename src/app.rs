@@ -1,55 +1,112 @@
 #![allow(clippy::module_name_repetitions)]
 
-use crate::browser::dom::virtual_dom_bridge;
+use crate::browser::dom::{dom_error, virtual_dom_bridge, DomError};
 use crate::browser::{
     service::routing,
     util::{self, window, ClosureNew},
     Url, DUMMY_BASE_URL,
 };
-use crate::virtual_dom::{patch, El, EventHandlerManager, IntoNodes, Mailbox, Node, Tag};
+use crate::testing::Recorder;
+use crate::virtual_dom::{
+    lifecycle::{self, Lifecycle},
+    node::boundary,
+    patch, El, EventDelegation, EventHandlerManager, IntoNodes, Mailbox, Tag,
+};
+use cmd_manager::CmdManager;
 use enclose::{enc, enclose};
+use futures::future::{Future, FutureExt};
+use futures::stream::Stream;
 use std::{
     any::Any,
     cell::{Cell, RefCell},
-    collections::VecDeque,
+    collections::{HashMap, VecDeque},
     fmt,
     rc::Rc,
 };
+use stream_manager::StreamManager;
 use sub_manager::SubManager;
-use wasm_bindgen::closure::Closure;
+use task_registry::TaskRegistry;
+use wasm_bindgen::{closure::Closure, JsCast, JsValue};
+use wasm_bindgen_futures::spawn_local;
 
+pub mod animation;
+pub mod builder;
 pub mod cfg;
+pub mod clock;
 pub mod cmd_manager;
 pub mod cmds;
+pub mod component;
 pub mod data;
+pub mod devtools;
 mod effect;
 pub mod get_element;
+pub mod instrumentation;
+mod keyed_cmds;
 pub mod message_mapper;
 pub mod orders;
+pub mod persistence;
+mod rate_limit;
 pub mod render_info;
+pub mod retry;
 pub mod stream_manager;
 pub mod streams;
 pub mod sub_manager;
 pub mod subs;
+mod task_registry;
 
-pub use cfg::AppCfg;
+pub use animation::{Animation, Easing, FrameInfo, Spring, Tween};
+pub use builder::Builder;
+pub use cfg::{AppCfg, LinkInterception, RenderMode};
 pub use cmd_manager::CmdHandle;
-pub(crate) use data::AppData;
-use effect::Effect;
+pub use component::{Component, Instance};
+pub(crate) use data::{AppData, ScheduledRender};
+pub use devtools::Devtools;
+pub(crate) use effect::Effect;
 pub use get_element::GetElement;
+pub use instrumentation::{PatchCounts, RenderStats};
+pub use keyed_cmds::CmdPolicy;
 pub use message_mapper::MessageMapper;
 pub use orders::{Orders, OrdersContainer, OrdersProxy};
+pub use persistence::{Persistence, PersistenceError};
 pub use render_info::RenderInfo;
+pub use retry::{Jitter, RetryEvent, RetryPolicy};
 pub use stream_manager::StreamHandle;
-pub use sub_manager::{Notification, SubHandle};
+pub use sub_manager::{Notification, SubHandle, SubOptions};
 
 /// Determines if an update should cause the `VDom` to rerender or not.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub enum ShouldRender {
     Render,
     ForceRenderNow,
     Skip,
 }
 
+/// Errors returned by `App::try_start`.
+#[derive(Debug)]
+pub enum StartError {
+    /// The window doesn't contain a document.
+    MissingDocument,
+    /// The root element cannot be found - see `GetElement`.
+    RootElementNotFound(String),
+    /// The query for the element with `base` tag failed.
+    BaseQueryFailed(JsValue),
+}
+
+impl fmt::Display for StartError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::MissingDocument => write!(f, "the window doesn't contain a document"),
+            Self::RootElementNotFound(error) => write!(f, "{}", error),
+            Self::BaseQueryFailed(error) => match error.as_string() {
+                Some(error) => write!(f, "the query for the `base` element failed: {}", error),
+                None => write!(f, "the query for the `base` element failed: {:?}", error),
+            },
+        }
+    }
+}
+
+impl std::error::Error for StartError {}
+
 pub struct App<Ms, Mdl, INodes>
 where
     Ms: 'static,
@@ -57,9 +114,9 @@ where
     INodes: IntoNodes<Ms>,
 {
     /// App configuration.
-    cfg: Rc<AppCfg<Ms, Mdl, INodes>>,
+    pub(crate) cfg: Rc<AppCfg<Ms, Mdl, INodes>>,
     /// Mutable app state.
-    data: Rc<AppData<Ms, Mdl>>,
+    pub(crate) data: Rc<AppData<Ms, Mdl>>,
 }
 
 impl<Ms, Mdl, INodes> fmt::Debug for App<Ms, Mdl, INodes>
@@ -132,7 +189,7 @@ where
     ///
     /// # Panics
     ///
-    /// Panics if the root element cannot be found.
+    /// Panics if the root element cannot be found. See `try_start`.
     ///
     // pub type UpdateFn<Ms, Mdl, INodes> = fn(Ms, &mut Mdl, &mut OrdersContainer<Ms, Mdl, INodes>);
     pub fn start(
@@ -141,74 +198,150 @@ where
         update: impl FnOnce(Ms, &mut Mdl, &mut OrdersContainer<Ms, Mdl, INodes>) + Clone + 'static,
         view: impl FnOnce(&Mdl) -> INodes + Clone + 'static,
     ) -> Self {
+        Self::try_start(root_element, init, update, view).expect("start app")
+    }
+
+    /// The same as `start`, but it returns an error instead of panicking
+    /// when the app cannot be mounted.
+    ///
+    /// Failed DOM operations while rendering (e.g. an invalid attribute name) don't stop the app.
+    /// They're caught by `error_boundary`s or sent to the app as `DomError` notifications.
+    /// Unhandled errors are logged to the console.
+    ///
+    /// # Errors
+    ///
+    /// Returns error if the document or the root element cannot be found.
+    pub fn try_start(
+        root_element: impl GetElement,
+        init: impl FnOnce(Url, &mut OrdersContainer<Ms, Mdl, INodes>) -> Mdl + 'static,
+        update: impl FnOnce(Ms, &mut Mdl, &mut OrdersContainer<Ms, Mdl, INodes>) + Clone + 'static,
+        view: impl FnOnce(&Mdl) -> INodes + Clone + 'static,
+    ) -> Result<Self, StartError> {
+        Self::builder(init, update, view).try_start(root_element)
+    }
+
+    /// Configure the app before starting - e.g. set the base path or the render mode.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    ///App::builder(init, update, view)
+    ///    .render_mode(RenderMode::Sync)
+    ///    .start("app");
+    /// ```
+    pub fn builder(
+        init: impl FnOnce(Url, &mut OrdersContainer<Ms, Mdl, INodes>) -> Mdl + 'static,
+        update: impl FnOnce(Ms, &mut Mdl, &mut OrdersContainer<Ms, Mdl, INodes>) + Clone + 'static,
+        view: impl FnOnce(&Mdl) -> INodes + Clone + 'static,
+    ) -> Builder<Ms, Mdl, INodes> {
+        Builder::new(init, update, view)
+    }
+
+    pub(crate) fn start_with_builder(
+        builder: Builder<Ms, Mdl, INodes>,
+        root_element: impl GetElement,
+    ) -> Result<Self, StartError> {
+        let document = util::window()
+            .document()
+            .ok_or(StartError::MissingDocument)?;
+
         // @TODO: Remove as soon as Webkit is fixed and older browsers are no longer in use.
         // https://github.com/seed-rs/seed/issues/241
         // https://bugs.webkit.org/show_bug.cgi?id=202881
-        std::mem::drop(util::document().query_selector("html"));
+        std::mem::drop(document.query_selector("html"));
 
         // Allows panic messages to output to the browser console.error.
         #[cfg(feature = "panic-hook")]
         console_error_panic_hook::set_once();
 
-        let base_path: Rc<[String]> = Rc::from(
-            util::document()
-                .query_selector("base")
-                .expect("query element with 'base' tag")
-                .and_then(|element| element.get_attribute("href"))
-                .and_then(|href| web_sys::Url::new_with_base(&href, DUMMY_BASE_URL).ok())
-                .map(|url| {
-                    url.pathname()
-                        .trim_matches('/')
-                        .split('/')
-                        .map(ToOwned::to_owned)
-                        .collect::<Vec<_>>()
-                })
-                .unwrap_or_default()
-                .as_slice(),
-        );
+        let base_path: Rc<[String]> = match builder.base_path {
+            Some(base_path) => Rc::from(base_path),
+            None => Rc::from(
+                document
+                    .query_selector("base")
+                    .map_err(StartError::BaseQueryFailed)?
+                    .and_then(|element| element.get_attribute("href"))
+                    .and_then(|href| web_sys::Url::new_with_base(&href, DUMMY_BASE_URL).ok())
+                    .map(|url| {
+                        url.pathname()
+                            .trim_matches('/')
+                            .split('/')
+                            .map(ToOwned::to_owned)
+                            .collect::<Vec<_>>()
+                    })
+                    .unwrap_or_default()
+                    .as_slice(),
+            ),
+        };
+
+        let mount_point = root_element
+            .get_element()
+            .map_err(StartError::RootElementNotFound)?;
 
         let app = Self {
             cfg: Rc::new(AppCfg {
-                document: util::window().document().expect("get window's document"),
-                mount_point: root_element.get_element().expect("get root element"),
-                update: Box::new(move |msg, model, orders| update.clone()(msg, model, orders)),
-                view: Box::new(move |model| view.clone()(model)),
+                document,
+                mount_point: mount_point.clone(),
+                update: builder.update,
+                view: builder.view,
                 base_path,
+                link_interception: builder.link_interception,
+                render_mode: builder.render_mode,
             }),
             data: Rc::new(AppData {
                 model: RefCell::new(None),
                 root_el: RefCell::new(None),
                 popstate_closure: RefCell::new(None),
                 hashchange_closure: RefCell::new(None),
+                link_listener_closure: RefCell::new(None),
                 window_event_handler_manager: RefCell::new(EventHandlerManager::new()),
                 sub_manager: RefCell::new(SubManager::new()),
                 msg_listeners: RefCell::new(Vec::new()),
                 scheduled_render_handle: RefCell::new(None),
                 after_next_render_callbacks: RefCell::new(Vec::new()),
                 render_info: Cell::new(None),
+                render_stats: Cell::new(None),
+                task_registry: TaskRegistry::new(),
+                unmounted: Cell::new(false),
+                replaying: Cell::new(false),
+                recorder: None,
+                persisters: RefCell::new(Vec::new()),
+                debounces: RefCell::new(HashMap::new()),
+                throttles: RefCell::new(HashMap::new()),
+                keyed_cmds: RefCell::new(HashMap::new()),
+                event_delegation: builder
+                    .event_delegation
+                    .then(|| EventDelegation::new(mount_point.clone().into())),
+                lifecycle_hooks: RefCell::new(Vec::new()),
             }),
         };
 
-        app.data.root_el.replace(Some(app.bootstrap_vdom()));
-
         let mut orders = OrdersContainer::new(app.clone());
 
-        let new_model = init(
+        let new_model = (builder.init)(
             Url::current().skip_base_path(&Rc::clone(&app.cfg.base_path)),
             &mut orders,
         );
         app.data.model.replace(Some(new_model));
 
-        routing::setup_popstate_listener(
-            enc!((app => s) move |closure| {
-                s.data.popstate_closure.replace(Some(closure));
-            }),
-            enc!((app => s) move |notification| s.notify_with_notification(notification)),
-            Rc::clone(&app.cfg.base_path),
-        );
-        routing::setup_link_listener(
-            enc!((app => s) move |notification| s.notify_with_notification(notification)),
-        );
+        if builder.handle_popstate {
+            routing::setup_popstate_listener(
+                enc!((app => s) move |closure| {
+                    s.data.popstate_closure.replace(Some(closure));
+                }),
+                enc!((app => s) move |notification| s.notify_with_notification(notification)),
+                Rc::clone(&app.cfg.base_path),
+            );
+        }
+        if let Some(target) = app.link_interception_target() {
+            routing::setup_link_listener(
+                &target,
+                enc!((app => s) move |closure| {
+                    s.data.link_listener_closure.replace(Some(closure));
+                }),
+                enc!((app => s) move |notification| s.notify_with_notification(notification)),
+            );
+        }
 
         orders.subscribe(enc!((app => s) move |url_requested| {
             routing::url_request_handler(
@@ -220,6 +353,60 @@ where
 
         app.process_effect_queue(orders.effects);
         app.rerender_vdom();
+        Ok(app)
+    }
+
+    /// Create the `App` without a browser - effects, cmds and streams are recorded
+    /// instead of processed. See `seed::testing::TestApp`.
+    pub(crate) fn start_headless(
+        url: Url,
+        init: impl FnOnce(Url, &mut OrdersContainer<Ms, Mdl, INodes>) -> Mdl + 'static,
+        update: impl FnOnce(Ms, &mut Mdl, &mut OrdersContainer<Ms, Mdl, INodes>) + Clone + 'static,
+        view: impl FnOnce(&Mdl) -> INodes + Clone + 'static,
+    ) -> Self {
+        let app = Self {
+            cfg: Rc::new(AppCfg {
+                // Headless app never touches the DOM.
+                document: JsValue::UNDEFINED.unchecked_into(),
+                mount_point: JsValue::UNDEFINED.unchecked_into(),
+                update: Box::new(move |msg, model, orders| update.clone()(msg, model, orders)),
+                view: Box::new(move |model| view.clone()(model)),
+                base_path: Rc::from(Vec::new()),
+                link_interception: LinkInterception::Disabled,
+                render_mode: RenderMode::default(),
+            }),
+            data: Rc::new(AppData {
+                model: RefCell::new(None),
+                root_el: RefCell::new(None),
+                popstate_closure: RefCell::new(None),
+                hashchange_closure: RefCell::new(None),
+                link_listener_closure: RefCell::new(None),
+                window_event_handler_manager: RefCell::new(EventHandlerManager::new()),
+                sub_manager: RefCell::new(SubManager::new()),
+                msg_listeners: RefCell::new(Vec::new()),
+                scheduled_render_handle: RefCell::new(None),
+                after_next_render_callbacks: RefCell::new(Vec::new()),
+                render_info: Cell::new(None),
+                render_stats: Cell::new(None),
+                task_registry: TaskRegistry::new(),
+                unmounted: Cell::new(false),
+                replaying: Cell::new(false),
+                recorder: Some(Recorder::new()),
+                persisters: RefCell::new(Vec::new()),
+                debounces: RefCell::new(HashMap::new()),
+                throttles: RefCell::new(HashMap::new()),
+                keyed_cmds: RefCell::new(HashMap::new()),
+                event_delegation: None,
+                lifecycle_hooks: RefCell::new(Vec::new()),
+            }),
+        };
+
+        let mut orders = OrdersContainer::new(app.clone());
+        let new_model = init(url, &mut orders);
+        app.data.model.replace(Some(new_model));
+
+        app.process_effect_queue(orders.effects);
+        app.schedule_render();
         app
     }
 
@@ -250,116 +437,164 @@ where
         self.process_effect_queue(queue);
     }
 
-    pub(crate) fn process_effect_queue(&self, mut queue: VecDeque<Effect<Ms>>) {
-        if std::thread::panicking() {
+    /// Measure `update` and `view` calls and DOM patching (or hydrating) and count DOM operations.
+    ///
+    /// The stats are available in `RenderInfo::stats` (see `Orders::after_next_render`).
+    /// `update` calls are aggregated per render - their count, total and the slowest duration.
+    /// Each call is recorded as a `performance.measure` entry for browser dev tools -
+    /// `seed:update` per message, `seed:view` and `seed:patch` per render.
+    pub fn set_instrumentation(&self, enabled: bool) {
+        self.data.render_stats.set(if enabled {
+            Some(RenderStats::default())
+        } else {
+            None
+        });
+    }
+
+    /// Call `f` as the phase `name` - see `set_instrumentation`.
+    /// Returns `f`'s output and its duration.
+    fn measure<T>(&self, name: &str, f: impl FnOnce() -> T) -> (T, f64) {
+        if self.data.render_stats.get().is_none() || self.data.recorder.is_some() {
+            return (f(), 0.);
+        }
+        let performance = window().performance().expect("get `Performance`");
+        instrumentation::measure(&performance, name, f)
+    }
+
+    /// Detach the app from the mount point and stop it.
+    ///
+    /// All DOM nodes created by the app and all listeners installed by the app are removed,
+    /// a scheduled render is cancelled, cmds and streams are dropped and the model is dropped.
+    /// Further `update` and `notify` calls are no-ops.
+    pub fn unmount(&self) {
+        if self.data.unmounted.replace(true) {
             return;
         }
 
-        while let Some(effect) = queue.pop_front() {
-            match effect {
-                Effect::Msg(msg) => {
-                    let mut new_effects = self.process_queue_message(msg);
-                    queue.append(&mut new_effects);
-                }
-                Effect::Notification(notification) => {
-                    let mut new_effects = self.process_queue_notification(&notification);
-                    queue.append(&mut new_effects);
-                }
-                Effect::TriggeredHandler(handler) => {
-                    let mut new_effects = self.process_queue_message(handler());
-                    queue.append(&mut new_effects);
-                }
+        self.cancel_scheduled_render();
+        self.data.task_registry.abort_all();
+        self.data.debounces.replace(HashMap::new());
+        self.data.throttles.replace(HashMap::new());
+        self.data.keyed_cmds.replace(HashMap::new());
+        if let Some(recorder) = &self.data.recorder {
+            recorder.clear_tasks();
+        }
+
+        // Drop the model first - it may contain `CmdHandle`s, `StreamHandle`s and `SubHandle`s.
+        // It's borrowed when `unmount` is called from `update` - see `process_queue_message`.
+        if let Ok(mut model) = self.data.model.try_borrow_mut() {
+            // Don't lose debounced saves.
+            self.flush_persisted(model.as_ref());
+            self.data.persisters.replace(Vec::new());
+            model.take();
+        }
+
+        if let Some(closure) = self.data.popstate_closure.replace(None) {
+            routing::remove_popstate_listener(&closure);
+        }
+        if let Some(closure) = self.data.hashchange_closure.replace(None) {
+            routing::remove_hashchange_listener(&closure);
+        }
+        if let Some(closure) = self.data.link_listener_closure.replace(None) {
+            if let Some(target) = self.link_interception_target() {
+                routing::remove_link_listener(&target, &closure);
             }
         }
-    }
+        // Listeners are removed on drop.
+        self.data
+            .window_event_handler_manager
+            .replace(EventHandlerManager::new());
+        self.data.sub_manager.replace(SubManager::new());
+        self.data.msg_listeners.replace(Vec::new());
+        self.data.after_next_render_callbacks.replace(Vec::new());
+        if let Some(event_delegation) = &self.data.event_delegation {
+            event_delegation.clear();
+        }
 
-    /// Bootstrap the dom at startup with the vdom by taking over all children of the mount point and
-    /// replacing them with the vdom.
-    fn bootstrap_vdom(&self) -> El<Ms> {
-        // "new" name is for consistency with `update` function.
-        // this section parent is a placeholder, so we can iterate over children
-        // in a way consistent with patching code.
-        let mut new = El::empty(Tag::Placeholder);
+        self.data.lifecycle_hooks.replace(Vec::new());
+        if let Some(root_el) = self.data.root_el.replace(None) {
+            // Messages are ignored - the app is unmounted.
+            let mut hooks = Vec::new();
+            for child in &root_el.children {
+                lifecycle::queue_subtree_hooks(child, Lifecycle::Remove, &mut hooks);
+            }
+            hooks.into_iter().for_each(|hook| drop(hook.call()));
 
-        // Map the DOM's elements onto the virtual DOM.
-        // Construct a vdom from the root element. Subsequently strip the workspace so that we
-        // can recreate it later - this is a kind of simple way to avoid missing nodes (but
-        // not entirely correct).
-        // TODO: 1) Please refer to [issue #277](https://github.com/seed-rs/seed/issues/277)
-        let mut dom_nodes: El<Ms> = (&self.cfg.mount_point).into();
-        #[cfg(debug_assertions)]
-        dom_nodes.warn_about_script_tags();
-
-        dom_nodes.strip_ws_nodes_from_self_and_children();
-
-        // Replace the root dom with a placeholder tag and move the children from the root element
-        // to the newly created root. Uses `Placeholder` to mimic update logic.
-        new.children = dom_nodes.children;
-
-        // Recreate the needed nodes.
-        // TODO: Please refer to [issue #277](https://github.com/seed-rs/seed/issues/277)
-        // TODO: Look into how the 0.7 API changes removing of MountType takeover check interact
-        // with the uses of `rerender_vdom` and `bootstrap_vdom`
-        virtual_dom_bridge::assign_ws_nodes_to_el(&util::document(), &mut new);
-
-        // Remove all old elements. We'll swap them out with the newly created elements later.
-        // This maneuver will effectively allow us to remove everything in the mount and thus
-        // takeover the mount point.
-        while let Some(child) = self.cfg.mount_point.first_child() {
-            self.cfg
-                .mount_point
-                .remove_child(&child)
-                .expect("No problem removing node from parent.");
-        }
-
-        // Attach all top-level elements to the mount point if present. This means that we have
-        // effectively taken full control of everything within the mounting element.
-        for child in &mut new.children {
-            match child {
-                Node::Element(child_el) => {
-                    virtual_dom_bridge::attach_el_and_children(
-                        child_el,
-                        &self.cfg.mount_point,
-                        &self.mailbox(),
-                    );
+            for child in &root_el.children {
+                virtual_dom_bridge::remove_portals(child);
+                if let Some(node_ws) = child.node_ws() {
+                    virtual_dom_bridge::remove_node(node_ws, &self.cfg.mount_point);
                 }
-                Node::Text(top_child_text) => {
-                    virtual_dom_bridge::attach_text_node(top_child_text, &self.cfg.mount_point);
-                }
-                Node::Empty | Node::NoChange => (),
             }
         }
+    }
+
+    pub(crate) fn process_effect_queue(&self, mut queue: VecDeque<Effect<Ms>>) {
+        if std::thread::panicking() || self.data.unmounted.get() {
+            return;
+        }
+
+        // Headless app processes effects step by step on demand.
+        if let Some(recorder) = &self.data.recorder {
+            recorder.record_effects(queue);
+            return;
+        }
 
-        new
+        while let Some(effect) = queue.pop_front() {
+            let mut new_effects = self.process_effect(effect);
+            queue.append(&mut new_effects);
+        }
+    }
+
+    pub(crate) fn process_effect(&self, effect: Effect<Ms>) -> VecDeque<Effect<Ms>> {
+        if self.data.unmounted.get() {
+            return VecDeque::new();
+        }
+        match effect {
+            Effect::Msg(msg) => self.process_queue_message(msg),
+            Effect::Notification(notification) => self.process_queue_notification(&notification),
+            Effect::TriggeredHandler(handler) => self.process_queue_message(handler()),
+        }
     }
 
-    fn rerender_vdom(&self) {
-        if std::thread::panicking() {
+    pub(crate) fn rerender_vdom(&self) {
+        if std::thread::panicking() || self.data.unmounted.get() {
             return;
         }
 
-        let new_render_timestamp = window().performance().expect("get `Performance`").now();
+        let new_render_timestamp = if self.data.recorder.is_some() {
+            0.
+        } else {
+            window().performance().expect("get `Performance`").now()
+        };
 
         // Create a new vdom: The top element, and all its children. Does not yet
         // have associated web_sys elements.
         let mut new = El::empty(Tag::Placeholder);
-        new.children = (self.cfg.view)(self.data.model.borrow().as_ref().unwrap()).into_nodes();
-
-        let old = self
-            .data
-            .root_el
-            .borrow_mut()
-            .take()
-            .expect("missing root element");
-
-        patch::patch_els(
-            &self.cfg.document,
-            &self.mailbox(),
-            &self.clone(),
-            &self.cfg.mount_point,
-            old.children.into_iter(),
-            new.children.iter_mut(),
-        );
+        let (children, view_duration) = self.measure("seed:view", || {
+            (self.cfg.view)(self.data.model.borrow().as_ref().unwrap()).into_nodes()
+        });
+        new.children = children;
+
+        // The first render hydrates the prerendered content of the mount point.
+        let old = self.data.root_el.borrow_mut().take();
+        let (patch_duration, errors) = dom_error::collect(|| {
+            let ((), patch_duration) = self.measure("seed:patch", || match old {
+                // Headless app only keeps the rendered nodes.
+                _ if self.data.recorder.is_some() => (),
+                Some(old) => patch::patch_els(
+                    &self.cfg.document,
+                    &self.mailbox(),
+                    &self.clone(),
+                    &self.cfg.mount_point,
+                    None,
+                    old.children.into_iter(),
+                    new.children.iter_mut(),
+                ),
+                None => self.hydrate(&mut new),
+            });
+            (patch_duration, self.catch_dom_errors(&mut new))
+        });
 
         // Now that we've re-rendered, replace our stored El with the new one;
         // it will be used as the old El next time.
@@ -367,32 +602,113 @@ where
 
         // Execute `after_next_render_callbacks`.
 
+        let stats = self.data.render_stats.get().map(|mut stats| {
+            stats.view_duration = view_duration;
+            stats.patch_duration = patch_duration;
+            self.data.render_stats.set(Some(RenderStats::default()));
+            stats
+        });
+
         let render_info = match self.data.render_info.take() {
             Some(old_render_info) => RenderInfo {
                 timestamp: new_render_timestamp,
                 timestamp_delta: Some(new_render_timestamp - old_render_info.timestamp),
+                stats,
             },
             None => RenderInfo {
                 timestamp: new_render_timestamp,
                 timestamp_delta: None,
+                stats,
             },
         };
         self.data.render_info.set(Some(render_info));
 
-        self.process_effect_queue(
+        let mut effects = self.dom_error_effects(errors);
+        effects.extend(
+            self.data
+                .lifecycle_hooks
+                .replace(Vec::new())
+                .into_iter()
+                .map(|hook| Effect::TriggeredHandler(Box::new(move || hook.call()))),
+        );
+        effects.extend(
             self.data
                 .after_next_render_callbacks
                 .replace(Vec::new())
                 .into_iter()
-                .map(|callback| Effect::TriggeredHandler(Box::new(move || callback(render_info))))
-                .collect(),
+                .map(|callback| Effect::TriggeredHandler(Box::new(move || callback(render_info)))),
+        );
+        self.process_effect_queue(effects);
+    }
+
+    /// Let error boundaries in the rendered `root` handle DOM errors reported while rendering.
+    /// Returns unhandled errors.
+    fn catch_dom_errors(&self, root: &mut El<Ms>) -> Vec<DomError> {
+        let errors = dom_error::take_reported();
+        if errors.is_empty() {
+            return errors;
+        }
+        let mut errors = boundary::catch_dom_errors(
+            &self.cfg.document,
+            &self.mailbox(),
+            &mut root.children,
+            errors,
+            &mut self.data.lifecycle_hooks.borrow_mut(),
+        );
+        // Errors in fallbacks.
+        errors.extend(dom_error::take_reported());
+        errors
+    }
+
+    /// Send errors to `DomError` subscribers or log them if there are no subscribers.
+    fn dom_error_effects(&self, errors: Vec<DomError>) -> VecDeque<Effect<Ms>> {
+        let mut effects = VecDeque::new();
+        for error in errors {
+            let handlers = self
+                .data
+                .sub_manager
+                .borrow_mut()
+                .notify(&Notification::new(error.clone()));
+            if handlers.is_empty() {
+                crate::error(error.to_string());
+            }
+            effects.extend(handlers.into_iter().map(Effect::TriggeredHandler));
+        }
+        effects
+    }
+
+    /// Adopts the DOM nodes in the mount point instead of recreating them.
+    ///
+    /// See [issue #277](https://github.com/seed-rs/seed/issues/277).
+    fn hydrate(&self, new: &mut El<Ms>) {
+        #[cfg(debug_assertions)]
+        if let Ok(Some(_)) = self.cfg.mount_point.query_selector("script") {
+            error!("Script tag found inside mount point! \
+                    Please check https://docs.rs/seed/latest/seed/app/builder/struct.Builder.html#examples");
+        }
+
+        let mut patch_counts = PatchCounts::default();
+        patch::hydrate_mount_point(
+            &self.cfg.document,
+            &self.mailbox(),
+            &self.cfg.mount_point,
+            &mut new.children,
+            &mut patch_counts,
         );
+        if let Some(mut stats) = self.data.render_stats.get() {
+            stats.patch_counts = patch_counts;
+            self.data.render_stats.set(Some(stats));
+        }
+        let queue = &mut self.data.lifecycle_hooks.borrow_mut();
+        for child in &new.children {
+            lifecycle::queue_subtree_hooks(child, Lifecycle::Insert, queue);
+        }
     }
 
     fn process_queue_notification(&self, notification: &Notification) -> VecDeque<Effect<Ms>> {
         self.data
             .sub_manager
-            .borrow()
+            .borrow_mut()
             .notify(notification)
             .into_iter()
             .map(Effect::TriggeredHandler)
@@ -407,11 +723,29 @@ where
                 (l)(&message);
             }
 
-            (self.cfg.update)(
-                message,
-                self.data.model.borrow_mut().as_mut().unwrap(),
-                &mut orders,
-            );
+            let ((), update_duration) = self.measure("seed:update", || {
+                (self.cfg.update)(
+                    message,
+                    self.data.model.borrow_mut().as_mut().unwrap(),
+                    &mut orders,
+                );
+            });
+            if let Some(mut stats) = self.data.render_stats.get() {
+                stats.record_update(update_duration);
+                self.data.render_stats.set(Some(stats));
+            }
+
+            if self.data.unmounted.get() {
+                self.flush_persisted(self.data.model.borrow().as_ref());
+                self.data.persisters.replace(Vec::new());
+                self.data.model.replace(None);
+                return VecDeque::new();
+            }
+            self.save_persisted();
+        }
+
+        if let Some(recorder) = &self.data.recorder {
+            recorder.record_render_decision(orders.should_render);
         }
 
         match orders.should_render {
@@ -426,26 +760,123 @@ where
     }
 
     fn schedule_render(&self) {
+        if self.data.unmounted.get() {
+            return;
+        }
+        if let Some(recorder) = &self.data.recorder {
+            recorder.render_scheduled.set(true);
+            return;
+        }
+
         let mut scheduled_render_handle = self.data.scheduled_render_handle.borrow_mut();
 
         if scheduled_render_handle.is_none() {
-            let cb = Closure::new(enclose!((self => s) move |_| {
-                s.data.scheduled_render_handle.borrow_mut().take();
-                s.rerender_vdom();
-            }));
+            match self.cfg.render_mode {
+                RenderMode::AnimationFrame => {
+                    let cb = Closure::new(enclose!((self => s) move |_| {
+                        s.data.scheduled_render_handle.borrow_mut().take();
+                        s.rerender_vdom();
+                    }));
+                    *scheduled_render_handle = Some(ScheduledRender::AnimationFrame(
+                        util::request_animation_frame(cb),
+                    ));
+                }
+                RenderMode::Microtask => {
+                    *scheduled_render_handle = Some(ScheduledRender::Microtask);
+                    spawn_local(enclose!((self => s) async move {
+                        // The render has been cancelled if the handle is missing.
+                        let scheduled = s.data.scheduled_render_handle.borrow_mut().take();
+                        if scheduled.is_some() {
+                            s.rerender_vdom();
+                        }
+                    }));
+                }
+                RenderMode::Sync => {
+                    drop(scheduled_render_handle);
+                    self.rerender_vdom();
+                }
+            }
+        }
+    }
 
-            *scheduled_render_handle = Some(util::request_animation_frame(cb));
+    /// The event target for the link interceptor - see `LinkInterception`.
+    fn link_interception_target(&self) -> Option<web_sys::EventTarget> {
+        match &self.cfg.link_interception {
+            LinkInterception::Document => Some(self.cfg.document.clone().into()),
+            LinkInterception::Container(container) => Some(container.clone().into()),
+            LinkInterception::Disabled => None,
         }
     }
 
-    fn cancel_scheduled_render(&self) {
+    pub(crate) fn cancel_scheduled_render(&self) {
+        if let Some(recorder) = &self.data.recorder {
+            recorder.render_scheduled.set(false);
+        }
         // Cancel animation frame request by dropping it.
         self.data.scheduled_render_handle.borrow_mut().take();
     }
 
+    pub(crate) fn perform_cmd(&self, cmd: impl Future<Output = ()> + 'static) {
+        if self.data.replaying.get() || self.data.unmounted.get() {
+            return;
+        }
+        match &self.data.recorder {
+            Some(recorder) => recorder.record_cmd(cmd.boxed_local()),
+            None => CmdManager::perform_cmd(cmd, &self.data.task_registry),
+        }
+    }
+
+    pub(crate) fn perform_cmd_with_handle(
+        &self,
+        cmd: impl Future<Output = ()> + 'static,
+    ) -> CmdHandle {
+        if self.data.replaying.get() || self.data.unmounted.get() {
+            return CmdManager::abortable(cmd).1;
+        }
+        match &self.data.recorder {
+            Some(recorder) => {
+                let (cmd, handle) = CmdManager::abortable(cmd);
+                recorder.record_cmd(cmd.boxed_local());
+                handle
+            }
+            None => CmdManager::perform_cmd_with_handle(cmd, &self.data.task_registry),
+        }
+    }
+
+    pub(crate) fn stream(&self, stream: impl Stream<Item = ()> + 'static) {
+        if self.data.replaying.get() || self.data.unmounted.get() {
+            return;
+        }
+        match &self.data.recorder {
+            Some(recorder) => {
+                recorder.record_stream(StreamManager::into_future(stream).boxed_local())
+            }
+            None => StreamManager::stream(stream, &self.data.task_registry),
+        }
+    }
+
+    pub(crate) fn stream_with_handle(
+        &self,
+        stream: impl Stream<Item = ()> + 'static,
+    ) -> StreamHandle {
+        if self.data.replaying.get() || self.data.unmounted.get() {
+            return StreamManager::abortable(stream).1;
+        }
+        match &self.data.recorder {
+            Some(recorder) => {
+                let (stream, handle) = StreamManager::abortable(stream);
+                recorder.record_stream(stream.boxed_local());
+                handle
+            }
+            None => StreamManager::stream_with_handle(stream, &self.data.task_registry),
+        }
+    }
+
     pub fn mailbox(&self) -> Mailbox<Ms> {
-        Mailbox::new(enclose!((self => s) move |option_message| {
+        let mut mailbox = Mailbox::new(enclose!((self => s) move |option_message| {
             s.update_with_option(option_message);
-        }))
+        }));
+        mailbox.event_delegation = self.data.event_delegation.clone();
+        mailbox
     }
 }
@@ -0,0 +1,308 @@
+//! Easing curves, tweens and springs driven by `streams::animation_frame`.
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//!Msg::Open => {
+//!    model.tween = Tween::new(model.height, 300., 250.).easing(Easing::EaseOutCubic);
+//!    model.frames = Some(orders.stream_with_handle(streams::animation_frame(Msg::OnFrame)));
+//!}
+//!Msg::OnFrame(frame) => {
+//!    if let Some(msg) = model.tween.animate(&mut model.height, frame.delta(), || Msg::Opened) {
+//!        orders.send_msg(msg);
+//!    }
+//!}
+//!Msg::Opened => model.frames = None,
+//! ```
+
+// ------ FrameInfo ------
+
+/// An animation frame - see `streams::animation_frame`.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct FrameInfo {
+    /// The frame time in milliseconds - see `performance.now()`.
+    pub timestamp: f64,
+    /// The difference from the previous frame. It's `None` for the first frame.
+    pub timestamp_delta: Option<f64>,
+}
+
+impl FrameInfo {
+    /// `timestamp_delta` in milliseconds or `0` for the first frame.
+    pub fn delta(&self) -> f64 {
+        self.timestamp_delta.unwrap_or_default()
+    }
+}
+
+// ------ Animation ------
+
+pub trait Animation {
+    /// Move the animation forward by `delta_ms` milliseconds and return the current value.
+    fn advance(&mut self, delta_ms: f64) -> f64;
+
+    fn value(&self) -> f64;
+
+    fn is_finished(&self) -> bool;
+
+    /// Advance the animation, write its value to `field`
+    /// and return the message from `on_finish` when the animation has just finished.
+    fn animate<Ms>(
+        &mut self,
+        field: &mut f64,
+        delta_ms: f64,
+        on_finish: impl FnOnce() -> Ms,
+    ) -> Option<Ms> {
+        let was_finished = self.is_finished();
+        *field = self.advance(delta_ms);
+        if !was_finished && self.is_finished() {
+            Some(on_finish())
+        } else {
+            None
+        }
+    }
+}
+
+// ------ Easing ------
+
+/// Maps the animation progress (`0.0` - `1.0`) to the value progress.
+#[derive(Copy, Clone, Debug, Default)]
+pub enum Easing {
+    #[default]
+    Linear,
+    EaseInQuad,
+    EaseOutQuad,
+    EaseInOutQuad,
+    EaseInCubic,
+    EaseOutCubic,
+    EaseInOutCubic,
+    Custom(fn(f64) -> f64),
+}
+
+impl Easing {
+    pub fn apply(self, progress: f64) -> f64 {
+        let t = progress.clamp(0., 1.);
+        match self {
+            Self::Linear => t,
+            Self::EaseInQuad => t * t,
+            Self::EaseOutQuad => t * (2. - t),
+            Self::EaseInOutQuad => {
+                if t < 0.5 {
+                    2. * t * t
+                } else {
+                    -1. + (4. - 2. * t) * t
+                }
+            }
+            Self::EaseInCubic => t.powi(3),
+            Self::EaseOutCubic => 1. - (1. - t).powi(3),
+            Self::EaseInOutCubic => {
+                if t < 0.5 {
+                    4. * t.powi(3)
+                } else {
+                    1. - (-2. * t + 2.).powi(3) / 2.
+                }
+            }
+            Self::Custom(f) => f(t),
+        }
+    }
+}
+
+// ------ Tween ------
+
+/// Interpolates from one value to another in the given time.
+#[derive(Copy, Clone, Debug)]
+pub struct Tween {
+    from: f64,
+    to: f64,
+    duration_ms: f64,
+    elapsed_ms: f64,
+    easing: Easing,
+}
+
+impl Tween {
+    pub fn new(from: f64, to: f64, duration_ms: f64) -> Self {
+        Self {
+            from,
+            to,
+            duration_ms,
+            elapsed_ms: 0.,
+            easing: Easing::default(),
+        }
+    }
+
+    pub const fn easing(mut self, easing: Easing) -> Self {
+        self.easing = easing;
+        self
+    }
+
+    /// Progress from `0.0` to `1.0`.
+    pub fn progress(&self) -> f64 {
+        if self.duration_ms <= 0. {
+            return 1.;
+        }
+        (self.elapsed_ms / self.duration_ms).min(1.)
+    }
+}
+
+impl Animation for Tween {
+    fn advance(&mut self, delta_ms: f64) -> f64 {
+        self.elapsed_ms += delta_ms.max(0.);
+        self.value()
+    }
+
+    fn value(&self) -> f64 {
+        self.from + (self.to - self.from) * self.easing.apply(self.progress())
+    }
+
+    fn is_finished(&self) -> bool {
+        self.progress() >= 1.
+    }
+}
+
+// ------ Spring ------
+
+/// A damped spring moving a value towards its target.
+///
+/// The default parameters (`stiffness: 170`, `damping: 26`, `mass: 1`)
+/// are fast without a visible bounce.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Spring {
+    value: f64,
+    velocity: f64,
+    target: f64,
+    stiffness: f64,
+    damping: f64,
+    mass: f64,
+    precision: f64,
+}
+
+impl Spring {
+    /// The longest simulation step in milliseconds - longer frames are split into more steps.
+    const MAX_STEP_MS: f64 = 1000. / 120.;
+
+    pub fn new(value: f64, target: f64) -> Self {
+        Self {
+            value,
+            velocity: 0.,
+            target,
+            stiffness: 170.,
+            damping: 26.,
+            mass: 1.,
+            precision: 0.01,
+        }
+    }
+
+    pub const fn stiffness(mut self, stiffness: f64) -> Self {
+        self.stiffness = stiffness;
+        self
+    }
+
+    pub const fn damping(mut self, damping: f64) -> Self {
+        self.damping = damping;
+        self
+    }
+
+    pub const fn mass(mut self, mass: f64) -> Self {
+        self.mass = mass;
+        self
+    }
+
+    /// The spring is at rest when both the distance to the target and the velocity
+    /// are smaller than `precision`. Default is `0.01`.
+    pub const fn precision(mut self, precision: f64) -> Self {
+        self.precision = precision;
+        self
+    }
+
+    /// Change the target while keeping the current value and velocity.
+    pub fn set_target(&mut self, target: f64) {
+        self.target = target;
+    }
+
+    pub const fn target(&self) -> f64 {
+        self.target
+    }
+
+    pub const fn velocity(&self) -> f64 {
+        self.velocity
+    }
+}
+
+impl Animation for Spring {
+    fn advance(&mut self, delta_ms: f64) -> f64 {
+        let mut remaining_ms = delta_ms.max(0.);
+        while remaining_ms > 0. && !self.is_finished() {
+            let step_ms = remaining_ms.min(Self::MAX_STEP_MS);
+            remaining_ms -= step_ms;
+
+            // Semi-implicit Euler.
+            let dt = step_ms / 1000.;
+            let spring_force = -self.stiffness * (self.value - self.target);
+            let damping_force = -self.damping * self.velocity;
+            self.velocity += (spring_force + damping_force) / self.mass * dt;
+            self.value += self.velocity * dt;
+        }
+        if self.is_finished() {
+            self.value = self.target;
+            self.velocity = 0.;
+        }
+        self.value
+    }
+
+    fn value(&self) -> f64 {
+        self.value
+    }
+
+    fn is_finished(&self) -> bool {
+        (self.value - self.target).abs() < self.precision && self.velocity.abs() < self.precision
+    }
+}
+
+// ------ ------ Tests ------ ------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wasm_bindgen_test::*;
+
+    #[wasm_bindgen_test]
+    fn easing_curves_start_at_0_and_end_at_1() {
+        for easing in &[
+            Easing::Linear,
+            Easing::EaseInQuad,
+            Easing::EaseOutQuad,
+            Easing::EaseInOutQuad,
+            Easing::EaseInCubic,
+            Easing::EaseOutCubic,
+            Easing::EaseInOutCubic,
+        ] {
+            assert!(easing.apply(0.).abs() < f64::EPSILON, "{:?}", easing);
+            assert!((easing.apply(1.) - 1.).abs() < f64::EPSILON, "{:?}", easing);
+            assert!((easing.apply(2.) - 1.).abs() < f64::EPSILON, "{:?}", easing);
+        }
+    }
+
+    #[wasm_bindgen_test]
+    fn tween_reports_completion_once() {
+        let mut tween = Tween::new(0., 100., 200.);
+        let mut field = 0.;
+
+        assert_eq!(tween.animate(&mut field, 50., || "done"), None);
+        assert!((field - 25.).abs() < f64::EPSILON);
+
+        assert_eq!(tween.animate(&mut field, 500., || "done"), Some("done"));
+        assert!((field - 100.).abs() < f64::EPSILON);
+
+        assert_eq!(tween.animate(&mut field, 16., || "done"), None);
+    }
+
+    #[wasm_bindgen_test]
+    fn spring_comes_to_rest_at_target() {
+        let mut spring = Spring::new(0., 100.);
+        let mut field = 0.;
+        let mut finished = None;
+        for _ in 0..300 {
+            finished = finished.or_else(|| spring.animate(&mut field, 16., || ()));
+        }
+        assert_eq!(finished, Some(()));
+        assert!((field - 100.).abs() < f64::EPSILON);
+    }
+}
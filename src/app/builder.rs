@@ -0,0 +1,159 @@
+use super::{App, GetElement, LinkInterception, OrdersContainer, RenderMode, StartError};
+use crate::browser::Url;
+use crate::virtual_dom::IntoNodes;
+
+/// Configures and starts the `App` - see `App::builder`.
+///
+/// # Example
+///
+/// ```rust,no_run
+///App::builder(init, update, view)
+///    .base_path("/admin")
+///    .link_interception(LinkInterception::Container(nav_element))
+///    .render_mode(RenderMode::Microtask)
+///    .start("app");
+/// ```
+#[allow(clippy::module_name_repetitions, clippy::type_complexity)]
+pub struct Builder<Ms, Mdl, INodes>
+where
+    Ms: 'static,
+    Mdl: 'static,
+    INodes: IntoNodes<Ms>,
+{
+    pub(crate) init: Box<dyn FnOnce(Url, &mut OrdersContainer<Ms, Mdl, INodes>) -> Mdl>,
+    pub(crate) update: Box<dyn Fn(Ms, &mut Mdl, &mut OrdersContainer<Ms, Mdl, INodes>)>,
+    pub(crate) view: Box<dyn Fn(&Mdl) -> INodes>,
+    pub(crate) base_path: Option<Vec<String>>,
+    pub(crate) link_interception: LinkInterception,
+    pub(crate) render_mode: RenderMode,
+    pub(crate) handle_popstate: bool,
+    pub(crate) event_delegation: bool,
+}
+
+impl<Ms, Mdl, INodes> Builder<Ms, Mdl, INodes>
+where
+    Ms: 'static,
+    Mdl: 'static,
+    INodes: IntoNodes<Ms> + 'static,
+{
+    pub(crate) fn new(
+        init: impl FnOnce(Url, &mut OrdersContainer<Ms, Mdl, INodes>) -> Mdl + 'static,
+        update: impl FnOnce(Ms, &mut Mdl, &mut OrdersContainer<Ms, Mdl, INodes>) + Clone + 'static,
+        view: impl FnOnce(&Mdl) -> INodes + Clone + 'static,
+    ) -> Self {
+        Self {
+            init: Box::new(init),
+            update: Box::new(move |msg, model, orders| update.clone()(msg, model, orders)),
+            view: Box::new(move |model| view.clone()(model)),
+            base_path: None,
+            link_interception: LinkInterception::default(),
+            render_mode: RenderMode::default(),
+            handle_popstate: true,
+            event_delegation: false,
+        }
+    }
+
+    /// Set the base path instead of reading it from the `<base href="...">` element.
+    ///
+    /// The base path is removed from urls passed to the app - e.g. `/admin/users`
+    /// is passed as `/users` when the base path is `/admin`.
+    pub fn base_path(mut self, base_path: &str) -> Self {
+        self.base_path = Some(
+            base_path
+                .trim_matches('/')
+                .split('/')
+                .filter(|segment| !segment.is_empty())
+                .map(ToOwned::to_owned)
+                .collect(),
+        );
+        self
+    }
+
+    /// Choose which clicks on links are sent to the app as `subs::UrlRequested`.
+    /// Default is `LinkInterception::Document`.
+    pub fn link_interception(mut self, link_interception: LinkInterception) -> Self {
+        self.link_interception = link_interception;
+        self
+    }
+
+    /// Choose when scheduled renders are performed. Default is `RenderMode::AnimationFrame`.
+    pub fn render_mode(mut self, render_mode: RenderMode) -> Self {
+        self.render_mode = render_mode;
+        self
+    }
+
+    /// Set to `false` when the embedding page owns the browser history -
+    /// `popstate` events won't be sent to the app as `subs::UrlChanged`.
+    pub fn handle_popstate(mut self, handle_popstate: bool) -> Self {
+        self.handle_popstate = handle_popstate;
+        self
+    }
+
+    /// Set to `true` to handle events of elements by one listener per event type
+    /// attached to the mount point (and portal targets) instead of a listener per element.
+    /// It reduces the number of closures created and dropped while rendering large lists.
+    ///
+    /// - Handlers are called from the target to the mount point by a listener on the mount point.
+    ///   `event.current_target()` is the mount point.
+    /// - The listener handles bubbling events in the bubble phase - delegated handlers are called
+    ///   after all not delegated listeners on the way (e.g. of the target, but also
+    ///   of the target's ancestors) and `event.stop_propagation()` in those listeners skips them.
+    ///   Events that don't bubble (e.g. `focus`) are handled in the capture phase - before
+    ///   listeners of the target.
+    /// - `event.stop_propagation()` in a delegated handler skips delegated handlers
+    ///   of the ancestors.
+    /// - Handlers with `ListenerOptions` (e.g. `ev(Ev::TouchMove, ..).passive()`) and window
+    ///   events are not delegated.
+    pub fn event_delegation(mut self, event_delegation: bool) -> Self {
+        self.event_delegation = event_delegation;
+        self
+    }
+
+    /// Start the app - see `App::start`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the root element cannot be found.
+    pub fn start(self, root_element: impl GetElement) -> App<Ms, Mdl, INodes> {
+        self.try_start(root_element).expect("start app")
+    }
+
+    /// Start the app - see `App::try_start`.
+    ///
+    /// # Errors
+    ///
+    /// Returns error if the document or the root element cannot be found.
+    pub fn try_start(
+        self,
+        root_element: impl GetElement,
+    ) -> Result<App<Ms, Mdl, INodes>, StartError> {
+        App::start_with_builder(self, root_element)
+    }
+}
+
+// ------ ------ Tests ------ ------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::*;
+    use wasm_bindgen_test::*;
+
+    fn init(_: Url, _: &mut impl Orders<()>) {}
+
+    fn view(_: &()) -> Node<()> {
+        empty![]
+    }
+
+    #[wasm_bindgen_test]
+    fn base_path_is_split_into_segments() {
+        let builder = App::builder(init, |_, _, _| (), view).base_path("/admin/panel/");
+        assert_eq!(
+            builder.base_path,
+            Some(vec!["admin".to_owned(), "panel".to_owned()])
+        );
+
+        let builder = App::builder(init, |_, _, _| (), view).base_path("/");
+        assert_eq!(builder.base_path, Some(Vec::new()));
+    }
+}
@@ -14,4 +14,35 @@ where
     pub(crate) update: Box<dyn Fn(Ms, &mut Mdl, &mut OrdersContainer<Ms, Mdl, INodes>)>,
     pub(crate) view: Box<dyn Fn(&Mdl) -> INodes>,
     pub(crate) base_path: Rc<[String]>,
+    pub(crate) link_interception: LinkInterception,
+    pub(crate) render_mode: RenderMode,
+}
+
+// ------ RenderMode ------
+
+/// When scheduled renders are performed - see `app::Builder::render_mode`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub enum RenderMode {
+    /// Render before the next repaint (`requestAnimationFrame`).
+    #[default]
+    AnimationFrame,
+    /// Render in a microtask - i.e. as soon as the current task is finished.
+    Microtask,
+    /// Render immediately after each `update` - useful in tests.
+    Sync,
+}
+
+// ------ LinkInterception ------
+
+/// Clicks on relative links intercepted and sent to the app as `subs::UrlRequested`
+/// - see `app::Builder::link_interception`.
+#[derive(Debug, Clone, Default)]
+pub enum LinkInterception {
+    /// Intercept clicks on links in the whole document.
+    #[default]
+    Document,
+    /// Intercept only clicks on links inside the given element.
+    Container(web_sys::Element),
+    /// Leave all clicks to the browser.
+    Disabled,
 }
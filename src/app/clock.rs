@@ -0,0 +1,283 @@
+//! Time source for Seed timers - `cmds::timeout`, `streams::interval`, `streams::backoff`, etc.
+//!
+//! Timers use browser timers unless a `VirtualClock` is installed.
+
+use crate::browser::util::window;
+use futures::stream::Stream;
+use futures::task::{Context, Poll, Waker};
+use gloo_timers::future::{IntervalStream, TimeoutFuture};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fmt;
+use std::future::Future;
+use std::pin::Pin;
+use std::rc::Rc;
+
+thread_local! {
+    static VIRTUAL_TIME: RefCell<Option<Rc<RefCell<VirtualTime>>>> = const { RefCell::new(None) };
+}
+
+fn virtual_time() -> Option<Rc<RefCell<VirtualTime>>> {
+    VIRTUAL_TIME.with(|time| time.borrow().clone())
+}
+
+/// The current time in milliseconds - `performance.now()` or the `VirtualClock` time.
+pub fn now() -> f64 {
+    match virtual_time() {
+        #[allow(clippy::cast_precision_loss)]
+        Some(time) => time.borrow().now_ms as f64,
+        None => window().performance().expect("get `Performance`").now(),
+    }
+}
+
+/// Resolves after `ms` milliseconds.
+///
+/// # Example
+///
+/// ```rust,no_run
+///orders.perform_cmd(async {
+///    clock::sleep(500).await;
+///    Msg::Hide
+///});
+/// ```
+pub fn sleep(ms: u32) -> Sleep {
+    match virtual_time() {
+        Some(time) => {
+            let deadline = time.borrow().now_ms + u64::from(ms);
+            Sleep::Virtual(VirtualSleep::new(time, deadline))
+        }
+        None => Sleep::Real { ms, timeout: None },
+    }
+}
+
+/// Streams `()` every `ms` milliseconds.
+pub fn interval(ms: u32) -> Interval {
+    match virtual_time() {
+        Some(time) => {
+            let deadline = time.borrow().now_ms + u64::from(ms);
+            Interval::Virtual {
+                period: u64::from(ms),
+                sleep: VirtualSleep::new(time, deadline),
+            }
+        }
+        None => Interval::Real(IntervalStream::new(ms)),
+    }
+}
+
+// ------ VirtualClock ------
+
+/// Replaces browser timers with manually advanced time - e.g. in `TestApp` tests.
+///
+/// Timers created while the clock is installed use the virtual time.
+/// The clock is uninstalled on drop.
+///
+/// # Example
+///
+/// ```rust,no_run
+///let clock = VirtualClock::install();
+///let app = TestApp::start(Url::new(), init, update, view);
+///app.update(Msg::Search("seed"));  // `orders.debounce("search", 300, ...)`
+///
+///clock.advance(300);
+///app.run_cmds();
+///assert_eq!(app.queued_msgs(), vec![Msg::Searched("seed")]);
+/// ```
+#[derive(Debug)]
+pub struct VirtualClock {
+    time: Rc<RefCell<VirtualTime>>,
+}
+
+impl VirtualClock {
+    /// Install the clock for the current thread. Virtual time starts at `0`.
+    pub fn install() -> Self {
+        let time = Rc::new(RefCell::new(VirtualTime::default()));
+        VIRTUAL_TIME.with(|virtual_time| virtual_time.replace(Some(Rc::clone(&time))));
+        Self { time }
+    }
+
+    /// The virtual time in milliseconds.
+    pub fn now(&self) -> u64 {
+        self.time.borrow().now_ms
+    }
+
+    /// Move the time forward and wake timers with passed deadlines.
+    ///
+    /// Timers fire when their tasks are polled - e.g. by `TestApp::run_cmds`.
+    pub fn advance(&self, ms: u64) {
+        let wakers = {
+            let mut time = self.time.borrow_mut();
+            time.now_ms += ms;
+            let now_ms = time.now_ms;
+            let mut wakers = Vec::new();
+            time.timers.retain(|_, (deadline, waker)| {
+                if *deadline <= now_ms {
+                    wakers.push(waker.clone());
+                    return false;
+                }
+                true
+            });
+            wakers
+        };
+        wakers.into_iter().for_each(Waker::wake);
+    }
+}
+
+impl Drop for VirtualClock {
+    fn drop(&mut self) {
+        VIRTUAL_TIME.with(|virtual_time| {
+            let mut virtual_time = virtual_time.borrow_mut();
+            if matches!(&*virtual_time, Some(time) if Rc::ptr_eq(time, &self.time)) {
+                *virtual_time = None;
+            }
+        });
+    }
+}
+
+#[derive(Debug, Default)]
+struct VirtualTime {
+    now_ms: u64,
+    next_timer_id: u64,
+    /// Deadlines and wakers of pending timers.
+    timers: HashMap<u64, (u64, Waker)>,
+}
+
+// ------ Sleep ------
+
+/// See `sleep`.
+pub enum Sleep {
+    /// The browser timer is started on the first poll.
+    Real {
+        ms: u32,
+        timeout: Option<TimeoutFuture>,
+    },
+    Virtual(VirtualSleep),
+}
+
+impl Future for Sleep {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context) -> Poll<()> {
+        match self.get_mut() {
+            Self::Real { ms, timeout } => {
+                Pin::new(timeout.get_or_insert_with(|| TimeoutFuture::new(*ms))).poll(cx)
+            }
+            Self::Virtual(sleep) => sleep.poll(cx),
+        }
+    }
+}
+
+impl fmt::Debug for Sleep {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Real { ms, .. } => write!(f, "Sleep::Real({})", ms),
+            Self::Virtual(sleep) => write!(f, "Sleep::Virtual({})", sleep.deadline),
+        }
+    }
+}
+
+// ------ Interval ------
+
+/// See `interval`.
+pub enum Interval {
+    Real(IntervalStream),
+    Virtual { period: u64, sleep: VirtualSleep },
+}
+
+impl Stream for Interval {
+    type Item = ();
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<()>> {
+        match self.get_mut() {
+            Self::Real(interval) => Pin::new(interval).poll_next(cx),
+            Self::Virtual { period, sleep } => match sleep.poll(cx) {
+                Poll::Ready(()) => {
+                    // The next deadline is based on the previous one to not drift.
+                    let deadline = sleep.deadline + u64::max(*period, 1);
+                    *sleep = VirtualSleep::new(Rc::clone(&sleep.time), deadline);
+                    Poll::Ready(Some(()))
+                }
+                Poll::Pending => Poll::Pending,
+            },
+        }
+    }
+}
+
+impl fmt::Debug for Interval {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Real(_) => f.write_str("Interval::Real"),
+            Self::Virtual { period, .. } => write!(f, "Interval::Virtual({})", period),
+        }
+    }
+}
+
+// ------ VirtualSleep ------
+
+pub struct VirtualSleep {
+    time: Rc<RefCell<VirtualTime>>,
+    id: u64,
+    deadline: u64,
+}
+
+impl VirtualSleep {
+    fn new(time: Rc<RefCell<VirtualTime>>, deadline: u64) -> Self {
+        let id = {
+            let mut time = time.borrow_mut();
+            time.next_timer_id += 1;
+            time.next_timer_id
+        };
+        Self { time, id, deadline }
+    }
+
+    fn poll(&mut self, cx: &mut Context) -> Poll<()> {
+        let mut time = self.time.borrow_mut();
+        if time.now_ms >= self.deadline {
+            time.timers.remove(&self.id);
+            return Poll::Ready(());
+        }
+        time.timers
+            .insert(self.id, (self.deadline, cx.waker().clone()));
+        Poll::Pending
+    }
+}
+
+impl Drop for VirtualSleep {
+    fn drop(&mut self) {
+        self.time.borrow_mut().timers.remove(&self.id);
+    }
+}
+
+// ------ ------ Tests ------ ------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::stream::StreamExt;
+    use futures::task::noop_waker_ref;
+
+    #[test]
+    fn sleep_resolves_when_time_is_advanced() {
+        let clock = VirtualClock::install();
+        let mut cx = Context::from_waker(noop_waker_ref());
+        let mut sleep = sleep(100);
+
+        assert_eq!(Pin::new(&mut sleep).poll(&mut cx), Poll::Pending);
+        clock.advance(99);
+        assert_eq!(Pin::new(&mut sleep).poll(&mut cx), Poll::Pending);
+        clock.advance(1);
+        assert_eq!(Pin::new(&mut sleep).poll(&mut cx), Poll::Ready(()));
+    }
+
+    #[test]
+    fn interval_ticks_for_each_passed_period() {
+        let clock = VirtualClock::install();
+        let mut cx = Context::from_waker(noop_waker_ref());
+        let mut interval = interval(100);
+
+        clock.advance(250);
+        assert_eq!(interval.poll_next_unpin(&mut cx), Poll::Ready(Some(())));
+        assert_eq!(interval.poll_next_unpin(&mut cx), Poll::Ready(Some(())));
+        assert_eq!(interval.poll_next_unpin(&mut cx), Poll::Pending);
+        assert!((now() - 250.).abs() < f64::EPSILON);
+    }
+}
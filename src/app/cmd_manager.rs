@@ -1,3 +1,4 @@
+use super::task_registry::TaskRegistry;
 use futures::future::{abortable, AbortHandle, Future, FutureExt};
 use wasm_bindgen_futures::spawn_local;
 
@@ -6,17 +7,27 @@ use wasm_bindgen_futures::spawn_local;
 pub(crate) struct CmdManager;
 
 impl CmdManager {
-    pub fn perform_cmd(cmd: impl Future<Output = ()> + 'static) {
+    pub fn perform_cmd(cmd: impl Future<Output = ()> + 'static, tasks: &TaskRegistry) {
         // The future is "leaked" into the JS world as a promise.
         // It's always executed on the next JS tick to prevent stack overflow.
-        spawn_local(cmd);
+        spawn_local(tasks.register(cmd));
     }
 
-    pub fn perform_cmd_with_handle(cmd: impl Future<Output = ()> + 'static) -> CmdHandle {
+    pub fn perform_cmd_with_handle(
+        cmd: impl Future<Output = ()> + 'static,
+        tasks: &TaskRegistry,
+    ) -> CmdHandle {
+        let (cmd, handle) = Self::abortable(cmd);
+        Self::perform_cmd(cmd, tasks);
+        handle
+    }
+
+    pub fn abortable(
+        cmd: impl Future<Output = ()> + 'static,
+    ) -> (impl Future<Output = ()> + 'static, CmdHandle) {
         let (cmd, handle) = abortable(cmd);
         // Ignore the error when the future is aborted. I.e. just stop the future execution.
-        spawn_local(cmd.map(move |_| ()));
-        CmdHandle(handle)
+        (cmd.map(move |_| ()), CmdHandle(handle))
     }
 }
 
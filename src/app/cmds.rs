@@ -1,5 +1,5 @@
+use super::clock;
 use futures::future::{Future, FutureExt};
-use gloo_timers::future::TimeoutFuture;
 
 // @TODO add fetch cmd?
 
@@ -24,5 +24,5 @@ pub fn timeout<MsU>(
     ms: u32,
     handler: impl FnOnce() -> MsU + Clone + 'static,
 ) -> impl Future<Output = MsU> {
-    TimeoutFuture::new(ms).map(move |_| handler())
+    clock::sleep(ms).map(move |_| handler())
 }
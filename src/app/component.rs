@@ -0,0 +1,465 @@
+//! Stateful components with their own `Model`, `Msg`, `update` and `view`.
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//!// ------ counter.rs ------
+//!
+//!pub struct Counter;
+//!
+//!pub enum Msg {
+//!    Increment,
+//!}
+//!
+//!pub struct Changed(pub i32);
+//!
+//!impl Component for Counter {
+//!    type Model = i32;
+//!    type Msg = Msg;
+//!    type Output = Changed;
+//!
+//!    fn init(_: &mut impl Orders<Msg>) -> i32 {
+//!        0
+//!    }
+//!
+//!    fn update(msg: Msg, model: &mut i32, _: &mut impl Orders<Msg>) -> Option<Changed> {
+//!        match msg {
+//!            Msg::Increment => *model += 1,
+//!        }
+//!        Some(Changed(*model))
+//!    }
+//!
+//!    fn view(model: &i32) -> Node<Msg> {
+//!        button![model, ev(Ev::Click, |_| Msg::Increment)]
+//!    }
+//!}
+//!
+//!// ------ lib.rs ------
+//!
+//!struct Model {
+//!    counter: Instance<Counter, Msg>,
+//!}
+//!
+//!enum Msg {
+//!    Counter(counter::Msg),
+//!}
+//!
+//!fn init(_: Url, orders: &mut impl Orders<Msg>) -> Model {
+//!    Model {
+//!        counter: Instance::new(orders, Msg::Counter),
+//!    }
+//!}
+//!
+//!fn update(msg: Msg, model: &mut Model, orders: &mut impl Orders<Msg>) {
+//!    match msg {
+//!        Msg::Counter(msg) => {
+//!            if let Some(counter::Changed(value)) = model.counter.update(msg, orders) {
+//!                log!("Counter changed", value);
+//!            }
+//!        }
+//!    }
+//!}
+//!
+//!fn view(model: &Model) -> Node<Msg> {
+//!    div![model.counter.view()]
+//!}
+//! ```
+
+use super::{
+    task_registry::TaskRegistry, App, CmdHandle, Orders, OrdersProxy, RenderInfo, StreamHandle,
+    SubHandle, SubOptions,
+};
+use crate::app::MessageMapper;
+use crate::virtual_dom::{IntoNodes, Node};
+use futures::future::{Future, FutureExt};
+use futures::stream::{Stream, StreamExt};
+use std::{any::Any, cell::RefCell, convert::identity, rc::Rc};
+
+// ------ Component ------
+
+pub trait Component: 'static {
+    type Model: 'static;
+    type Msg: 'static;
+    /// Returned to the parent from `update` - e.g. `Changed(i32)`. Use `()` if there is no output.
+    type Output: 'static;
+
+    fn init(orders: &mut impl Orders<Self::Msg>) -> Self::Model;
+
+    fn update(
+        msg: Self::Msg,
+        model: &mut Self::Model,
+        orders: &mut impl Orders<Self::Msg>,
+    ) -> Option<Self::Output>;
+
+    fn view(model: &Self::Model) -> Node<Self::Msg>;
+}
+
+// ------ Scope ------
+
+/// Cmds, streams and subscriptions created by the component.
+#[derive(Default)]
+struct Scope {
+    tasks: TaskRegistry,
+    sub_handles: RefCell<Vec<SubHandle>>,
+}
+
+impl Drop for Scope {
+    fn drop(&mut self) {
+        self.tasks.abort_all();
+    }
+}
+
+// ------ Instance ------
+
+/// A component living in the parent's model.
+///
+/// Messages of the component are mapped to the parent's messages by `to_msg`.
+/// Cmds, streams and subscriptions created through component's `Orders` are dropped together
+/// with the instance.
+///
+/// _Note:_ Cmds, streams and subscriptions created through `orders.proxy(..)` inside the component
+/// aren't bound to the instance.
+pub struct Instance<C: Component, Ms> {
+    model: C::Model,
+    to_msg: Rc<dyn Fn(C::Msg) -> Ms>,
+    // Dropped after the model.
+    scope: Scope,
+}
+
+impl<C: Component, Ms: 'static> Instance<C, Ms> {
+    /// Create a new instance by `Component::init`.
+    pub fn new(orders: &mut impl Orders<Ms>, to_msg: impl Fn(C::Msg) -> Ms + 'static) -> Self {
+        let to_msg: Rc<dyn Fn(C::Msg) -> Ms> = Rc::new(to_msg);
+        let scope = Scope::default();
+        let model = C::init(&mut ComponentOrders::new(orders, &to_msg, &scope));
+        Self {
+            model,
+            to_msg,
+            scope,
+        }
+    }
+
+    /// Invoke `Component::update`. Returns component's output.
+    pub fn update(&mut self, msg: C::Msg, orders: &mut impl Orders<Ms>) -> Option<C::Output> {
+        let mut orders = ComponentOrders::new(orders, &self.to_msg, &self.scope);
+        C::update(msg, &mut self.model, &mut orders)
+    }
+
+    /// Invoke `Component::view` and map its messages.
+    pub fn view(&self) -> Node<Ms> {
+        let to_msg = Rc::clone(&self.to_msg);
+        C::view(&self.model).map_msg(move |msg| to_msg(msg))
+    }
+
+    pub const fn model(&self) -> &C::Model {
+        &self.model
+    }
+
+    pub fn model_mut(&mut self) -> &mut C::Model {
+        &mut self.model
+    }
+}
+
+// ------ ComponentOrders ------
+
+/// `OrdersProxy` that binds cmds, streams and subscriptions to the component's `Scope`.
+struct ComponentOrders<'a, Ms, AppMs, Mdl, INodes>
+where
+    AppMs: 'static,
+    Mdl: 'static,
+    INodes: IntoNodes<AppMs>,
+{
+    proxy: OrdersProxy<'a, Ms, AppMs, Mdl, INodes>,
+    scope: &'a Scope,
+}
+
+impl<'a, Ms, AppMs, Mdl, INodes> ComponentOrders<'a, Ms, AppMs, Mdl, INodes>
+where
+    Ms: 'static,
+    AppMs: 'static,
+    INodes: IntoNodes<AppMs> + 'static,
+{
+    fn new<ParentMs: 'static>(
+        orders: &'a mut impl Orders<ParentMs, AppMs = AppMs, Mdl = Mdl, INodes = INodes>,
+        to_msg: &Rc<dyn Fn(Ms) -> ParentMs>,
+        scope: &'a Scope,
+    ) -> Self {
+        let to_msg = Rc::clone(to_msg);
+        Self {
+            proxy: orders.proxy(move |msg| to_msg(msg)),
+            scope,
+        }
+    }
+
+    /// Map cmd's output to `AppMs` and send it to the app.
+    #[allow(clippy::redundant_closure)]
+    fn scoped_cmd<MsU: 'static>(
+        &self,
+        cmd: impl Future<Output = MsU> + 'static,
+    ) -> impl Future<Output = ()> {
+        let f = self.msg_mapper();
+        let app = self.clone_app();
+
+        let handler = map_callback_return_to_option_ms!(
+            dyn Fn(MsU) -> Option<Ms>,
+            identity,
+            "Cmds can return only Msg, Option<Msg> or ()!",
+            Box
+        );
+
+        let cmd = cmd.map(move |msg| app.mailbox().send(handler(msg).map(|msg| f(msg))));
+        self.scope.tasks.register(cmd)
+    }
+
+    /// Map stream's items to `AppMs` and send them to the app.
+    #[allow(clippy::redundant_closure)]
+    fn scoped_stream<MsU: 'static>(
+        &self,
+        stream: impl Stream<Item = MsU> + 'static,
+    ) -> impl Stream<Item = ()> {
+        let f = self.msg_mapper();
+        let app = self.clone_app();
+
+        let handler = map_callback_return_to_option_ms!(
+            dyn Fn(MsU) -> Option<Ms>,
+            identity,
+            "Streams can stream only Msg, Option<Msg> or ()!",
+            Box
+        );
+
+        let stream = stream.map(move |msg| app.mailbox().send(handler(msg).map(|msg| f(msg))));
+        self.scope.tasks.register_stream(stream)
+    }
+}
+
+impl<'a, Ms, AppMs, Mdl, INodes> Orders<Ms> for ComponentOrders<'a, Ms, AppMs, Mdl, INodes>
+where
+    Ms: 'static,
+    AppMs: 'static,
+    INodes: IntoNodes<AppMs> + 'static,
+{
+    type AppMs = AppMs;
+    type Mdl = Mdl;
+    type INodes = INodes;
+
+    fn proxy<ChildMs: 'static>(
+        &mut self,
+        f: impl FnOnce(ChildMs) -> Ms + 'static + Clone,
+    ) -> OrdersProxy<'_, ChildMs, AppMs, Mdl, INodes> {
+        self.proxy.proxy(f)
+    }
+
+    fn render(&mut self) -> &mut Self {
+        self.proxy.render();
+        self
+    }
+
+    fn force_render_now(&mut self) -> &mut Self {
+        self.proxy.force_render_now();
+        self
+    }
+
+    fn skip(&mut self) -> &mut Self {
+        self.proxy.skip();
+        self
+    }
+
+    fn notify(&mut self, message: impl Any + Clone) -> &mut Self {
+        self.proxy.notify(message);
+        self
+    }
+
+    fn notify_retained(&mut self, message: impl Any + Clone) -> &mut Self {
+        self.proxy.notify_retained(message);
+        self
+    }
+
+    fn send_msg(&mut self, msg: Ms) -> &mut Self {
+        self.proxy.send_msg(msg);
+        self
+    }
+
+    fn perform_cmd<MsU: 'static>(&mut self, cmd: impl Future<Output = MsU> + 'static) -> &mut Self {
+        let cmd = self.scoped_cmd(cmd);
+        self.clone_app().perform_cmd(cmd);
+        self
+    }
+
+    fn perform_cmd_with_handle<MsU: 'static>(
+        &mut self,
+        cmd: impl Future<Output = MsU> + 'static,
+    ) -> CmdHandle {
+        let cmd = self.scoped_cmd(cmd);
+        self.clone_app().perform_cmd_with_handle(cmd)
+    }
+
+    fn clone_app(&self) -> App<Self::AppMs, Self::Mdl, Self::INodes> {
+        self.proxy.clone_app()
+    }
+
+    fn msg_mapper(&self) -> Rc<dyn Fn(Ms) -> Self::AppMs> {
+        self.proxy.msg_mapper()
+    }
+
+    fn after_next_render<MsU: 'static>(
+        &mut self,
+        callback: impl FnOnce(RenderInfo) -> MsU + 'static,
+    ) -> &mut Self {
+        self.proxy.after_next_render(callback);
+        self
+    }
+
+    fn subscribe_with_options<MsU: 'static, SubMs: 'static + Clone>(
+        &mut self,
+        handler: impl FnOnce(SubMs) -> MsU + Clone + 'static,
+        options: SubOptions<SubMs>,
+    ) -> &mut Self {
+        let sub_handle = self
+            .proxy
+            .subscribe_with_options_and_handle(handler, options);
+        self.scope.sub_handles.borrow_mut().push(sub_handle);
+        self
+    }
+
+    fn subscribe_with_options_and_handle<MsU: 'static, SubMs: 'static + Clone>(
+        &mut self,
+        handler: impl FnOnce(SubMs) -> MsU + Clone + 'static,
+        options: SubOptions<SubMs>,
+    ) -> SubHandle {
+        self.proxy
+            .subscribe_with_options_and_handle(handler, options)
+    }
+
+    fn stream<MsU: 'static>(&mut self, stream: impl Stream<Item = MsU> + 'static) -> &mut Self {
+        let stream = self.scoped_stream(stream);
+        self.clone_app().stream(stream);
+        self
+    }
+
+    fn stream_with_handle<MsU: 'static>(
+        &mut self,
+        stream: impl Stream<Item = MsU> + 'static,
+    ) -> StreamHandle {
+        let stream = self.scoped_stream(stream);
+        self.clone_app().stream_with_handle(stream)
+    }
+}
+
+// ------ ------ Tests ------ ------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::*;
+    use crate::testing::TestApp;
+    use futures::future;
+
+    // ------ Counter ------
+
+    struct Counter;
+
+    #[derive(Clone, Debug, PartialEq)]
+    enum CounterMsg {
+        Increment,
+        IncrementLater,
+        Reset,
+    }
+
+    #[derive(Clone)]
+    struct Reset;
+
+    impl Component for Counter {
+        type Model = u32;
+        type Msg = CounterMsg;
+        type Output = u32;
+
+        fn init(orders: &mut impl Orders<CounterMsg>) -> u32 {
+            orders.subscribe(|Reset| CounterMsg::Reset);
+            0
+        }
+
+        fn update(
+            msg: CounterMsg,
+            model: &mut u32,
+            orders: &mut impl Orders<CounterMsg>,
+        ) -> Option<u32> {
+            match msg {
+                CounterMsg::Increment => *model += 1,
+                CounterMsg::IncrementLater => {
+                    orders.perform_cmd(future::ready(CounterMsg::Increment));
+                }
+                CounterMsg::Reset => *model = 0,
+            }
+            Some(*model)
+        }
+
+        fn view(model: &u32) -> Node<CounterMsg> {
+            button![model, ev(Ev::Click, |_| CounterMsg::Increment)]
+        }
+    }
+
+    // ------ App ------
+
+    #[derive(Clone, Debug, PartialEq)]
+    enum Msg {
+        Counter(CounterMsg),
+        RemoveCounter,
+    }
+
+    struct Model {
+        counter: Option<Instance<Counter, Msg>>,
+        last_output: Option<u32>,
+    }
+
+    fn init(_: Url, orders: &mut impl Orders<Msg>) -> Model {
+        Model {
+            counter: Some(Instance::new(orders, Msg::Counter)),
+            last_output: None,
+        }
+    }
+
+    fn update(msg: Msg, model: &mut Model, orders: &mut impl Orders<Msg>) {
+        match msg {
+            Msg::Counter(msg) => {
+                if let Some(counter) = &mut model.counter {
+                    model.last_output = counter.update(msg, orders);
+                }
+            }
+            Msg::RemoveCounter => model.counter = None,
+        }
+    }
+
+    fn view(model: &Model) -> Node<Msg> {
+        div![model.counter.as_ref().map(Instance::view)]
+    }
+
+    #[test]
+    fn messages_and_output_are_mapped() {
+        let app = TestApp::start(Url::new(), init, update, view);
+        app.update(Msg::Counter(CounterMsg::Increment));
+        assert_eq!(app.model().last_output, Some(1));
+        assert_eq!(app.view()[0].to_html(), "<div><button>1</button></div>");
+
+        app.notify(Reset);
+        app.settle();
+        assert_eq!(app.model().last_output, Some(0));
+
+        app.update(Msg::Counter(CounterMsg::IncrementLater));
+        assert_eq!(app.run_cmds(), 1);
+        assert_eq!(app.queued_msgs(), vec![Msg::Counter(CounterMsg::Increment)]);
+    }
+
+    #[test]
+    fn dropped_instance_drops_subscriptions_and_cmds() {
+        let app = TestApp::start(Url::new(), init, update, view);
+        app.update(Msg::Counter(CounterMsg::IncrementLater));
+        app.update(Msg::RemoveCounter);
+
+        assert_eq!(app.run_cmds(), 1);
+        assert!(app.queued_msgs().is_empty());
+
+        app.notify(Reset);
+        assert_eq!(app.settle(), 1);
+        assert!(app.queued_msgs().is_empty());
+    }
+}
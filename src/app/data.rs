@@ -1,21 +1,57 @@
-use super::{RenderInfo, SubManager};
+use super::{
+    keyed_cmds::KeyedCmds, persistence::Persister, rate_limit::Throttle,
+    task_registry::TaskRegistry, CmdHandle, RenderInfo, RenderStats, SubManager,
+};
 use crate::browser::util;
-use crate::virtual_dom::{El, EventHandlerManager};
-use std::cell::{Cell, RefCell};
+use crate::testing::Recorder;
+use crate::virtual_dom::{lifecycle::HookCall, El, EventDelegation, EventHandlerManager};
+use std::{
+    cell::{Cell, RefCell},
+    collections::HashMap,
+    rc::Rc,
+};
 use wasm_bindgen::closure::Closure;
 
 type StoredPopstate = RefCell<Option<Closure<dyn FnMut(web_sys::Event)>>>;
 
+/// A pending render - dropping it cancels the render.
+pub(crate) enum ScheduledRender {
+    AnimationFrame(#[allow(dead_code)] util::RequestAnimationFrameHandle),
+    /// The scheduled microtask renders only if this value is still stored in `AppData`.
+    Microtask,
+}
+
 #[allow(clippy::type_complexity, dead_code)]
 pub(crate) struct AppData<Ms: 'static, Mdl> {
     pub model: RefCell<Option<Mdl>>,
     pub(crate) root_el: RefCell<Option<El<Ms>>>,
     pub popstate_closure: StoredPopstate,
     pub hashchange_closure: StoredPopstate,
+    pub link_listener_closure: StoredPopstate,
     pub window_event_handler_manager: RefCell<EventHandlerManager<Ms>>,
     pub sub_manager: RefCell<SubManager<Ms>>,
-    pub msg_listeners: RefCell<Vec<Box<dyn Fn(&Ms)>>>,
-    pub scheduled_render_handle: RefCell<Option<util::RequestAnimationFrameHandle>>,
+    pub msg_listeners: RefCell<Vec<Rc<dyn Fn(&Ms)>>>,
+    pub scheduled_render_handle: RefCell<Option<ScheduledRender>>,
     pub after_next_render_callbacks: RefCell<Vec<Box<dyn FnOnce(RenderInfo) -> Option<Ms>>>>,
     pub render_info: Cell<Option<RenderInfo>>,
+    /// Stats for the next `RenderInfo`; `Some` when the instrumentation is enabled.
+    pub(crate) render_stats: Cell<Option<RenderStats>>,
+    pub(crate) task_registry: TaskRegistry,
+    pub(crate) unmounted: Cell<bool>,
+    /// `Devtools` replay is running - cmds and streams are dropped.
+    pub(crate) replaying: Cell<bool>,
+    /// `Some` for headless apps - see `seed::testing::TestApp`.
+    pub(crate) recorder: Option<Recorder<Ms>>,
+    /// See `Orders::persist`.
+    pub(crate) persisters: RefCell<Vec<Rc<Persister<Mdl>>>>,
+    /// Debounce timers by their keys - see `Orders::debounce`.
+    pub(crate) debounces: RefCell<HashMap<String, CmdHandle>>,
+    /// Throttle intervals by their keys - see `Orders::throttle`.
+    pub(crate) throttles: RefCell<HashMap<String, Throttle<Ms>>>,
+    /// See `Orders::perform_keyed_cmd`.
+    pub(crate) keyed_cmds: RefCell<HashMap<String, KeyedCmds<Ms>>>,
+    /// See `app::Builder::event_delegation`.
+    pub(crate) event_delegation: Option<EventDelegation<Ms>>,
+    /// Lifecycle hooks queued while patching - see `on_insert`.
+    pub(crate) lifecycle_hooks: RefCell<Vec<HookCall<Ms>>>,
 }
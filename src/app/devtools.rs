@@ -0,0 +1,562 @@
+//! Message log, model snapshots and time-travel for debugging.
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//!thread_local! {
+//!    // `Devtools` records messages until it's dropped - keep it alive.
+//!    static DEVTOOLS: RefCell<Option<Devtools<Msg, Model, Node<Msg>>>> = RefCell::new(None);
+//!}
+//!
+//!#[wasm_bindgen(start)]
+//!pub fn start() {
+//!    let app = App::start("app", init, update, view);
+//!    DEVTOOLS.with(|devtools| devtools.replace(Some(Devtools::new(&app))));
+//!}
+//!
+//!// Later - e.g. in an event handler of a hidden debug panel:
+//!DEVTOOLS.with(|devtools| {
+//!    let devtools = devtools.borrow();
+//!    let devtools = devtools.as_ref().expect("started devtools");
+//!    devtools.step_back();
+//!    let session = devtools.export().expect("export session");
+//!    // ... and in another browser / a test:
+//!    devtools.import(&session).expect("import session");
+//!});
+//! ```
+
+use super::{App, AppData, OrdersContainer};
+use crate::virtual_dom::IntoNodes;
+use js_sys::JSON;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_wasm_bindgen as swb;
+use std::{
+    cell::RefCell,
+    collections::VecDeque,
+    rc::{Rc, Weak},
+};
+use wasm_bindgen::JsValue;
+
+/// Version of the exported session format.
+const SESSION_VERSION: u32 = 1;
+
+/// The default maximum number of recorded messages - see `Devtools::with_capacity`.
+pub const DEFAULT_CAPACITY: usize = 1000;
+
+/// Convenient type alias.
+pub type Result<T> = std::result::Result<T, DevtoolsError>;
+
+// ------ DevtoolsError ------
+
+#[allow(clippy::module_name_repetitions)]
+#[derive(Debug)]
+pub enum DevtoolsError {
+    SerdeError(swb::Error),
+    ParseError(JsValue),
+    StringifyError(JsValue),
+    UnsupportedVersion(u32),
+    /// The session doesn't contain the initial model, so it cannot be replayed.
+    MissingInitialModel,
+}
+
+impl From<swb::Error> for DevtoolsError {
+    fn from(v: swb::Error) -> Self {
+        Self::SerdeError(v)
+    }
+}
+
+// ------ Entry ------
+
+/// Recorded message.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Entry<Ms> {
+    pub msg: Ms,
+    /// Milliseconds since the UNIX epoch - see `js_sys::Date::now`.
+    pub timestamp: f64,
+}
+
+// ------ Session ------
+
+#[derive(Serialize, Deserialize)]
+struct Session<Ms, Mdl> {
+    version: u32,
+    initial_model: Mdl,
+    entries: Vec<Entry<Ms>>,
+}
+
+// ------ Log ------
+
+struct Log<Ms, Mdl> {
+    entries: VecDeque<Entry<Ms>>,
+    /// `snapshots[i]` is the model after `i` messages.
+    /// The model after all messages is the app's model or `live_model` while time-traveling.
+    snapshots: VecDeque<Mdl>,
+    live_model: Option<Mdl>,
+    /// The number of applied messages.
+    position: usize,
+    /// The maximum number of entries - the oldest ones are forgotten.
+    capacity: usize,
+}
+
+impl<Ms, Mdl> Log<Ms, Mdl> {
+    /// Forget the oldest entries over `capacity`. The oldest remaining snapshot
+    /// becomes the initial model.
+    fn trim(&mut self) {
+        while self.entries.len() > self.capacity {
+            self.entries.pop_front();
+            self.snapshots.pop_front();
+            self.position = self.position.saturating_sub(1);
+        }
+    }
+}
+
+// ------ ListenerGuard ------
+
+/// Removes the message listener when the last `Devtools` clone is dropped.
+struct ListenerGuard<Ms: 'static, Mdl> {
+    app_data: Weak<AppData<Ms, Mdl>>,
+    listener: Rc<dyn Fn(&Ms)>,
+}
+
+impl<Ms, Mdl> Drop for ListenerGuard<Ms, Mdl> {
+    fn drop(&mut self) {
+        if let Some(app_data) = self.app_data.upgrade() {
+            app_data
+                .msg_listeners
+                .borrow_mut()
+                .retain(|listener| !Rc::ptr_eq(listener, &self.listener));
+        }
+    }
+}
+
+// ------ Devtools ------
+
+/// Records messages and model snapshots of the `App` and allows to travel in time.
+///
+/// A message sent while time-traveling discards the newer part of the log
+/// and the recording continues from the displayed model.
+///
+/// The recording stops when the last clone is dropped.
+pub struct Devtools<Ms, Mdl, INodes>
+where
+    Ms: 'static,
+    Mdl: 'static,
+    INodes: IntoNodes<Ms>,
+{
+    app: App<Ms, Mdl, INodes>,
+    log: Rc<RefCell<Log<Ms, Mdl>>>,
+    _listener_guard: Rc<ListenerGuard<Ms, Mdl>>,
+}
+
+impl<Ms, Mdl, INodes> Clone for Devtools<Ms, Mdl, INodes>
+where
+    INodes: IntoNodes<Ms>,
+{
+    fn clone(&self) -> Self {
+        Self {
+            app: self.app.clone(),
+            log: Rc::clone(&self.log),
+            _listener_guard: Rc::clone(&self._listener_guard),
+        }
+    }
+}
+
+impl<Ms, Mdl, INodes> Devtools<Ms, Mdl, INodes>
+where
+    Ms: Clone + 'static,
+    Mdl: Clone + 'static,
+    INodes: IntoNodes<Ms> + 'static,
+{
+    /// Start recording messages of the given `App`. At most `DEFAULT_CAPACITY` messages are kept.
+    pub fn new(app: &App<Ms, Mdl, INodes>) -> Self {
+        Self::with_capacity(app, DEFAULT_CAPACITY)
+    }
+
+    /// Start recording messages of the given `App`. At most `capacity` messages are kept -
+    /// the oldest ones are forgotten and the oldest remaining snapshot becomes the initial model.
+    pub fn with_capacity(app: &App<Ms, Mdl, INodes>, capacity: usize) -> Self {
+        let log = Rc::new(RefCell::new(Log {
+            entries: VecDeque::new(),
+            snapshots: VecDeque::new(),
+            live_model: None,
+            position: 0,
+            capacity,
+        }));
+
+        let app_data = Rc::downgrade(&app.data);
+        let weak_log = Rc::downgrade(&log);
+        let headless = app.data.recorder.is_some();
+
+        let listener: Rc<dyn Fn(&Ms)> = Rc::new(move |msg: &Ms| {
+            let (app_data, log) = match (app_data.upgrade(), weak_log.upgrade()) {
+                (Some(app_data), Some(log)) => (app_data, log),
+                _ => return,
+            };
+            let mut log = log.borrow_mut();
+
+            // Messages sent while time-traveling start a new branch.
+            let position = log.position;
+            log.entries.truncate(position);
+            log.snapshots.truncate(position);
+            log.live_model = None;
+
+            let model = app_data.model.borrow().as_ref().expect("get model").clone();
+            log.snapshots.push_back(model);
+            log.entries.push_back(Entry {
+                msg: msg.clone(),
+                // There is no JS in headless (test) apps.
+                timestamp: if headless { 0. } else { js_sys::Date::now() },
+            });
+            log.position = log.entries.len();
+            log.trim();
+        });
+        app.data
+            .msg_listeners
+            .borrow_mut()
+            .push(Rc::clone(&listener));
+
+        Self {
+            app: app.clone(),
+            log,
+            _listener_guard: Rc::new(ListenerGuard {
+                app_data: Rc::downgrade(&app.data),
+                listener,
+            }),
+        }
+    }
+
+    /// Recorded messages.
+    pub fn entries(&self) -> Vec<Entry<Ms>> {
+        self.log.borrow().entries.iter().cloned().collect()
+    }
+
+    /// The number of recorded messages.
+    pub fn len(&self) -> usize {
+        self.log.borrow().entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The number of applied messages. It's equal to `len` when the app isn't time-traveling.
+    pub fn position(&self) -> usize {
+        self.log.borrow().position
+    }
+
+    pub fn is_live(&self) -> bool {
+        self.position() == self.len()
+    }
+
+    /// The model after `position` messages.
+    pub fn snapshot(&self, position: usize) -> Option<Mdl> {
+        let log = self.log.borrow();
+        if let Some(snapshot) = log.snapshots.get(position) {
+            return Some(snapshot.clone());
+        }
+        if position == log.entries.len() {
+            return Some(match &log.live_model {
+                Some(live_model) => live_model.clone(),
+                None => self.app.data.model.borrow().as_ref()?.clone(),
+            });
+        }
+        None
+    }
+
+    /// Set the app's model to the snapshot after `position` messages and rerender.
+    ///
+    /// `position` is clamped to `len`.
+    pub fn jump_to(&self, position: usize) {
+        let position = position.min(self.len());
+        if position == self.position() {
+            return;
+        }
+        let model = if position == self.len() {
+            self.log.borrow_mut().live_model.take()
+        } else {
+            let mut log = self.log.borrow_mut();
+            let snapshot = match log.snapshots.get(position) {
+                Some(snapshot) => snapshot.clone(),
+                None => return,
+            };
+            if log.position == log.entries.len() {
+                log.live_model = self.app.data.model.borrow().clone();
+            }
+            Some(snapshot)
+        };
+        self.log.borrow_mut().position = position;
+        self.set_model(model);
+    }
+
+    /// Returns `false` if there is no older snapshot.
+    pub fn step_back(&self) -> bool {
+        let position = self.position();
+        if position == 0 {
+            return false;
+        }
+        self.jump_to(position - 1);
+        true
+    }
+
+    /// Returns `false` if there is no newer snapshot.
+    pub fn step_forward(&self) -> bool {
+        let position = self.position();
+        if position == self.len() {
+            return false;
+        }
+        self.jump_to(position + 1);
+        true
+    }
+
+    /// Stop time-traveling - i.e. jump to the newest snapshot.
+    pub fn go_live(&self) {
+        self.jump_to(self.len());
+    }
+
+    /// Forget recorded messages. The current model becomes the initial one.
+    pub fn clear(&self) {
+        self.go_live();
+        let mut log = self.log.borrow_mut();
+        log.entries.clear();
+        log.snapshots.clear();
+        log.position = 0;
+    }
+
+    /// Replay all recorded messages into a fresh copy of the initial model, recompute snapshots
+    /// and set the result as the app's model.
+    ///
+    /// _Note:_ Effects are suppressed during replay - messages and notifications sent through `Orders`,
+    /// cmds, streams, subscriptions and `after_next_render` callbacks are dropped.
+    pub fn replay(&self) {
+        let initial_model = match self.snapshot(0) {
+            Some(initial_model) => initial_model,
+            None => return,
+        };
+        self.replay_from(initial_model);
+    }
+
+    fn replay_from(&self, initial_model: Mdl) {
+        let entries = self.entries();
+        let mut model = initial_model;
+        let mut snapshots = VecDeque::with_capacity(entries.len());
+
+        let after_next_render_callbacks = self.app.data.after_next_render_callbacks.borrow().len();
+        self.app.data.replaying.set(true);
+        for entry in entries {
+            snapshots.push_back(model.clone());
+            let mut orders = OrdersContainer::new(self.app.clone());
+            (self.app.cfg.update)(entry.msg, &mut model, &mut orders);
+        }
+        self.app.data.replaying.set(false);
+        self.app
+            .data
+            .after_next_render_callbacks
+            .borrow_mut()
+            .truncate(after_next_render_callbacks);
+
+        {
+            let mut log = self.log.borrow_mut();
+            log.position = snapshots.len();
+            log.snapshots = snapshots;
+            log.live_model = None;
+            log.trim();
+        }
+        self.set_model(Some(model));
+    }
+
+    /// Export the initial model and recorded messages as JSON - e.g. to attach it to a bug report.
+    ///
+    /// # Errors
+    ///
+    /// Returns error if the session cannot be serialized.
+    pub fn export(&self) -> Result<String>
+    where
+        Ms: Serialize,
+        Mdl: Serialize,
+    {
+        let session = Session {
+            version: SESSION_VERSION,
+            initial_model: self.snapshot(0),
+            entries: self.entries(),
+        };
+        Ok(JSON::stringify(&swb::to_value(&session)?)
+            .map_err(DevtoolsError::StringifyError)?
+            .into())
+    }
+
+    /// Replace the log with the exported session and replay it.
+    ///
+    /// # Errors
+    ///
+    /// Returns error if the session cannot be deserialized, it has an unsupported version
+    /// or it doesn't contain the initial model. The log isn't changed then.
+    pub fn import(&self, session: &str) -> Result<()>
+    where
+        Ms: DeserializeOwned,
+        Mdl: DeserializeOwned,
+    {
+        let session = JSON::parse(session).map_err(DevtoolsError::ParseError)?;
+        let session: Session<Ms, Option<Mdl>> = swb::from_value(session)?;
+        if session.version != SESSION_VERSION {
+            return Err(DevtoolsError::UnsupportedVersion(session.version));
+        }
+        let initial_model = session
+            .initial_model
+            .ok_or(DevtoolsError::MissingInitialModel)?;
+        self.log.borrow_mut().entries = session.entries.into();
+        self.replay_from(initial_model);
+        Ok(())
+    }
+
+    fn set_model(&self, model: Option<Mdl>) {
+        if model.is_some() {
+            self.app.data.model.replace(model);
+            // Schedule rerender.
+            self.app.update_with_option(None);
+        }
+    }
+}
+
+// ------ ------ Tests ------ ------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::*;
+    use crate::testing::TestApp;
+    use wasm_bindgen_test::*;
+
+    #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+    enum Msg {
+        Add(i32),
+        Subscribe,
+    }
+
+    fn init(_: Url, _: &mut impl Orders<Msg>) -> i32 {
+        0
+    }
+
+    fn update(msg: Msg, model: &mut i32, orders: &mut impl Orders<Msg>) {
+        match msg {
+            Msg::Add(number) => {
+                *model += number;
+                orders.perform_cmd(async {});
+            }
+            Msg::Subscribe => {
+                orders.subscribe(Msg::Add);
+            }
+        }
+    }
+
+    fn view(model: &i32) -> Node<Msg> {
+        div![model]
+    }
+
+    #[test]
+    fn time_travel() {
+        let app = TestApp::start(Url::new(), init, update, view);
+        let devtools = Devtools::new(&app.clone_app());
+        app.update(Msg::Add(1));
+        app.update(Msg::Add(2));
+        app.update(Msg::Add(3));
+        assert_eq!(devtools.len(), 3);
+        assert_eq!(devtools.snapshot(0), Some(0));
+        assert_eq!(devtools.snapshot(2), Some(3));
+
+        assert!(devtools.step_back());
+        assert_eq!(*app.model(), 3);
+        devtools.jump_to(0);
+        assert_eq!(*app.model(), 0);
+        assert!(!devtools.step_back());
+        assert!(devtools.step_forward());
+        assert_eq!(*app.model(), 1);
+        devtools.go_live();
+        assert_eq!(*app.model(), 6);
+        assert!(devtools.is_live());
+
+        devtools.jump_to(1);
+        app.update(Msg::Add(10));
+        assert_eq!(*app.model(), 11);
+        assert_eq!(
+            devtools
+                .entries()
+                .into_iter()
+                .map(|entry| entry.msg)
+                .collect::<Vec<_>>(),
+            vec![Msg::Add(1), Msg::Add(10)]
+        );
+    }
+
+    #[test]
+    fn replay_suppresses_effects() {
+        let app = TestApp::start(Url::new(), init, update, view);
+        let devtools = Devtools::new(&app.clone_app());
+        app.update(Msg::Add(1));
+        app.update(Msg::Add(2));
+        assert_eq!(app.pending_cmds(), 2);
+
+        *app.model_mut() = 100;
+        devtools.replay();
+        assert_eq!(*app.model(), 3);
+        assert_eq!(app.pending_cmds(), 2);
+        assert_eq!(devtools.position(), 2);
+    }
+
+    #[test]
+    fn replay_doesnt_duplicate_subscriptions() {
+        let app = TestApp::start(Url::new(), init, update, view);
+        let devtools = Devtools::new(&app.clone_app());
+        app.update(Msg::Subscribe);
+
+        devtools.replay();
+        app.notify(5);
+        app.settle();
+        assert_eq!(*app.model(), 5);
+    }
+
+    #[test]
+    fn capacity_limits_log() {
+        let app = TestApp::start(Url::new(), init, update, view);
+        let devtools = Devtools::with_capacity(&app.clone_app(), 2);
+        app.update(Msg::Add(1));
+        app.update(Msg::Add(2));
+        app.update(Msg::Add(3));
+        assert_eq!(devtools.len(), 2);
+        assert_eq!(devtools.position(), 2);
+        assert_eq!(devtools.snapshot(0), Some(1));
+
+        devtools.jump_to(0);
+        assert_eq!(*app.model(), 1);
+        devtools.replay();
+        assert_eq!(*app.model(), 6);
+    }
+
+    #[test]
+    fn dropped_devtools_remove_listener() {
+        let app = TestApp::start(Url::new(), init, update, view);
+        let devtools = Devtools::new(&app.clone_app());
+        let devtools_clone = devtools.clone();
+        drop(devtools);
+        assert_eq!(app.clone_app().data.msg_listeners.borrow().len(), 1);
+        drop(devtools_clone);
+        assert!(app.clone_app().data.msg_listeners.borrow().is_empty());
+    }
+
+    // `JSON` isn't available in native tests.
+    #[wasm_bindgen_test]
+    fn import_requires_initial_model() {
+        let app = TestApp::start(Url::new(), init, update, view);
+        let devtools = Devtools::new(&app.clone_app());
+        app.update(Msg::Add(1));
+
+        let session =
+            r#"{"version":1,"initial_model":null,"entries":[{"msg":{"Add":5},"timestamp":0}]}"#;
+        assert!(matches!(
+            devtools.import(session),
+            Err(DevtoolsError::MissingInitialModel)
+        ));
+        assert_eq!(devtools.len(), 1);
+        devtools.jump_to(0);
+        assert_eq!(*app.model(), 0);
+    }
+}
@@ -0,0 +1,170 @@
+//! Opt-in performance instrumentation - see `App::set_instrumentation`.
+
+use crate::virtual_dom::patch::PatchCommand;
+use web_sys::Performance;
+
+// ------ RenderStats ------
+
+/// Measured phases of one render and of the updates since the previous render.
+///
+/// Updates are aggregated - use `performance.measure` entries `seed:update`
+/// in browser dev tools to inspect individual messages.
+///
+/// Durations are in milliseconds. They're always `0` in headless apps (`seed::testing::TestApp`).
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct RenderStats {
+    /// The number of `update` calls.
+    pub updates: u32,
+    /// The total duration of `update` calls.
+    pub update_duration: f64,
+    /// The duration of the slowest `update` call.
+    pub max_update_duration: f64,
+    /// The duration of the `view` call.
+    pub view_duration: f64,
+    /// The duration of patching (or hydrating) the DOM.
+    pub patch_duration: f64,
+    pub patch_counts: PatchCounts,
+}
+
+impl RenderStats {
+    pub(crate) fn record_update(&mut self, duration: f64) {
+        self.updates += 1;
+        self.update_duration += duration;
+        self.max_update_duration = self.max_update_duration.max(duration);
+    }
+}
+
+// ------ PatchCounts ------
+
+/// The number of DOM patch operations by their kind, including operations done while hydrating.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct PatchCounts {
+    pub append_el: u32,
+    pub append_text: u32,
+    pub insert_el: u32,
+    pub insert_text: u32,
+    /// Keyed children moved to their new positions.
+    pub move_node: u32,
+    pub append_portal: u32,
+    pub patch_portal: u32,
+    pub remove_portal: u32,
+    pub patch_el: u32,
+    pub patch_text: u32,
+    pub replace_el_by_el: u32,
+    pub replace_el_by_text: u32,
+    pub replace_text_by_el: u32,
+    pub remove_el: u32,
+    pub remove_text: u32,
+}
+
+impl PatchCounts {
+    /// The number of all operations.
+    pub const fn total(&self) -> u32 {
+        self.append_el
+            + self.append_text
+            + self.insert_el
+            + self.insert_text
+            + self.move_node
+            + self.append_portal
+            + self.patch_portal
+            + self.remove_portal
+            + self.patch_el
+            + self.patch_text
+            + self.replace_el_by_el
+            + self.replace_el_by_text
+            + self.replace_text_by_el
+            + self.remove_el
+            + self.remove_text
+    }
+
+    pub(crate) fn count<Ms>(&mut self, command: &PatchCommand<Ms>) {
+        let counter = match command {
+            PatchCommand::AppendEl { .. } => &mut self.append_el,
+            PatchCommand::AppendText { .. } => &mut self.append_text,
+            PatchCommand::InsertEl { .. } => &mut self.insert_el,
+            PatchCommand::InsertText { .. } => &mut self.insert_text,
+            PatchCommand::MoveNode { .. } => &mut self.move_node,
+            PatchCommand::AppendPortal { .. } => &mut self.append_portal,
+            PatchCommand::PatchPortal { .. } => &mut self.patch_portal,
+            PatchCommand::RemovePortal { .. } => &mut self.remove_portal,
+            PatchCommand::PatchEl { .. } => &mut self.patch_el,
+            PatchCommand::PatchText { .. } => &mut self.patch_text,
+            PatchCommand::ReplaceElByEl { .. } => &mut self.replace_el_by_el,
+            PatchCommand::ReplaceElByText { .. } => &mut self.replace_el_by_text,
+            PatchCommand::ReplaceTextByEl { .. } => &mut self.replace_text_by_el,
+            PatchCommand::RemoveEl { .. } => &mut self.remove_el,
+            PatchCommand::RemoveText { .. } => &mut self.remove_text,
+        };
+        *counter += 1;
+    }
+}
+
+/// Call `f` and add a `performance.measure` entry `name` for it.
+/// Returns `f`'s output and its duration.
+pub(crate) fn measure<T>(performance: &Performance, name: &str, f: impl FnOnce() -> T) -> (T, f64) {
+    let start_mark = format!("{}:start", name);
+    std::mem::drop(performance.mark(&start_mark));
+    let start = performance.now();
+
+    let output = f();
+
+    let duration = performance.now() - start;
+    std::mem::drop(performance.measure_with_start_mark(name, &start_mark));
+    performance.clear_marks_with_mark_name(&start_mark);
+    (output, duration)
+}
+
+// ------ ------ Tests ------ ------
+
+#[cfg(test)]
+mod tests {
+    use crate::prelude::*;
+    use crate::testing::TestApp;
+
+    #[derive(Clone, Debug)]
+    enum Msg {
+        Increment,
+    }
+
+    fn init(_: Url, _: &mut impl Orders<Msg>) -> i32 {
+        0
+    }
+
+    fn update(msg: Msg, model: &mut i32, _: &mut impl Orders<Msg>) {
+        match msg {
+            Msg::Increment => *model += 1,
+        }
+    }
+
+    fn view(model: &i32) -> Node<Msg> {
+        div![model]
+    }
+
+    #[test]
+    fn stats_are_collected_per_render() {
+        let app = TestApp::start(Url::new(), init, update, view);
+        app.clone_app().set_instrumentation(true);
+
+        app.update(Msg::Increment);
+        app.update(Msg::Increment);
+        app.render();
+        let stats = app.clone_app().data.render_info.get().unwrap().stats;
+        assert_eq!(stats.map(|stats| stats.updates), Some(2));
+
+        app.render();
+        let stats = app.clone_app().data.render_info.get().unwrap().stats;
+        assert_eq!(stats.map(|stats| stats.updates), Some(0));
+
+        app.clone_app().set_instrumentation(false);
+        app.update(Msg::Increment);
+        app.render();
+        assert!(app
+            .clone_app()
+            .data
+            .render_info
+            .get()
+            .unwrap()
+            .stats
+            .is_none());
+    }
+}
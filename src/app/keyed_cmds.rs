@@ -0,0 +1,218 @@
+//! Cmds grouped by keys with a concurrency policy - see `Orders::perform_keyed_cmd`.
+
+use super::{App, CmdHandle};
+use crate::virtual_dom::IntoNodes;
+use futures::future::{Future, FutureExt, LocalBoxFuture};
+use std::collections::{BTreeMap, VecDeque};
+
+// ------ CmdPolicy ------
+
+/// What happens when a keyed cmd is performed while other cmds with the same key are running.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum CmdPolicy {
+    /// Abort the running cmds (and drop the queued ones) and run the new cmd.
+    LatestWins,
+    /// Drop the new cmd.
+    FirstWins,
+    /// Run the new cmd when all previous cmds have finished - i.e. one at a time in order.
+    Queue,
+    /// Run at most N cmds at once; the other ones wait in a queue.
+    MaxParallel(usize),
+}
+
+impl CmdPolicy {
+    fn max_running(self) -> usize {
+        match self {
+            Self::LatestWins | Self::FirstWins | Self::Queue => 1,
+            Self::MaxParallel(max) => max.max(1),
+        }
+    }
+}
+
+// ------ KeyedCmds ------
+
+/// Running and waiting cmds with the same key.
+pub(crate) struct KeyedCmds<Ms> {
+    next_id: u64,
+    /// Cmds are aborted on their handle drop.
+    running: BTreeMap<u64, CmdHandle>,
+    queued: VecDeque<LocalBoxFuture<'static, Option<Ms>>>,
+}
+
+impl<Ms> Default for KeyedCmds<Ms> {
+    fn default() -> Self {
+        Self {
+            next_id: 0,
+            running: BTreeMap::new(),
+            queued: VecDeque::new(),
+        }
+    }
+}
+
+impl<Ms, Mdl, INodes> App<Ms, Mdl, INodes>
+where
+    Ms: 'static,
+    Mdl: 'static,
+    INodes: IntoNodes<Ms> + 'static,
+{
+    pub(crate) fn perform_keyed_cmd(
+        &self,
+        key: &str,
+        policy: CmdPolicy,
+        cmd: impl Future<Output = Option<Ms>> + 'static,
+    ) {
+        if self.data.replaying.get() || self.data.unmounted.get() {
+            return;
+        }
+        let mut keyed_cmds = self.data.keyed_cmds.borrow_mut();
+        let cmds = keyed_cmds.entry(key.to_owned()).or_default();
+
+        match policy {
+            CmdPolicy::LatestWins => {
+                cmds.running.clear();
+                cmds.queued.clear();
+            }
+            CmdPolicy::FirstWins if !cmds.running.is_empty() => return,
+            _ => (),
+        }
+        if cmds.running.len() < policy.max_running() {
+            self.start_keyed_cmd(key, cmds, cmd.boxed_local());
+        } else {
+            cmds.queued.push_back(cmd.boxed_local());
+        }
+    }
+
+    /// Abort running and drop waiting cmds with the given `key`.
+    pub(crate) fn cancel_keyed_cmds(&self, key: &str) {
+        // Take cmds out of `AppData` before they are dropped.
+        let cmds = self.data.keyed_cmds.borrow_mut().remove(key);
+        drop(cmds);
+    }
+
+    fn start_keyed_cmd(
+        &self,
+        key: &str,
+        cmds: &mut KeyedCmds<Ms>,
+        cmd: LocalBoxFuture<'static, Option<Ms>>,
+    ) {
+        let id = cmds.next_id;
+        cmds.next_id += 1;
+
+        let (app, key) = (self.clone(), key.to_owned());
+        let handle = self.perform_cmd_with_handle(async move {
+            let msg = cmd.await;
+            // The cmd isn't running anymore when `update` handles its message
+            // - e.g. `update` may perform another cmd with the same key.
+            app.finish_keyed_cmd(&key, id);
+            app.mailbox().send(msg);
+        });
+        cmds.running.insert(id, handle);
+    }
+
+    fn finish_keyed_cmd(&self, key: &str, id: u64) {
+        let mut keyed_cmds = self.data.keyed_cmds.borrow_mut();
+        let cmds = match keyed_cmds.get_mut(key) {
+            Some(cmds) => cmds,
+            None => return,
+        };
+        cmds.running.remove(&id);
+        match cmds.queued.pop_front() {
+            Some(cmd) => self.start_keyed_cmd(key, cmds, cmd),
+            None if cmds.running.is_empty() => {
+                keyed_cmds.remove(key);
+            }
+            None => (),
+        }
+    }
+}
+
+// ------ ------ Tests ------ ------
+
+#[cfg(test)]
+mod tests {
+    use crate::prelude::*;
+    use crate::testing::TestApp;
+
+    #[derive(Clone, Debug, PartialEq)]
+    enum Msg {
+        Fetch(CmdPolicy, u32),
+        Fetched(u32),
+        FetchNext(u32),
+    }
+
+    fn init(_: Url, _: &mut impl Orders<Msg>) {}
+
+    fn update(msg: Msg, _: &mut (), orders: &mut impl Orders<Msg>) {
+        match msg {
+            Msg::Fetch(policy, page) => {
+                orders.perform_keyed_cmd("fetch", policy, async move { Msg::Fetched(page) });
+            }
+            Msg::Fetched(_) => (),
+            Msg::FetchNext(page) => {
+                orders.perform_keyed_cmd("fetch", CmdPolicy::FirstWins, async move {
+                    Msg::FetchNext(page + 1)
+                });
+            }
+        }
+    }
+
+    fn view(_: &()) -> Node<Msg> {
+        empty![]
+    }
+
+    #[test]
+    fn latest_wins() {
+        let app = TestApp::start(Url::new(), init, update, view);
+        app.update(Msg::Fetch(CmdPolicy::LatestWins, 1));
+        app.update(Msg::Fetch(CmdPolicy::LatestWins, 2));
+        app.run_cmds();
+        assert_eq!(app.queued_msgs(), vec![Msg::Fetched(2)]);
+    }
+
+    #[test]
+    fn first_wins() {
+        let app = TestApp::start(Url::new(), init, update, view);
+        app.update(Msg::Fetch(CmdPolicy::FirstWins, 1));
+        app.update(Msg::Fetch(CmdPolicy::FirstWins, 2));
+        assert_eq!(app.pending_cmds(), 1);
+        app.run_cmds();
+        assert_eq!(app.queued_msgs(), vec![Msg::Fetched(1)]);
+    }
+
+    #[test]
+    fn queue_runs_cmds_one_at_a_time() {
+        let app = TestApp::start(Url::new(), init, update, view);
+        app.update(Msg::Fetch(CmdPolicy::Queue, 1));
+        app.update(Msg::Fetch(CmdPolicy::Queue, 2));
+        assert_eq!(app.pending_cmds(), 1);
+        app.run_cmds();
+        assert_eq!(app.queued_msgs(), vec![Msg::Fetched(1)]);
+        assert_eq!(app.pending_cmds(), 1);
+        app.run_cmds();
+        assert_eq!(app.queued_msgs(), vec![Msg::Fetched(1), Msg::Fetched(2)]);
+    }
+
+    #[test]
+    fn max_parallel() {
+        let app = TestApp::start(Url::new(), init, update, view);
+        for page in 1..=3 {
+            app.update(Msg::Fetch(CmdPolicy::MaxParallel(2), page));
+        }
+        assert_eq!(app.pending_cmds(), 2);
+        app.run_cmds();
+        assert_eq!(app.pending_cmds(), 1);
+    }
+
+    #[test]
+    fn first_wins_cmd_can_be_chained_from_its_result() {
+        let app = TestApp::start(Url::new(), init, update, view);
+        app.update(Msg::FetchNext(1));
+        app.run_cmds();
+        assert!(app.clone_app().data.keyed_cmds.borrow().is_empty());
+
+        app.settle();
+        assert_eq!(app.pending_cmds(), 1);
+        app.run_cmds();
+        assert_eq!(app.queued_msgs(), vec![Msg::FetchNext(3)]);
+    }
+}
@@ -1,8 +1,13 @@
-use super::{subs, App, CmdHandle, RenderInfo, StreamHandle, SubHandle};
-use crate::browser::Url;
+use super::{
+    persistence, subs, App, CmdHandle, CmdPolicy, Persistence, RenderInfo, StreamHandle, SubHandle,
+    SubOptions,
+};
+use crate::browser::{web_storage::WebStorage, Url};
 use crate::virtual_dom::IntoNodes;
+use futures::future::FutureExt;
 use futures::stream::Stream;
-use std::{any::Any, future::Future, rc::Rc};
+use serde::{de::DeserializeOwned, Serialize};
+use std::{any::Any, convert::identity, future::Future, rc::Rc};
 
 // @TODO: Add links to doc comment once https://github.com/rust-lang/rust/issues/43466 is resolved
 // or use nightly rustdoc. Applicable to the entire code base.
@@ -61,6 +66,21 @@ pub trait Orders<Ms: 'static> {
     /// _Note:_: All notifications are pushed to the queue - i.e. `update` function is NOT called immediately.
     fn notify(&mut self, message: impl Any + Clone) -> &mut Self;
 
+    /// Notify subscribers like `notify` and retain the `message`.
+    ///
+    /// Subscribers created later receive the retained message immediately, until another
+    /// retained message with the same type is sent. It's useful for shared state like the current user.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    ///orders.notify_retained(auth::LoggedUser(Some(user)));
+    /// ...
+    /// // In a child module initialized later:
+    ///orders.subscribe(Msg::LoggedUserChanged);  // `Msg::LoggedUserChanged(auth::LoggedUser(Some(user)))`
+    /// ```
+    fn notify_retained(&mut self, message: impl Any + Clone) -> &mut Self;
+
     /// Invoke function `update` with the given `msg`.
     ///
     /// # Example
@@ -122,6 +142,52 @@ pub trait Orders<Ms: 'static> {
         cmd: impl Future<Output = MsU> + 'static,
     ) -> CmdHandle;
 
+    /// Execute given `cmd` and send its output (if it's `Msg`) to `update` function.
+    /// - `policy` decides what happens when other cmds with the same `key` are running.
+    ///
+    /// Output has to be `Msg`, `Option<Msg>` or `()`. Keys are shared by the whole app.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    ///Msg::QueryChanged(query) => {
+    ///    // A response to an old query is never sent to `update`.
+    ///    orders.perform_keyed_cmd("search", CmdPolicy::LatestWins, async move {
+    ///        Msg::Found(search(&query).await)
+    ///    });
+    ///}
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics when the output isn't `Msg`, `Option<Msg>` or `()`.
+    fn perform_keyed_cmd<MsU: 'static>(
+        &mut self,
+        key: &str,
+        policy: CmdPolicy,
+        cmd: impl Future<Output = MsU> + 'static,
+    ) -> &mut Self {
+        let app = self.clone_app();
+        let msg_mapper = self.msg_mapper();
+
+        let handler = map_callback_return_to_option_ms!(
+            dyn Fn(MsU) -> Option<Ms>,
+            identity,
+            "Cmds can return only Msg, Option<Msg> or ()!",
+            Box
+        );
+
+        let cmd = cmd.map(move |msg| handler(msg).map(|msg| msg_mapper(msg)));
+        app.perform_keyed_cmd(key, policy, cmd);
+        self
+    }
+
+    /// Abort running and drop waiting cmds performed by `perform_keyed_cmd` with the given `key`.
+    fn cancel_keyed_cmds(&mut self, key: &str) -> &mut Self {
+        self.clone_app().cancel_keyed_cmds(key);
+        self
+    }
+
     /// Get app instance. Cloning is cheap because `App` contains only `Rc` fields.
     fn clone_app(&self) -> App<Self::AppMs, Self::Mdl, Self::INodes>;
 
@@ -228,7 +294,9 @@ pub trait Orders<Ms: 'static> {
     fn subscribe<MsU: 'static, SubMs: 'static + Clone>(
         &mut self,
         handler: impl FnOnce(SubMs) -> MsU + Clone + 'static,
-    ) -> &mut Self;
+    ) -> &mut Self {
+        self.subscribe_with_options(handler, SubOptions::default())
+    }
 
     /// Subscribe for messages with the `handler`s input type.
     /// - Returns `SubHandle` that you should save to your `Model`.
@@ -259,6 +327,50 @@ pub trait Orders<Ms: 'static> {
     fn subscribe_with_handle<MsU: 'static, SubMs: 'static + Clone>(
         &mut self,
         handler: impl FnOnce(SubMs) -> MsU + Clone + 'static,
+    ) -> SubHandle {
+        self.subscribe_with_options_and_handle(handler, SubOptions::default())
+    }
+
+    /// Subscribe like `subscribe` with the given priority and filter - see `SubOptions`.
+    ///
+    /// Handler has to return `Msg`, `Option<Msg>` or `()`.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    ///orders.subscribe_with_options(
+    ///    Msg::UrlChanged,
+    ///    SubOptions::new().priority(10).filter(|subs::UrlChanged(url)| !url.path().is_empty()),
+    ///);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics when the handler doesn't return `Msg`, `Option<Msg>` or `()`.
+    ///
+    /// Stabilisation of issue [391](https://github.com/seed-rs/seed/issues/391) makes this a compile-time error.
+    // @TODO remove `'static`s once `optin_builtin_traits`, `negative_impls`
+    // @TODO or https://github.com/rust-lang/rust/issues/41875 is stable
+    fn subscribe_with_options<MsU: 'static, SubMs: 'static + Clone>(
+        &mut self,
+        handler: impl FnOnce(SubMs) -> MsU + Clone + 'static,
+        options: SubOptions<SubMs>,
+    ) -> &mut Self;
+
+    /// Subscribe like `subscribe_with_handle` with the given priority and filter - see `SubOptions`.
+    ///
+    /// # Panics
+    ///
+    /// Panics when the handler doesn't return `Msg`, `Option<Msg>` or `()`.
+    ///
+    /// Stabilisation of issue [391](https://github.com/seed-rs/seed/issues/391) makes this a compile-time error.
+    #[must_use = "subscription is cancelled on its handle drop"]
+    // @TODO remove `'static`s once `optin_builtin_traits`, `negative_impls`
+    // @TODO or https://github.com/rust-lang/rust/issues/41875 is stable
+    fn subscribe_with_options_and_handle<MsU: 'static, SubMs: 'static + Clone>(
+        &mut self,
+        handler: impl FnOnce(SubMs) -> MsU + Clone + 'static,
+        options: SubOptions<SubMs>,
     ) -> SubHandle;
 
     /// Stream `Msg`, `Option<Msg>` or `()`.
@@ -321,4 +433,87 @@ pub trait Orders<Ms: 'static> {
     fn request_url(&mut self, url: Url) -> &mut Self {
         self.notify(subs::UrlRequested::new(url))
     }
+
+    /// Send `msg` after `delay_ms` milliseconds unless another message is debounced
+    /// with the same `key` in the meantime - then only the latest message is sent.
+    ///
+    /// Keys are shared by the whole app. Pending messages are dropped when the app is unmounted.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    ///Msg::QueryChanged(query) => {
+    ///    model.query = query.clone();
+    ///    orders.debounce("search", 300, Msg::Search(query));
+    ///}
+    /// ```
+    fn debounce(&mut self, key: &str, delay_ms: u32, msg: Ms) -> &mut Self {
+        let msg = (self.msg_mapper())(msg);
+        self.clone_app().debounce(key, delay_ms, msg);
+        self
+    }
+
+    /// Send `msg` immediately and ignore messages with the same `key` for `interval_ms` milliseconds.
+    /// The latest ignored message is sent when the interval ends.
+    ///
+    /// Keys are shared by the whole app. Pending messages are dropped when the app is unmounted.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    ///Msg::Scrolled(position) => {
+    ///    orders.throttle("scroll", 100, Msg::LoadVisibleRows(position));
+    ///}
+    /// ```
+    fn throttle(&mut self, key: &str, interval_ms: u32, msg: Ms) -> &mut Self {
+        let app = self.clone_app();
+        if app.is_throttled(key) {
+            app.replace_trailing_msg(key, (self.msg_mapper())(msg));
+        } else {
+            app.start_throttle(key, interval_ms);
+            self.send_msg(msg);
+        }
+        self
+    }
+
+    /// Restore data saved by `persistence` and save the part of the model returned by `selector`
+    /// after each update (or after a debounce delay - see `Persistence::debounce`).
+    ///
+    /// Call it in your `init` function - it returns the restored and migrated data
+    /// or `None` if there are no data.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    ///fn init(_: Url, orders: &mut impl Orders<Msg>) -> Model {
+    ///    let persistence = Persistence::<LocalStorage>::new("todos").debounce(300);
+    ///    Model {
+    ///        todos: orders
+    ///            .persist(persistence, |model: &Model| &model.todos)
+    ///            .unwrap_or_else(|error| {
+    ///                error!(error);
+    ///                None
+    ///            })
+    ///            .unwrap_or_default(),
+    ///    }
+    ///}
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns error if the stored data cannot be restored (see `Persistence::restore`)
+    /// or `selector` doesn't accept the app's model (`Mdl`). The data aren't persisted then,
+    /// so stored data aren't overwritten - e.g. data saved by a newer version of the app.
+    fn persist<Mdl, S, T>(
+        &mut self,
+        persistence: Persistence<S>,
+        selector: impl Fn(&Mdl) -> &T + 'static,
+    ) -> persistence::Result<Option<T>>
+    where
+        Mdl: 'static,
+        S: WebStorage + 'static,
+        T: Serialize + DeserializeOwned + 'static,
+    {
+        self.clone_app().persist(persistence, selector)
+    }
 }
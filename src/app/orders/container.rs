@@ -1,8 +1,7 @@
-use crate::app::cmd_manager::CmdManager;
 use crate::app::orders::{proxy::OrdersProxy, Orders};
-use crate::app::stream_manager::StreamManager;
 use crate::app::{
     App, CmdHandle, Effect, Notification, RenderInfo, ShouldRender, StreamHandle, SubHandle,
+    SubOptions,
 };
 use crate::virtual_dom::IntoNodes;
 use futures::future::FutureExt;
@@ -72,6 +71,12 @@ where
         self
     }
 
+    fn notify_retained(&mut self, message: impl Any + Clone) -> &mut Self {
+        self.effects
+            .push_back(Effect::Notification(Notification::new_retained(message)));
+        self
+    }
+
     fn send_msg(&mut self, msg: Ms) -> &mut Self {
         self.effects.push_back(Effect::Msg(Some(msg)));
         self
@@ -88,7 +93,7 @@ where
         );
 
         let cmd = cmd.map(move |msg| app.mailbox().send(handler(msg)));
-        CmdManager::perform_cmd(cmd);
+        self.app.perform_cmd(cmd);
         self
     }
 
@@ -106,7 +111,7 @@ where
         );
 
         let cmd = cmd.map(move |msg| app.mailbox().send(handler(msg)));
-        CmdManager::perform_cmd_with_handle(cmd)
+        self.app.perform_cmd_with_handle(cmd)
     }
 
     fn clone_app(&self) -> App<Self::AppMs, Self::Mdl, Self::INodes> {
@@ -136,10 +141,15 @@ where
         self
     }
 
-    fn subscribe<MsU: 'static, SubMs: 'static + Clone>(
+    fn subscribe_with_options<MsU: 'static, SubMs: 'static + Clone>(
         &mut self,
         handler: impl FnOnce(SubMs) -> MsU + Clone + 'static,
+        options: SubOptions<SubMs>,
     ) -> &mut Self {
+        // Replayed `update`s would duplicate the existing subscriptions - see `Devtools::replay`.
+        if self.app.data.replaying.get() {
+            return self;
+        }
         #[allow(clippy::redundant_closure)]
         let handler = map_callback_return_to_option_ms!(
             dyn Fn(SubMs) -> Option<Ms>,
@@ -149,18 +159,26 @@ where
         );
 
         #[allow(clippy::redundant_closure)]
-        self.app
+        let replayed_msg = self
+            .app
             .data
             .sub_manager
             .borrow_mut()
-            .subscribe(move |sub_ms| handler(sub_ms));
+            .subscribe(move |sub_ms| handler(sub_ms), options);
+        if let Some(msg) = replayed_msg {
+            self.send_msg(msg);
+        }
         self
     }
 
-    fn subscribe_with_handle<MsU: 'static, SubMs: 'static + Clone>(
+    fn subscribe_with_options_and_handle<MsU: 'static, SubMs: 'static + Clone>(
         &mut self,
         handler: impl FnOnce(SubMs) -> MsU + Clone + 'static,
+        options: SubOptions<SubMs>,
     ) -> SubHandle {
+        if self.app.data.replaying.get() {
+            return SubHandle::inactive();
+        }
         let handler = map_callback_return_to_option_ms!(
             dyn Fn(SubMs) -> Option<Ms>,
             handler.clone(),
@@ -169,11 +187,16 @@ where
         );
 
         #[allow(clippy::redundant_closure)]
-        self.app
+        let (sub_handle, replayed_msg) = self
+            .app
             .data
             .sub_manager
             .borrow_mut()
-            .subscribe_with_handle(move |sub_ms| handler(sub_ms))
+            .subscribe_with_handle(move |sub_ms| handler(sub_ms), options);
+        if let Some(msg) = replayed_msg {
+            self.send_msg(msg);
+        }
+        sub_handle
     }
 
     fn stream<MsU: 'static>(&mut self, stream: impl Stream<Item = MsU> + 'static) -> &mut Self {
@@ -187,7 +210,7 @@ where
         );
 
         let stream = stream.map(move |msg| app.mailbox().send(handler(msg)));
-        StreamManager::stream(stream);
+        self.app.stream(stream);
         self
     }
 
@@ -205,6 +228,6 @@ where
         );
 
         let stream = stream.map(move |msg| app.mailbox().send(handler(msg)));
-        StreamManager::stream_with_handle(stream)
+        self.app.stream_with_handle(stream)
     }
 }
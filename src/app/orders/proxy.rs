@@ -1,10 +1,8 @@
 use super::{
-    super::{App, CmdHandle, RenderInfo, StreamHandle, SubHandle},
+    super::{App, CmdHandle, RenderInfo, StreamHandle, SubHandle, SubOptions},
     Orders, OrdersContainer,
 };
 
-use crate::app::cmd_manager::CmdManager;
-use crate::app::stream_manager::StreamManager;
 use crate::virtual_dom::IntoNodes;
 use futures::future::{Future, FutureExt};
 use futures::stream::{Stream, StreamExt};
@@ -79,6 +77,11 @@ where
         self
     }
 
+    fn notify_retained(&mut self, message: impl Any + Clone) -> &mut Self {
+        self.orders_container.notify_retained(message);
+        self
+    }
+
     #[allow(clippy::redundant_closure)]
     fn send_msg(&mut self, msg: Ms) -> &mut Self {
         let f = self.f.clone();
@@ -99,7 +102,7 @@ where
         );
 
         let cmd = cmd.map(move |msg| app.mailbox().send(handler(msg).map(|msg| f(msg))));
-        CmdManager::perform_cmd(cmd);
+        self.clone_app().perform_cmd(cmd);
         self
     }
 
@@ -119,7 +122,7 @@ where
 
         #[allow(clippy::redundant_closure)]
         let cmd = cmd.map(move |msg| app.mailbox().send(handler(msg).map(|msg| f(msg))));
-        CmdManager::perform_cmd_with_handle(cmd)
+        self.clone_app().perform_cmd_with_handle(cmd)
     }
 
     fn clone_app(&self) -> App<Self::AppMs, Self::Mdl, Self::INodes> {
@@ -155,10 +158,15 @@ where
         self
     }
 
-    fn subscribe<MsU: 'static, SubMs: 'static + Clone>(
+    fn subscribe_with_options<MsU: 'static, SubMs: 'static + Clone>(
         &mut self,
         handler: impl FnOnce(SubMs) -> MsU + Clone + 'static,
+        options: SubOptions<SubMs>,
     ) -> &mut Self {
+        // Replayed `update`s would duplicate the existing subscriptions - see `Devtools::replay`.
+        if self.clone_app().data.replaying.get() {
+            return self;
+        }
         let handler = map_callback_return_to_option_ms!(
             dyn Fn(SubMs) -> Option<Ms>,
             handler.clone(),
@@ -168,18 +176,26 @@ where
 
         let f = self.f.clone();
         #[allow(clippy::redundant_closure)]
-        self.clone_app()
+        let replayed_msg = self
+            .clone_app()
             .data
             .sub_manager
             .borrow_mut()
-            .subscribe(move |sub_ms| handler(sub_ms).map(|ms| f(ms)));
+            .subscribe(move |sub_ms| handler(sub_ms).map(|ms| f(ms)), options);
+        if let Some(msg) = replayed_msg {
+            self.orders_container.send_msg(msg);
+        }
         self
     }
 
-    fn subscribe_with_handle<MsU: 'static, SubMs: 'static + Clone>(
+    fn subscribe_with_options_and_handle<MsU: 'static, SubMs: 'static + Clone>(
         &mut self,
         handler: impl FnOnce(SubMs) -> MsU + Clone + 'static,
+        options: SubOptions<SubMs>,
     ) -> SubHandle {
+        if self.clone_app().data.replaying.get() {
+            return SubHandle::inactive();
+        }
         let handler = map_callback_return_to_option_ms!(
             dyn Fn(SubMs) -> Option<Ms>,
             handler.clone(),
@@ -189,11 +205,16 @@ where
 
         let f = self.f.clone();
         #[allow(clippy::redundant_closure)]
-        self.clone_app()
+        let (sub_handle, replayed_msg) = self
+            .clone_app()
             .data
             .sub_manager
             .borrow_mut()
-            .subscribe_with_handle(move |sub_ms| handler(sub_ms).map(|ms| f(ms)))
+            .subscribe_with_handle(move |sub_ms| handler(sub_ms).map(|ms| f(ms)), options);
+        if let Some(msg) = replayed_msg {
+            self.orders_container.send_msg(msg);
+        }
+        sub_handle
     }
 
     fn stream<MsU: 'static>(&mut self, stream: impl Stream<Item = MsU> + 'static) -> &mut Self {
@@ -209,7 +230,7 @@ where
 
         #[allow(clippy::redundant_closure)]
         let stream = stream.map(move |msg| app.mailbox().send(handler(msg).map(|msg| f(msg))));
-        StreamManager::stream(stream);
+        self.clone_app().stream(stream);
         self
     }
 
@@ -229,6 +250,6 @@ where
 
         #[allow(clippy::redundant_closure)]
         let stream = stream.map(move |msg| app.mailbox().send(handler(msg).map(|msg| f(msg))));
-        StreamManager::stream_with_handle(stream)
+        self.clone_app().stream_with_handle(stream)
     }
 }
@@ -0,0 +1,549 @@
+//! Automatic persistence of the model (or its part) in `LocalStorage` / `SessionStorage`.
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//!const STORAGE_KEY: &str = "todos-seed";
+//!
+//!fn init(_: Url, orders: &mut impl Orders<Msg>) -> Model {
+//!    let persistence = Persistence::<LocalStorage>::new(STORAGE_KEY)
+//!        .version(2)
+//!        // Version 1 stored only titles.
+//!        .migrate(1, |titles: Vec<String>| {
+//!            titles.into_iter().map(Todo::new).collect::<Vec<_>>()
+//!        })
+//!        .debounce(300);
+//!
+//!    let todos = match orders.persist(persistence, |model: &Model| &model.todos) {
+//!        Ok(todos) => todos.unwrap_or_default(),
+//!        // Stored todos aren't overwritten - e.g. they may belong to a newer version of the app.
+//!        Err(error) => {
+//!            error!(error);
+//!            Vec::new()
+//!        }
+//!    };
+//!    Model {
+//!        todos,
+//!        new_todo_title: String::new(),
+//!    }
+//!}
+//! ```
+
+use super::{clock, App, CmdHandle};
+use crate::browser::web_storage::{WebStorage, WebStorageError};
+use crate::virtual_dom::IntoNodes;
+use js_sys::{Reflect, JSON};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_wasm_bindgen as swb;
+use std::{
+    any::{Any, TypeId},
+    cell::RefCell,
+    collections::BTreeMap,
+    marker::PhantomData,
+    rc::Rc,
+};
+use wasm_bindgen::JsValue;
+
+type Migration = Box<dyn Fn(JsValue) -> Result<JsValue>>;
+
+/// Convenient type alias.
+pub type Result<T> = std::result::Result<T, PersistenceError>;
+
+// ------ PersistenceError ------
+
+#[allow(clippy::module_name_repetitions)]
+#[derive(Debug)]
+pub enum PersistenceError {
+    WebStorageError(WebStorageError),
+    SerdeError(swb::Error),
+    ParseError(JsValue),
+    StringifyError(JsValue),
+    MissingMigration(u32),
+    /// The stored version is newer than the current one - e.g. after a rollback.
+    NewerVersion(u32),
+    /// The `Orders::persist` selector doesn't accept the app's model -
+    /// e.g. it's been called through `Orders::proxy` with a child model.
+    ModelMismatch,
+}
+
+impl From<WebStorageError> for PersistenceError {
+    fn from(v: WebStorageError) -> Self {
+        Self::WebStorageError(v)
+    }
+}
+
+impl From<swb::Error> for PersistenceError {
+    fn from(v: swb::Error) -> Self {
+        Self::SerdeError(v)
+    }
+}
+
+// ------ Envelope ------
+
+/// Stored payload. Data stored without the envelope (e.g. by `WebStorage::insert`)
+/// are restored as version `0`.
+#[derive(Serialize)]
+struct Envelope<'a, T> {
+    version: u32,
+    data: &'a T,
+}
+
+#[derive(Deserialize)]
+struct EnvelopeHeader {
+    version: u32,
+}
+
+// ------ Persistence ------
+
+/// Persistence configuration - see `Orders::persist`.
+pub struct Persistence<S: WebStorage> {
+    key: String,
+    version: u32,
+    migrations: BTreeMap<u32, Migration>,
+    debounce: u32,
+    storage: PhantomData<S>,
+}
+
+impl<S: WebStorage + 'static> Persistence<S> {
+    /// Persist data under the `key` with version `1` and without debouncing.
+    pub fn new(key: impl Into<String>) -> Self {
+        Self {
+            key: key.into(),
+            version: 1,
+            migrations: BTreeMap::new(),
+            debounce: 0,
+            storage: PhantomData,
+        }
+    }
+
+    /// Set the current schema version. Bump it when you change the persisted data type
+    /// and register a migration from the previous version.
+    pub fn version(mut self, version: u32) -> Self {
+        self.version = version;
+        self
+    }
+
+    /// Register a migration from the stored `from_version` to `from_version + 1`.
+    ///
+    /// Migrations are chained - e.g. version `1` is upgraded to `3` by migrations `1` and `2`.
+    /// Data stored without `Persistence` (e.g. by `WebStorage::insert`) have version `0`.
+    pub fn migrate<Old, New>(
+        mut self,
+        from_version: u32,
+        migration: impl Fn(Old) -> New + 'static,
+    ) -> Self
+    where
+        Old: DeserializeOwned,
+        New: Serialize,
+    {
+        self.migrations.insert(
+            from_version,
+            Box::new(move |data| {
+                let old: Old = swb::from_value(data)?;
+                Ok(swb::to_value(&migration(old))?)
+            }),
+        );
+        self
+    }
+
+    /// Save data `ms` milliseconds after the last update instead of after each update.
+    ///
+    /// _Note:_ A pending save is lost when the page is closed.
+    pub fn debounce(mut self, ms: u32) -> Self {
+        self.debounce = ms;
+        self
+    }
+
+    /// Load stored data and upgrade them to the current version.
+    ///
+    /// Returns `Ok(None)` when there are no stored data.
+    ///
+    /// # Errors
+    ///
+    /// Returns error if the data cannot be read, migrated or deserialized.
+    pub fn restore<T: DeserializeOwned>(&self) -> Result<Option<T>> {
+        let item = match S::storage()?
+            .get_item(&self.key)
+            .map_err(WebStorageError::GetError)?
+        {
+            Some(item) => item,
+            None => return Ok(None),
+        };
+        let stored: JsValue = JSON::parse(&item).map_err(PersistenceError::ParseError)?;
+        let data_key = JsValue::from_str("data");
+        let (mut version, mut data) = match swb::from_value::<EnvelopeHeader>(stored.clone()) {
+            Ok(EnvelopeHeader { version }) if Reflect::has(&stored, &data_key).unwrap_or(false) => {
+                let data =
+                    Reflect::get(&stored, &data_key).map_err(PersistenceError::ParseError)?;
+                (version, data)
+            }
+            _ => (0, stored),
+        };
+
+        if version > self.version {
+            return Err(PersistenceError::NewerVersion(version));
+        }
+        while version < self.version {
+            let migration = self
+                .migrations
+                .get(&version)
+                .ok_or(PersistenceError::MissingMigration(version))?;
+            data = migration(data)?;
+            version += 1;
+        }
+        Ok(Some(swb::from_value(data)?))
+    }
+
+    /// Store `data` with the current version.
+    ///
+    /// # Errors
+    ///
+    /// Returns error if the data cannot be serialized or stored.
+    pub fn save<T: Serialize>(&self, data: &T) -> Result<()> {
+        self.save_serialized(&self.serialize(data)?)
+    }
+
+    /// Serialize `data` with the current version into the stored string.
+    fn serialize<T: Serialize>(&self, data: &T) -> Result<String> {
+        let envelope = swb::to_value(&Envelope {
+            version: self.version,
+            data,
+        })?;
+        Ok(JSON::stringify(&envelope)
+            .map_err(PersistenceError::StringifyError)?
+            .into())
+    }
+
+    fn save_serialized(&self, serialized: &str) -> Result<()> {
+        S::storage()?
+            .set_item(&self.key, serialized)
+            .map_err(WebStorageError::InsertError)?;
+        Ok(())
+    }
+
+    /// Remove stored data.
+    ///
+    /// # Errors
+    ///
+    /// Returns error if the storage isn't accessible.
+    pub fn remove(&self) -> Result<()> {
+        S::remove(&self.key)?;
+        Ok(())
+    }
+}
+
+// ------ Persister ------
+
+/// Saves a part of the model after updates.
+pub(crate) struct Persister<Mdl> {
+    save: Box<dyn Fn(&Mdl)>,
+    debounce: u32,
+    /// The debounced save - it's cancelled on drop.
+    scheduled_save: RefCell<Option<CmdHandle>>,
+}
+
+impl<Mdl: 'static> Persister<Mdl> {
+    /// `SelectorMdl` is `Mdl` - see `App::persist`.
+    pub fn new<SelectorMdl, S, T>(
+        persistence: Persistence<S>,
+        selector: impl Fn(&SelectorMdl) -> &T + 'static,
+    ) -> Self
+    where
+        SelectorMdl: 'static,
+        S: WebStorage + 'static,
+        T: Serialize + 'static,
+    {
+        let debounce = persistence.debounce;
+        // Don't write the same data again.
+        let last_saved: RefCell<Option<String>> = RefCell::new(None);
+
+        Self {
+            save: Box::new(move |model: &Mdl| {
+                let model = (model as &dyn Any)
+                    .downcast_ref::<SelectorMdl>()
+                    .expect("downcast the app's model");
+                let serialized = match persistence.serialize(selector(model)) {
+                    Ok(serialized) => serialized,
+                    Err(error) => {
+                        crate::error(error);
+                        return;
+                    }
+                };
+                if last_saved.borrow().as_ref() == Some(&serialized) {
+                    return;
+                }
+                match persistence.save_serialized(&serialized) {
+                    Ok(()) => {
+                        last_saved.replace(Some(serialized));
+                    }
+                    Err(error) => {
+                        crate::error(error);
+                    }
+                }
+            }),
+            debounce,
+            scheduled_save: RefCell::new(None),
+        }
+    }
+
+    /// Save now or schedule saving.
+    pub fn on_update<Ms, INodes>(self: &Rc<Self>, model: &Mdl, app: &App<Ms, Mdl, INodes>)
+    where
+        INodes: IntoNodes<Ms> + 'static,
+    {
+        if self.debounce == 0 {
+            return (self.save)(model);
+        }
+        let (persister, app_data) = (Rc::downgrade(self), Rc::downgrade(&app.data));
+        // `clock::sleep` respects `VirtualClock`.
+        let sleep = clock::sleep(self.debounce);
+        let handle = app.perform_cmd_with_handle(async move {
+            sleep.await;
+            if let (Some(persister), Some(app_data)) = (persister.upgrade(), app_data.upgrade()) {
+                persister.scheduled_save.replace(None);
+                if let Some(model) = app_data.model.borrow().as_ref() {
+                    (persister.save)(model);
+                }
+            }
+        });
+        // The previous save is cancelled on drop.
+        self.scheduled_save.replace(Some(handle));
+    }
+
+    /// Save immediately if there is a scheduled save.
+    pub fn flush(&self, model: &Mdl) {
+        if self.scheduled_save.replace(None).is_some() {
+            (self.save)(model);
+        }
+    }
+}
+
+impl<Ms, Mdl, INodes> App<Ms, Mdl, INodes>
+where
+    INodes: IntoNodes<Ms> + 'static,
+{
+    /// `SelectorMdl` has to be the app's model - `init` doesn't know the concrete `Orders::Mdl`.
+    ///
+    /// Data aren't saved when they cannot be restored, so they aren't overwritten.
+    pub(crate) fn persist<SelectorMdl, S, T>(
+        &self,
+        persistence: Persistence<S>,
+        selector: impl Fn(&SelectorMdl) -> &T + 'static,
+    ) -> Result<Option<T>>
+    where
+        SelectorMdl: 'static,
+        S: WebStorage + 'static,
+        T: Serialize + DeserializeOwned + 'static,
+    {
+        if TypeId::of::<SelectorMdl>() != TypeId::of::<Mdl>() {
+            return Err(PersistenceError::ModelMismatch);
+        }
+        let restored = persistence.restore()?;
+        self.data
+            .persisters
+            .borrow_mut()
+            .push(Rc::new(Persister::new(persistence, selector)));
+        Ok(restored)
+    }
+
+    pub(crate) fn save_persisted(&self) {
+        let model = self.data.model.borrow();
+        if let Some(model) = model.as_ref() {
+            for persister in self.data.persisters.borrow().iter() {
+                persister.on_update(model, self);
+            }
+        }
+    }
+
+    pub(crate) fn flush_persisted(&self, model: Option<&Mdl>) {
+        if let Some(model) = model {
+            for persister in self.data.persisters.borrow().iter() {
+                persister.flush(model);
+            }
+        }
+    }
+}
+
+// ------ ------ Tests ------ ------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::*;
+    use crate::testing::{TestApp, VirtualClock};
+    use wasm_bindgen_test::*;
+
+    wasm_bindgen_test_configure!(run_in_browser);
+
+    const STORAGE_KEY: &str = "seed-persistence-test";
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    struct Todo {
+        title: String,
+        completed: bool,
+    }
+
+    struct Model {
+        todos: Vec<Todo>,
+        restore_error: Option<PersistenceError>,
+    }
+
+    enum Msg {
+        Add(&'static str),
+    }
+
+    fn persistence() -> Persistence<LocalStorage> {
+        Persistence::new(STORAGE_KEY)
+            .version(3)
+            .migrate(1, |titles: Vec<String>| titles.join(","))
+            .migrate(2, |titles: String| {
+                titles
+                    .split(',')
+                    .map(|title| Todo {
+                        title: title.to_owned(),
+                        completed: false,
+                    })
+                    .collect::<Vec<_>>()
+            })
+    }
+
+    fn init(_: Url, orders: &mut impl Orders<Msg>) -> Model {
+        match orders.persist(persistence(), |model: &Model| &model.todos) {
+            Ok(todos) => Model {
+                todos: todos.unwrap_or_default(),
+                restore_error: None,
+            },
+            Err(error) => Model {
+                todos: Vec::new(),
+                restore_error: Some(error),
+            },
+        }
+    }
+
+    fn update(msg: Msg, model: &mut Model, _: &mut impl Orders<Msg>) {
+        match msg {
+            Msg::Add(title) => model.todos.push(Todo {
+                title: title.to_owned(),
+                completed: false,
+            }),
+        }
+    }
+
+    fn view(_: &Model) -> Node<Msg> {
+        empty![]
+    }
+
+    #[wasm_bindgen_test]
+    fn restore_with_migrations_and_save() {
+        LocalStorage::insert(
+            STORAGE_KEY,
+            &serde_json::json!({"version": 1, "data": ["a", "b"]}),
+        )
+        .unwrap();
+
+        let app = TestApp::start(Url::new(), init, update, view);
+        assert_eq!(
+            app.model()
+                .todos
+                .iter()
+                .map(|todo| todo.title.as_str())
+                .collect::<Vec<_>>(),
+            vec!["a", "b"]
+        );
+
+        app.update(Msg::Add("c"));
+        let restored: Vec<Todo> = persistence().restore().unwrap().unwrap();
+        assert_eq!(restored.len(), 3);
+        assert_eq!(restored[2].title, "c");
+
+        persistence().remove().unwrap();
+        assert!(persistence().restore::<Vec<Todo>>().unwrap().is_none());
+    }
+
+    #[wasm_bindgen_test]
+    fn newer_version_is_rejected() {
+        LocalStorage::insert(STORAGE_KEY, &serde_json::json!({"version": 4, "data": []})).unwrap();
+        assert!(matches!(
+            persistence().restore::<Vec<Todo>>(),
+            Err(PersistenceError::NewerVersion(4))
+        ));
+        persistence().remove().unwrap();
+    }
+
+    #[wasm_bindgen_test]
+    fn data_without_envelope_are_migrated_from_version_0() {
+        LocalStorage::insert(STORAGE_KEY, &["a", "b"]).unwrap();
+        assert!(matches!(
+            persistence().restore::<Vec<Todo>>(),
+            Err(PersistenceError::MissingMigration(0))
+        ));
+
+        let restored: Vec<Todo> = persistence()
+            .migrate(0, |titles: Vec<String>| titles)
+            .restore()
+            .unwrap()
+            .unwrap();
+        assert_eq!(restored[1].title, "b");
+        persistence().remove().unwrap();
+    }
+
+    #[wasm_bindgen_test]
+    fn data_are_not_overwritten_after_restore_error() {
+        LocalStorage::insert(STORAGE_KEY, &serde_json::json!({"version": 4, "data": []})).unwrap();
+
+        let app = TestApp::start(Url::new(), init, update, view);
+        assert!(matches!(
+            app.model().restore_error,
+            Some(PersistenceError::NewerVersion(4))
+        ));
+        app.update(Msg::Add("c"));
+        assert!(matches!(
+            persistence().restore::<Vec<Todo>>(),
+            Err(PersistenceError::NewerVersion(4))
+        ));
+        persistence().remove().unwrap();
+    }
+
+    #[wasm_bindgen_test]
+    fn selector_of_other_model_is_rejected() {
+        fn init(_: Url, orders: &mut impl Orders<Msg>) -> Model {
+            let result = orders.persist(persistence(), |todos: &Vec<Todo>| todos);
+            Model {
+                todos: Vec::new(),
+                restore_error: result.err(),
+            }
+        }
+        let app = TestApp::start(Url::new(), init, update, view);
+        assert!(matches!(
+            app.model().restore_error,
+            Some(PersistenceError::ModelMismatch)
+        ));
+    }
+
+    #[wasm_bindgen_test]
+    fn debounced_save_respects_virtual_clock() {
+        fn init(_: Url, orders: &mut impl Orders<Msg>) -> Model {
+            let todos = orders
+                .persist(persistence().debounce(300), |model: &Model| &model.todos)
+                .unwrap();
+            Model {
+                todos: todos.unwrap_or_default(),
+                restore_error: None,
+            }
+        }
+        persistence().remove().unwrap();
+        let clock = VirtualClock::install();
+        let app = TestApp::start(Url::new(), init, update, view);
+
+        app.update(Msg::Add("a"));
+        clock.advance(200);
+        app.run_cmds();
+        assert!(persistence().restore::<Vec<Todo>>().unwrap().is_none());
+
+        clock.advance(100);
+        app.run_cmds();
+        let restored: Vec<Todo> = persistence().restore().unwrap().unwrap();
+        assert_eq!(restored[0].title, "a");
+        persistence().remove().unwrap();
+    }
+}
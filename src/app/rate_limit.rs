@@ -0,0 +1,163 @@
+//! Keyed debouncing and throttling of messages - see `Orders::debounce` and `Orders::throttle`.
+
+use super::{clock, App, CmdHandle};
+use crate::virtual_dom::IntoNodes;
+use enclose::enc;
+use std::{cell::RefCell, rc::Rc};
+
+// ------ Throttle ------
+
+/// An open throttle interval. The timer is cancelled on drop.
+pub(crate) struct Throttle<Ms> {
+    _handle: CmdHandle,
+    /// The latest message sent while throttled.
+    trailing_msg: Rc<RefCell<Option<Ms>>>,
+}
+
+impl<Ms, Mdl, INodes> App<Ms, Mdl, INodes>
+where
+    Ms: 'static,
+    Mdl: 'static,
+    INodes: IntoNodes<Ms> + 'static,
+{
+    /// Send `msg` after `delay_ms` unless another message is debounced with the same `key`.
+    pub(crate) fn debounce(&self, key: &str, delay_ms: u32, msg: Ms) {
+        let (app, key) = (self.clone(), key.to_owned());
+        // The virtual deadline is set now, a browser timer starts when the cmd is polled
+        // for the first time - i.e. in the next microtask.
+        let timeout = clock::sleep(delay_ms);
+        let handle = self.perform_cmd_with_handle(enc!((key) async move {
+            timeout.await;
+            app.data.debounces.borrow_mut().remove(&key);
+            app.update(msg);
+        }));
+        // The previous timer is cancelled on drop.
+        self.data.debounces.borrow_mut().insert(key, handle);
+    }
+
+    pub(crate) fn is_throttled(&self, key: &str) -> bool {
+        self.data.throttles.borrow().contains_key(key)
+    }
+
+    /// Open the interval for `key`; messages sent in the meantime are replaced by the latest one
+    /// which is sent when the interval ends.
+    pub(crate) fn start_throttle(&self, key: &str, interval_ms: u32) {
+        let (app, key) = (self.clone(), key.to_owned());
+        let trailing_msg = Rc::new(RefCell::new(None));
+        let timeout = clock::sleep(interval_ms);
+        let handle = self.perform_cmd_with_handle(enc!((key, trailing_msg) async move {
+            timeout.await;
+            app.data.throttles.borrow_mut().remove(&key);
+            let trailing_msg = trailing_msg.borrow_mut().take();
+            if let Some(msg) = trailing_msg {
+                // The trailing message opens a new interval to keep the rate.
+                app.start_throttle(&key, interval_ms);
+                app.update(msg);
+            }
+        }));
+        self.data.throttles.borrow_mut().insert(
+            key,
+            Throttle {
+                _handle: handle,
+                trailing_msg,
+            },
+        );
+    }
+
+    pub(crate) fn replace_trailing_msg(&self, key: &str, msg: Ms) {
+        if let Some(throttle) = self.data.throttles.borrow().get(key) {
+            throttle.trailing_msg.replace(Some(msg));
+        }
+    }
+}
+
+// ------ ------ Tests ------ ------
+
+#[cfg(test)]
+mod tests {
+    use crate::prelude::*;
+    use crate::testing::{TestApp, VirtualClock};
+
+    #[derive(Clone, Debug, PartialEq)]
+    enum Msg {
+        Search(&'static str),
+        Scroll(u32),
+        Searched(&'static str),
+        Scrolled(u32),
+        Resize(u32),
+        Resized(u32),
+    }
+
+    fn init(_: Url, _: &mut impl Orders<Msg>) {}
+
+    fn update(msg: Msg, _: &mut (), orders: &mut impl Orders<Msg>) {
+        match msg {
+            Msg::Search(query) => {
+                orders.debounce("search", 300, Msg::Searched(query));
+            }
+            Msg::Scroll(position) => {
+                orders.throttle("scroll", 100, Msg::Scrolled(position));
+            }
+            // The same key as `Msg::Search`.
+            Msg::Resize(width) => {
+                orders.throttle("search", 100, Msg::Resized(width));
+            }
+            Msg::Searched(_) | Msg::Scrolled(_) | Msg::Resized(_) => (),
+        }
+    }
+
+    fn view(_: &()) -> Node<Msg> {
+        empty![]
+    }
+
+    #[test]
+    fn debounce_keeps_one_timer_per_key() {
+        let app = TestApp::start(Url::new(), init, update, view);
+        app.update(Msg::Search("a"));
+        app.update(Msg::Search("ab"));
+        assert_eq!(app.clone_app().data.debounces.borrow().len(), 1);
+        assert!(app.queued_msgs().is_empty());
+    }
+
+    #[test]
+    fn debounced_msg_is_sent_after_delay() {
+        let clock = VirtualClock::install();
+        let app = TestApp::start(Url::new(), init, update, view);
+        app.update(Msg::Search("a"));
+        clock.advance(200);
+        app.update(Msg::Search("ab"));
+
+        clock.advance(200);
+        app.run_cmds();
+        assert!(app.queued_msgs().is_empty());
+
+        clock.advance(100);
+        app.run_cmds();
+        assert_eq!(app.queued_msgs(), vec![Msg::Searched("ab")]);
+    }
+
+    #[test]
+    fn throttle_sends_leading_msg_immediately() {
+        let app = TestApp::start(Url::new(), init, update, view);
+        app.send_msg(Msg::Scroll(1));
+        app.send_msg(Msg::Scroll(2));
+        app.step();
+        app.step();
+        assert_eq!(app.queued_msgs(), vec![Msg::Scrolled(1)]);
+        assert!(app.clone_app().is_throttled("scroll"));
+    }
+
+    #[test]
+    fn debounce_and_throttle_keys_are_independent() {
+        let clock = VirtualClock::install();
+        let app = TestApp::start(Url::new(), init, update, view);
+        app.update(Msg::Search("a"));
+        app.send_msg(Msg::Resize(1));
+        app.step();
+        assert_eq!(app.queued_msgs(), vec![Msg::Resized(1)]);
+
+        clock.advance(300);
+        app.run_cmds();
+        assert_eq!(app.queued_msgs(), vec![Msg::Resized(1), Msg::Searched("a")]);
+    }
+}
@@ -1,5 +1,9 @@
+use super::RenderStats;
+
 #[derive(Copy, Clone, Debug)]
 pub struct RenderInfo {
     pub timestamp: f64,
     pub timestamp_delta: Option<f64>,
+    /// `Some` when the instrumentation is enabled - see `App::set_instrumentation`.
+    pub stats: Option<RenderStats>,
 }
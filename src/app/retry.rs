@@ -0,0 +1,284 @@
+//! Retry policies - see `streams::retry` and `streams::backoff_with_policy`.
+
+use rand::{rngs::SmallRng, Rng, RngCore, SeedableRng};
+use std::fmt;
+
+// ------ Jitter ------
+
+/// Randomization of retry delays - it prevents clients from retrying at the same time.
+///
+/// See [Exponential Backoff And Jitter](https://aws.amazon.com/blogs/architecture/exponential-backoff-and-jitter/).
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Default)]
+pub enum Jitter {
+    /// The exponential delay without randomization.
+    None,
+    /// A random delay between `0` and the exponential delay.
+    #[default]
+    Full,
+    /// A half of the exponential delay plus a random delay up to the other half.
+    Equal,
+    /// A random delay between the base delay and the previous delay times the multiplier.
+    Decorrelated,
+}
+
+// ------ RetryPolicy ------
+
+/// Configures delays between retries.
+///
+/// The delay before the retry `n` (starting from `0`) is
+/// `min(max_delay, base_delay * multiplier^n)` randomized by `Jitter`.
+///
+/// # Example
+///
+/// ```rust,no_run
+///let policy = RetryPolicy::new()
+///    .base_delay_ms(500)
+///    .max_delay_ms(10_000)
+///    .max_retries(5)
+///    .jitter(Jitter::Equal);
+/// ```
+pub struct RetryPolicy {
+    base_delay_ms: u32,
+    multiplier: f64,
+    max_delay_ms: u32,
+    max_retries: Option<u32>,
+    jitter: Jitter,
+    rng: Box<dyn RngCore>,
+}
+
+impl RetryPolicy {
+    /// Base delay `1` second, multiplier `2`, max delay `32` seconds,
+    /// unlimited retries and `Jitter::Full`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub const fn base_delay_ms(mut self, base_delay_ms: u32) -> Self {
+        self.base_delay_ms = base_delay_ms;
+        self
+    }
+
+    pub const fn multiplier(mut self, multiplier: f64) -> Self {
+        self.multiplier = multiplier;
+        self
+    }
+
+    pub const fn max_delay_ms(mut self, max_delay_ms: u32) -> Self {
+        self.max_delay_ms = max_delay_ms;
+        self
+    }
+
+    /// The number of retries after the first attempt. Unlimited by default.
+    pub const fn max_retries(mut self, max_retries: u32) -> Self {
+        self.max_retries = Some(max_retries);
+        self
+    }
+
+    pub const fn jitter(mut self, jitter: Jitter) -> Self {
+        self.jitter = jitter;
+        self
+    }
+
+    /// Set the random number generator used for jitter - e.g. a seeded one in tests.
+    pub fn rng(mut self, rng: impl RngCore + 'static) -> Self {
+        self.rng = Box::new(rng);
+        self
+    }
+
+    /// Delays in milliseconds before individual retries.
+    /// The iterator ends when the max retries have been reached.
+    pub fn delays(self) -> Delays {
+        Delays {
+            previous_delay_ms: self.base_delay_ms,
+            retries: 0,
+            policy: self,
+        }
+    }
+
+    fn exponential_delay_ms(&self, retries: u32) -> u32 {
+        let retries = i32::try_from(retries).unwrap_or(i32::MAX);
+        let delay = f64::from(self.base_delay_ms) * self.multiplier.powi(retries);
+        // `as` saturates, `NaN` is converted to `0`.
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        let delay = delay as u32;
+        u32::min(delay, self.max_delay_ms)
+    }
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            base_delay_ms: 1000,
+            multiplier: 2.,
+            max_delay_ms: 32_000,
+            max_retries: None,
+            jitter: Jitter::default(),
+            rng: Box::new(SmallRng::from_entropy()),
+        }
+    }
+}
+
+impl fmt::Debug for RetryPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RetryPolicy")
+            .field("base_delay_ms", &self.base_delay_ms)
+            .field("multiplier", &self.multiplier)
+            .field("max_delay_ms", &self.max_delay_ms)
+            .field("max_retries", &self.max_retries)
+            .field("jitter", &self.jitter)
+            .field("rng", &"Box<dyn RngCore>")
+            .finish()
+    }
+}
+
+// ------ Delays ------
+
+/// See `RetryPolicy::delays`.
+#[derive(Debug)]
+pub struct Delays {
+    policy: RetryPolicy,
+    retries: u32,
+    previous_delay_ms: u32,
+}
+
+impl Iterator for Delays {
+    type Item = u32;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if matches!(self.policy.max_retries, Some(max_retries) if self.retries >= max_retries) {
+            return None;
+        }
+        let policy = &mut self.policy;
+        let delay = policy.exponential_delay_ms(self.retries);
+
+        let delay = match policy.jitter {
+            Jitter::None => delay,
+            Jitter::Full => policy.rng.gen_range(0..=delay),
+            Jitter::Equal => delay / 2 + policy.rng.gen_range(0..=delay - delay / 2),
+            Jitter::Decorrelated => {
+                let min = policy.base_delay_ms;
+                // `as` saturates.
+                #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+                let max = (f64::from(self.previous_delay_ms) * policy.multiplier) as u32;
+                let delay = policy.rng.gen_range(min..=u32::max(min, max));
+                u32::min(delay, policy.max_delay_ms)
+            }
+        };
+        self.retries += 1;
+        self.previous_delay_ms = delay;
+        Some(delay)
+    }
+}
+
+// ------ RetryEvent ------
+
+/// Progress of `streams::retry`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum RetryEvent<T, E> {
+    /// The attempt failed, the next one starts after `delay_ms`.
+    Retrying {
+        attempt: u32,
+        error: E,
+        delay_ms: u32,
+    },
+    /// The attempt succeeded. This is the last event.
+    Succeeded { attempts: u32, value: T },
+    /// The last allowed attempt failed. This is the last event.
+    Failed { attempts: u32, error: E },
+}
+
+// ------ ------ Tests ------ ------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wasm_bindgen_test::*;
+
+    fn policy(jitter: Jitter) -> RetryPolicy {
+        RetryPolicy::new()
+            .base_delay_ms(100)
+            .max_delay_ms(1000)
+            .max_retries(6)
+            .jitter(jitter)
+            .rng(SmallRng::seed_from_u64(42))
+    }
+
+    #[wasm_bindgen_test]
+    fn exponential_delays_are_truncated() {
+        let delays: Vec<_> = policy(Jitter::None).delays().collect();
+        assert_eq!(delays, vec![100, 200, 400, 800, 1000, 1000]);
+    }
+
+    #[wasm_bindgen_test]
+    fn jittered_delays_are_in_bounds() {
+        let exponential: Vec<_> = policy(Jitter::None).delays().collect();
+
+        for (delay, max) in policy(Jitter::Full).delays().zip(&exponential) {
+            assert!(delay <= *max);
+        }
+        for (delay, max) in policy(Jitter::Equal).delays().zip(&exponential) {
+            assert!(delay >= max / 2 && delay <= *max);
+        }
+        for delay in policy(Jitter::Decorrelated).delays() {
+            assert!((100..=1000).contains(&delay));
+        }
+    }
+
+    #[wasm_bindgen_test]
+    fn retry_stream_reports_attempts() {
+        use crate::app::streams;
+        use futures::{executor::block_on, future, stream::StreamExt};
+
+        let mut responses = vec![Ok("user"), Err("503"), Err("500")];
+        let events = block_on(
+            streams::retry(
+                RetryPolicy::new().base_delay_ms(0).max_retries(5),
+                move || future::ready(responses.pop().unwrap()),
+                |event| event,
+            )
+            .collect::<Vec<_>>(),
+        );
+        assert_eq!(
+            events,
+            vec![
+                RetryEvent::Retrying {
+                    attempt: 1,
+                    error: "500",
+                    delay_ms: 0
+                },
+                RetryEvent::Retrying {
+                    attempt: 2,
+                    error: "503",
+                    delay_ms: 0
+                },
+                RetryEvent::Succeeded {
+                    attempts: 3,
+                    value: "user"
+                },
+            ]
+        );
+
+        let events = block_on(
+            streams::retry(
+                RetryPolicy::new().max_retries(0),
+                || future::ready(Err::<(), _>("404")),
+                |event| event,
+            )
+            .collect::<Vec<_>>(),
+        );
+        assert_eq!(
+            events,
+            vec![RetryEvent::Failed {
+                attempts: 1,
+                error: "404"
+            }]
+        );
+    }
+
+    #[wasm_bindgen_test]
+    fn seeded_rng_makes_delays_deterministic() {
+        let a: Vec<_> = policy(Jitter::Decorrelated).delays().collect();
+        let b: Vec<_> = policy(Jitter::Decorrelated).delays().collect();
+        assert_eq!(a, b);
+    }
+}
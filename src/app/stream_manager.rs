@@ -1,4 +1,5 @@
-use futures::future::{abortable, ready, AbortHandle, FutureExt};
+use super::task_registry::TaskRegistry;
+use futures::future::{abortable, ready, AbortHandle, Future, FutureExt};
 use futures::stream::{Stream, StreamExt};
 use wasm_bindgen_futures::spawn_local;
 
@@ -7,19 +8,32 @@ use wasm_bindgen_futures::spawn_local;
 pub(crate) struct StreamManager;
 
 impl StreamManager {
-    pub fn stream(stream: impl Stream<Item = ()> + 'static) {
-        // Convert `Stream` to `Future` and execute it. The stream is "leaked" into the JS world.
-        spawn_local(stream.for_each(|_| ready(())));
+    pub fn stream(stream: impl Stream<Item = ()> + 'static, tasks: &TaskRegistry) {
+        // Execute the stream converted to `Future`. The stream is "leaked" into the JS world.
+        spawn_local(tasks.register(Self::into_future(stream)));
     }
 
-    pub fn stream_with_handle(stream: impl Stream<Item = ()> + 'static) -> StreamHandle {
-        // Convert `Stream` to `Future`.
-        let stream = stream.for_each(|_| ready(()));
+    pub fn stream_with_handle(
+        stream: impl Stream<Item = ()> + 'static,
+        tasks: &TaskRegistry,
+    ) -> StreamHandle {
+        let (stream, handle) = Self::abortable(stream);
+        spawn_local(tasks.register(stream));
+        handle
+    }
+
+    /// Convert `Stream` to `Future`.
+    pub fn into_future(stream: impl Stream<Item = ()> + 'static) -> impl Future<Output = ()> {
+        stream.for_each(|_| ready(()))
+    }
+
+    pub fn abortable(
+        stream: impl Stream<Item = ()> + 'static,
+    ) -> (impl Future<Output = ()> + 'static, StreamHandle) {
         // Create `AbortHandle`.
-        let (stream, handle) = abortable(stream);
+        let (stream, handle) = abortable(Self::into_future(stream));
         // Ignore the error when the future is aborted. I.e. just stop the stream.
-        spawn_local(stream.map(move |_| ()));
-        StreamHandle(handle)
+        (stream.map(move |_| ()), StreamHandle(handle))
     }
 }
 
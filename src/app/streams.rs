@@ -1,15 +1,28 @@
+use crate::app::animation::FrameInfo;
+use crate::app::clock;
+use crate::app::retry::{RetryEvent, RetryPolicy};
 use crate::browser::util::{document, window};
-use crate::virtual_dom::Ev;
-use futures::stream::{Stream, StreamExt};
-use gloo_timers::future::IntervalStream;
-use web_sys::Event;
+use crate::virtual_dom::{ElRef, Ev};
+use futures::future::{self, Future};
+use futures::stream::{self, Stream, StreamExt};
+use wasm_bindgen::JsCast;
+use web_sys::{
+    DomRectReadOnly, Element, Event, IntersectionObserverEntry, IntersectionObserverInit,
+    MediaQueryListEvent,
+};
 
 mod event_stream;
-use event_stream::EventStream;
+pub(crate) use event_stream::EventStream;
 
 mod backoff_stream;
 use backoff_stream::BackoffStream;
 
+mod observer_stream;
+use observer_stream::ObserverStream;
+
+mod animation_frame_stream;
+use animation_frame_stream::AnimationFrameStream;
+
 // ------ Interval stream ------
 
 /// Stream no values on predefined time interval in milliseconds.
@@ -31,7 +44,36 @@ pub fn interval<MsU>(
     ms: u32,
     handler: impl FnOnce() -> MsU + Clone + 'static,
 ) -> impl Stream<Item = MsU> {
-    IntervalStream::new(ms).map(move |_| handler.clone()())
+    clock::interval(ms).map(move |_| handler.clone()())
+}
+
+// ------ Animation frame stream ------
+
+/// Stream a `FrameInfo` on every animation frame.
+/// Animation frames are requested until the stream is dropped.
+///
+/// Handler has to return `Msg`, `Option<Msg>` or `()`.
+///
+/// # Example
+///
+/// ```rust,no_run
+///model.frames = Some(orders.stream_with_handle(streams::animation_frame(Msg::OnFrame)));
+/// ...
+///Msg::OnFrame(frame) => {
+///    if let Some(msg) = model.tween.animate(&mut model.x, frame.delta(), || Msg::Finished) {
+///        orders.send_msg(msg);
+///    }
+///}
+/// ```
+///
+/// # Panics
+///
+/// Panics when the handler doesn't return `Msg`, `Option<Msg>` or `()`.
+/// (It will be changed to a compile-time error).
+pub fn animation_frame<MsU>(
+    handler: impl FnOnce(FrameInfo) -> MsU + Clone + 'static,
+) -> impl Stream<Item = MsU> {
+    AnimationFrameStream::new().map(move |frame| handler.clone()(frame))
 }
 
 // ------ Backoff stream ------
@@ -63,6 +105,113 @@ pub fn backoff<MsU>(
     BackoffStream::new(max_seconds.unwrap_or(32)).map(move |retries| handler.clone()(retries))
 }
 
+/// Stream retries count with delays configured by `policy`.
+/// The stream ends when the policy's max retries have been reached.
+///
+/// Handler receives the number of retries (starting from 1); Has to return `Msg`, `Option<Msg>` or `()`.
+///
+/// # Example
+///
+/// ```rust,no_run
+///let policy = RetryPolicy::new().base_delay_ms(500).max_retries(5).jitter(Jitter::Equal);
+///orders.stream_with_handle(streams::backoff_with_policy(policy, Msg::Reconnect));
+/// ```
+///
+/// # Panics
+///
+/// Panics when the handler doesn't return `Msg`, `Option<Msg>` or `()`.
+/// (It will be changed to a compile-time error).
+pub fn backoff_with_policy<MsU>(
+    policy: RetryPolicy,
+    handler: impl FnOnce(usize) -> MsU + Clone + 'static,
+) -> impl Stream<Item = MsU> {
+    stream::iter(policy.delays())
+        .then(sleep)
+        .enumerate()
+        .map(move |(index, _)| handler.clone()(index + 1))
+}
+
+// ------ Retry stream ------
+
+/// Call `request` until it succeeds or the `policy`'s max retries have been reached.
+///
+/// Streams a `RetryEvent` for each failed attempt and a final `RetryEvent::Succeeded`
+/// or `RetryEvent::Failed`. Dropping the stream cancels the pending request or delay.
+///
+/// Handler has to return `Msg`, `Option<Msg>` or `()`.
+///
+/// # Example
+///
+/// ```rust,no_run
+///orders.stream(streams::retry(
+///    RetryPolicy::new().max_retries(3),
+///    || async { fetch("/api/user").await?.check_status()?.json::<User>().await },
+///    Msg::UserFetch,  // `Msg::UserFetch(RetryEvent<User, FetchError>)`
+///));
+/// ```
+///
+/// # Panics
+///
+/// Panics when the handler doesn't return `Msg`, `Option<Msg>` or `()`.
+/// (It will be changed to a compile-time error).
+pub fn retry<T, E, Fut, MsU>(
+    policy: RetryPolicy,
+    request: impl FnMut() -> Fut + 'static,
+    handler: impl FnOnce(RetryEvent<T, E>) -> MsU + Clone + 'static,
+) -> impl Stream<Item = MsU>
+where
+    T: 'static,
+    E: 'static,
+    Fut: Future<Output = Result<T, E>> + 'static,
+{
+    struct State<R> {
+        request: R,
+        delays: crate::app::retry::Delays,
+        attempts: u32,
+        delay_ms: Option<u32>,
+    }
+
+    let state = State {
+        request,
+        delays: policy.delays(),
+        attempts: 0,
+        delay_ms: None,
+    };
+
+    stream::unfold(Some(state), |state| async move {
+        let mut state = state?;
+        if let Some(delay_ms) = state.delay_ms.take() {
+            sleep(delay_ms).await;
+        }
+        state.attempts += 1;
+        let attempts = state.attempts;
+
+        let event = match (state.request)().await {
+            Ok(value) => return Some((RetryEvent::Succeeded { attempts, value }, None)),
+            Err(error) => match state.delays.next() {
+                Some(delay_ms) => {
+                    state.delay_ms = Some(delay_ms);
+                    RetryEvent::Retrying {
+                        attempt: attempts,
+                        error,
+                        delay_ms,
+                    }
+                }
+                None => return Some((RetryEvent::Failed { attempts, error }, None)),
+            },
+        };
+        Some((event, Some(state)))
+    })
+    .map(move |event| handler.clone()(event))
+}
+
+/// Zero delays are skipped to not wait for the next timer tick.
+async fn sleep(delay_ms: u32) {
+    if delay_ms > 0 {
+        clock::sleep(delay_ms).await;
+    }
+}
+
 // ------ Window Event stream ------
 
 /// Stream `Window` `web_sys::Event`s.
@@ -110,3 +259,183 @@ pub fn document_event<MsU>(
 ) -> impl Stream<Item = MsU> {
     EventStream::new(&document(), trigger.into()).map(move |event| handler.clone()(event))
 }
+
+// ------ Media query stream ------
+
+/// Stream whether the document matches the media `query`.
+/// The current state is streamed first, then its changes.
+///
+/// Handler has to return `Msg`, `Option<Msg>` or `()`.
+///
+/// # Example
+///
+/// ```rust,no_run
+///orders.stream(streams::media_query("(prefers-color-scheme: dark)", Msg::DarkModeChanged));
+///orders.stream_with_handle(streams::media_query("(min-width: 768px)", |wide| log!(wide)));
+/// ```
+///
+/// # Panics
+///
+/// Panics when the `query` is invalid or the handler doesn't return `Msg`, `Option<Msg>` or `()`.
+/// (The latter will be changed to a compile-time error).
+pub fn media_query<MsU>(
+    query: &str,
+    handler: impl FnOnce(bool) -> MsU + Clone + 'static,
+) -> impl Stream<Item = MsU> {
+    let media_query_list = window()
+        .match_media(query)
+        .expect("match media query")
+        .expect("get `MediaQueryList`");
+
+    stream::once(future::ready(media_query_list.matches()))
+        .chain(
+            EventStream::new(&media_query_list, "change")
+                .map(|event: MediaQueryListEvent| event.matches()),
+        )
+        .map(move |matches| handler.clone()(matches))
+}
+
+// ------ Visibility stream ------
+
+/// Stream whether the document is visible - i.e. the tab isn't hidden or the window minimized.
+/// The current state is streamed first, then its changes.
+///
+/// Handler has to return `Msg`, `Option<Msg>` or `()`.
+///
+/// # Example
+///
+/// ```rust,no_run
+///orders.stream(streams::visibility(Msg::VisibilityChanged));
+/// ```
+///
+/// # Panics
+///
+/// Panics when the handler doesn't return `Msg`, `Option<Msg>` or `()`.
+/// (It will be changed to a compile-time error).
+pub fn visibility<MsU>(
+    handler: impl FnOnce(bool) -> MsU + Clone + 'static,
+) -> impl Stream<Item = MsU> {
+    stream::once(future::ready(!document().hidden()))
+        .chain(
+            EventStream::<Event>::new(&document(), Ev::VisibilityChange)
+                .map(|_| !document().hidden()),
+        )
+        .map(move |visible| handler.clone()(visible))
+}
+
+// ------ Online status stream ------
+
+/// Stream whether the browser is online.
+/// The current status is streamed first, then its changes.
+///
+/// Handler has to return `Msg`, `Option<Msg>` or `()`.
+///
+/// # Example
+///
+/// ```rust,no_run
+///orders.stream(streams::online_status(Msg::OnlineStatusChanged));
+/// ```
+///
+/// # Panics
+///
+/// Panics when the handler doesn't return `Msg`, `Option<Msg>` or `()`.
+/// (It will be changed to a compile-time error).
+pub fn online_status<MsU>(
+    handler: impl FnOnce(bool) -> MsU + Clone + 'static,
+) -> impl Stream<Item = MsU> {
+    let online = EventStream::<Event>::new(&window(), Ev::Online).map(|_| true);
+    let offline = EventStream::<Event>::new(&window(), Ev::Offline).map(|_| false);
+
+    stream::once(future::ready(window().navigator().on_line()))
+        .chain(stream::select(online, offline))
+        .map(move |online| handler.clone()(online))
+}
+
+// ------ Resize observer stream ------
+
+/// Stream content rectangles of the referenced element when its size changes.
+/// It uses [ResizeObserver](https://developer.mozilla.org/en-US/docs/Web/API/ResizeObserver).
+///
+/// The element has to be already rendered - e.g. create the stream in response
+/// to a message from `orders.after_next_render`. Otherwise the stream ends immediately.
+///
+/// Handler has to return `Msg`, `Option<Msg>` or `()`.
+///
+/// # Example
+///
+/// ```rust,no_run
+///Msg::Rendered => {
+///    model.resize_stream = Some(orders.stream_with_handle(
+///        streams::resize_observer(&model.container, |rect| Msg::Resized(rect.width(), rect.height()))
+///    ));
+///}
+/// ```
+///
+/// # Panics
+///
+/// Panics when the handler doesn't return `Msg`, `Option<Msg>` or `()`.
+/// (It will be changed to a compile-time error).
+pub fn resize_observer<E: Clone + JsCast, MsU>(
+    el_ref: &ElRef<E>,
+    handler: impl FnOnce(DomRectReadOnly) -> MsU + Clone + 'static,
+) -> impl Stream<Item = MsU> {
+    ObserverStream::resize(el_ref.map_type::<Element>().get())
+        .map(move |entry| handler.clone()(entry.content_rect()))
+}
+
+// ------ Intersection observer stream ------
+
+/// Stream `IntersectionObserverEntry`s of the referenced element.
+/// It uses [IntersectionObserver](https://developer.mozilla.org/en-US/docs/Web/API/IntersectionObserver)
+/// with the default options - see `intersection_observer_with_options`.
+///
+/// The element has to be already rendered - e.g. create the stream in response
+/// to a message from `orders.after_next_render`. Otherwise the stream ends immediately.
+///
+/// Handler has to return `Msg`, `Option<Msg>` or `()`.
+///
+/// # Example
+///
+/// ```rust,no_run
+///Msg::Rendered => {
+///    model.visibility_stream = Some(orders.stream_with_handle(
+///        streams::intersection_observer(&model.footer, |entry| Msg::FooterVisible(entry.is_intersecting()))
+///    ));
+///}
+/// ```
+///
+/// # Panics
+///
+/// Panics when the handler doesn't return `Msg`, `Option<Msg>` or `()`.
+/// (It will be changed to a compile-time error).
+pub fn intersection_observer<E: Clone + JsCast, MsU>(
+    el_ref: &ElRef<E>,
+    handler: impl FnOnce(IntersectionObserverEntry) -> MsU + Clone + 'static,
+) -> impl Stream<Item = MsU> {
+    intersection_observer_with_options(el_ref, &IntersectionObserverInit::new(), handler)
+}
+
+/// Stream `IntersectionObserverEntry`s of the referenced element - see `intersection_observer`.
+///
+/// # Example
+///
+/// ```rust,no_run
+///let mut options = IntersectionObserverInit::new();
+///options.threshold(&JsValue::from(1));
+///orders.stream_with_handle(
+///    streams::intersection_observer_with_options(&model.red_box, &options, Msg::Observed)
+///);
+/// ```
+///
+/// # Panics
+///
+/// Panics when `options` are invalid or the handler doesn't return `Msg`, `Option<Msg>` or `()`.
+/// (The latter will be changed to a compile-time error).
+pub fn intersection_observer_with_options<E: Clone + JsCast, MsU>(
+    el_ref: &ElRef<E>,
+    options: &IntersectionObserverInit,
+    handler: impl FnOnce(IntersectionObserverEntry) -> MsU + Clone + 'static,
+) -> impl Stream<Item = MsU> {
+    ObserverStream::intersection(el_ref.map_type::<Element>().get(), options)
+        .map(move |entry| handler.clone()(entry))
+}
@@ -0,0 +1,88 @@
+use crate::app::animation::FrameInfo;
+use crate::browser::util::window;
+use futures::stream::Stream;
+use std::cell::RefCell;
+use std::pin::Pin;
+use std::rc::Rc;
+use std::task::{Context, Poll, Waker};
+use wasm_bindgen::closure::Closure;
+use wasm_bindgen::JsCast;
+
+// ------ AnimationFrameStream ------
+
+#[derive(Default)]
+struct State {
+    timestamp: Option<f64>,
+    waker: Option<Waker>,
+}
+
+/// Requests the next animation frame whenever it's polled. The request is cancelled on drop.
+pub struct AnimationFrameStream {
+    state: Rc<RefCell<State>>,
+    callback: Closure<dyn FnMut(f64)>,
+    request_id: Option<i32>,
+    previous_timestamp: Option<f64>,
+}
+
+impl AnimationFrameStream {
+    pub fn new() -> Self {
+        let state = Rc::new(RefCell::new(State::default()));
+
+        // @TODO replace with `Closure::new` once stable (or use the Seed's temporary one).
+        let callback = Closure::wrap(Box::new({
+            let state = Rc::clone(&state);
+            move |timestamp| {
+                let mut state = state.borrow_mut();
+                state.timestamp = Some(timestamp);
+                if let Some(waker) = state.waker.take() {
+                    waker.wake();
+                }
+            }
+        }) as Box<dyn FnMut(f64)>);
+
+        Self {
+            state,
+            callback,
+            request_id: None,
+            previous_timestamp: None,
+        }
+    }
+}
+
+impl Stream for AnimationFrameStream {
+    type Item = FrameInfo;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<Self::Item>> {
+        let timestamp = self.state.borrow_mut().timestamp.take();
+        if let Some(timestamp) = timestamp {
+            self.request_id = None;
+            let timestamp_delta = self
+                .previous_timestamp
+                .replace(timestamp)
+                .map(|previous_timestamp| timestamp - previous_timestamp);
+            return Poll::Ready(Some(FrameInfo {
+                timestamp,
+                timestamp_delta,
+            }));
+        }
+
+        self.state.borrow_mut().waker = Some(cx.waker().clone());
+        if self.request_id.is_none() {
+            let request_id = window()
+                .request_animation_frame(self.callback.as_ref().unchecked_ref())
+                .expect("request animation frame");
+            self.request_id = Some(request_id);
+        }
+        Poll::Pending
+    }
+}
+
+impl Drop for AnimationFrameStream {
+    fn drop(&mut self) {
+        if let Some(request_id) = self.request_id {
+            window()
+                .cancel_animation_frame(request_id)
+                .expect("cancel animation frame");
+        }
+    }
+}
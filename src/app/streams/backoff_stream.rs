@@ -1,9 +1,8 @@
-use futures::channel::mpsc;
+use crate::app::clock::{self, Sleep};
 use futures::stream::Stream;
-use gloo_timers::callback::Timeout;
 use rand::{rngs::SmallRng, Rng, SeedableRng};
+use std::future::Future;
 use std::pin::Pin;
-use std::rc::Rc;
 use std::task::{Context, Poll};
 
 // ------ BackoffStream ------
@@ -13,23 +12,16 @@ use std::task::{Context, Poll};
 pub struct BackoffStream {
     max_seconds: u32,
     retries: usize,
-    timeout: Timeout,
-    tick_sender: Rc<mpsc::UnboundedSender<()>>,
-    tick_receiver: mpsc::UnboundedReceiver<()>,
+    sleep: Sleep,
 }
 
 impl BackoffStream {
     pub fn new(max_seconds: u32) -> Self {
-        let (tick_sender, tick_receiver) = mpsc::unbounded();
-        let tick_sender = Rc::new(tick_sender);
-
         let retries = 0;
         Self {
             max_seconds,
             retries,
-            timeout: start_timeout(wait_time(retries, max_seconds), &tick_sender),
-            tick_sender,
-            tick_receiver,
+            sleep: clock::sleep(wait_time(retries, max_seconds)),
         }
     }
 }
@@ -38,14 +30,12 @@ impl Stream for BackoffStream {
     type Item = usize;
 
     fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<Self::Item>> {
-        match Stream::poll_next(Pin::new(&mut self.tick_receiver), cx) {
-            Poll::Ready(Some(_)) => {
+        match Pin::new(&mut self.sleep).poll(cx) {
+            Poll::Ready(()) => {
                 self.retries += 1;
-                self.timeout =
-                    start_timeout(wait_time(self.retries, self.max_seconds), &self.tick_sender);
+                self.sleep = clock::sleep(wait_time(self.retries, self.max_seconds));
                 Poll::Ready(Some(self.retries))
             }
-            Poll::Ready(None) => Poll::Ready(None),
             Poll::Pending => Poll::Pending,
         }
     }
@@ -63,10 +53,3 @@ fn wait_time(retries: usize, max_seconds: u32) -> u32 {
 
     u32::min(duration, max_duration)
 }
-
-fn start_timeout(ms: u32, tick_sender: &Rc<mpsc::UnboundedSender<()>>) -> Timeout {
-    let tick_sender = Rc::clone(tick_sender);
-    Timeout::new(ms, move || {
-        tick_sender.unbounded_send(()).expect("send backoff tick");
-    })
-}
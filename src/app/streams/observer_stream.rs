@@ -0,0 +1,158 @@
+use crate::browser::util::window;
+use futures::channel::mpsc::{unbounded, UnboundedReceiver};
+use futures::stream::Stream;
+use js_sys::{Array, Function, Reflect};
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use wasm_bindgen::closure::Closure;
+use wasm_bindgen::{JsCast, JsValue};
+use web_sys::{
+    DomRectReadOnly, Element, IntersectionObserver, IntersectionObserverEntry,
+    IntersectionObserverInit,
+};
+
+// ------ ResizeObserver ------
+
+// @TODO Replace with `web_sys::ResizeObserver` once it's available in the supported `web_sys` versions.
+/// A minimal `ResizeObserver` binding through `js_sys::Reflect`.
+struct ResizeObserver(JsValue);
+
+impl ResizeObserver {
+    fn new(callback: &Function) -> Self {
+        let constructor = Reflect::get(&window(), &JsValue::from("ResizeObserver"))
+            .expect("get `ResizeObserver`")
+            .unchecked_into::<Function>();
+        let observer =
+            Reflect::construct(&constructor, &Array::of1(callback)).expect("create ResizeObserver");
+        Self(observer)
+    }
+
+    fn observe(&self, target: &Element) {
+        self.call("observe", &Array::of1(target));
+    }
+
+    fn disconnect(&self) {
+        self.call("disconnect", &Array::new());
+    }
+
+    fn call(&self, method: &str, args: &Array) {
+        let method = Reflect::get(&self.0, &JsValue::from(method))
+            .expect("get `ResizeObserver` method")
+            .unchecked_into::<Function>();
+        Reflect::apply(&method, &self.0, args).expect("call `ResizeObserver` method");
+    }
+}
+
+/// `ResizeObserverEntry` - see `ResizeObserver`.
+pub struct ResizeObserverEntry(JsValue);
+
+impl ResizeObserverEntry {
+    pub fn content_rect(&self) -> DomRectReadOnly {
+        Reflect::get(&self.0, &JsValue::from("contentRect"))
+            .expect("get `contentRect`")
+            .unchecked_into()
+    }
+}
+
+impl From<JsValue> for ResizeObserverEntry {
+    fn from(entry: JsValue) -> Self {
+        Self(entry)
+    }
+}
+
+// ------ Observer ------
+
+enum Observer {
+    Resize(ResizeObserver),
+    Intersection(IntersectionObserver),
+}
+
+impl Observer {
+    fn disconnect(&self) {
+        match self {
+            Self::Resize(observer) => observer.disconnect(),
+            Self::Intersection(observer) => observer.disconnect(),
+        }
+    }
+}
+
+// ------ ObserverStream ------
+
+/// Streams entries of `ResizeObserver` or `IntersectionObserver` observing one element.
+/// The observer is disconnected on drop.
+///
+/// The stream ends immediately when there is no element to observe.
+pub struct ObserverStream<E> {
+    observer: Option<Observer>,
+    _callback: Closure<dyn Fn(Array)>,
+    receiver: UnboundedReceiver<E>,
+}
+
+impl<E> ObserverStream<E>
+where
+    E: From<JsValue> + 'static,
+{
+    fn new(element: Option<Element>, create_observer: impl FnOnce(&Function) -> Observer) -> Self {
+        let (sender, receiver) = unbounded();
+
+        // @TODO replace with `Closure::new` once stable (or use the Seed's temporary one).
+        let callback = Closure::wrap(Box::new(move |entries: Array| {
+            for entry in entries.iter() {
+                sender.unbounded_send(E::from(entry)).unwrap();
+            }
+        }) as Box<dyn Fn(Array)>);
+
+        let observer = element.map(|element| {
+            let observer = create_observer(callback.as_ref().unchecked_ref());
+            match &observer {
+                Observer::Resize(observer) => observer.observe(&element),
+                Observer::Intersection(observer) => observer.observe(&element),
+            }
+            observer
+        });
+
+        Self {
+            observer,
+            _callback: callback,
+            receiver,
+        }
+    }
+}
+
+impl ObserverStream<ResizeObserverEntry> {
+    pub fn resize(element: Option<Element>) -> Self {
+        Self::new(element, |callback| {
+            Observer::Resize(ResizeObserver::new(callback))
+        })
+    }
+}
+
+impl ObserverStream<IntersectionObserverEntry> {
+    pub fn intersection(element: Option<Element>, options: &IntersectionObserverInit) -> Self {
+        Self::new(element, |callback| {
+            Observer::Intersection(
+                IntersectionObserver::new_with_options(callback, options)
+                    .expect("create IntersectionObserver"),
+            )
+        })
+    }
+}
+
+impl<E> Stream for ObserverStream<E> {
+    type Item = E;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<Self::Item>> {
+        if self.observer.is_none() {
+            return Poll::Ready(None);
+        }
+        Stream::poll_next(Pin::new(&mut self.receiver), cx)
+    }
+}
+
+impl<E> Drop for ObserverStream<E> {
+    fn drop(&mut self) {
+        if let Some(observer) = &self.observer {
+            observer.disconnect();
+        }
+    }
+}
@@ -8,62 +8,44 @@ use uuid::Uuid;
 // ------ SubManager ------
 
 type Subscriptions<Ms> = HashMap<TypeId, IndexMap<Uuid, Subscription<Ms>>>;
+type SubFilter<SubMs> = Rc<dyn Fn(&SubMs) -> bool>;
 
 #[derive(Default)]
 pub(crate) struct SubManager<Ms> {
     subs: Rc<RefCell<Subscriptions<Ms>>>,
+    /// The last retained notification for each `SubMs` type.
+    retained: HashMap<TypeId, Notification>,
 }
 
 impl<Ms: 'static> SubManager<Ms> {
     pub fn new() -> Self {
         Self {
             subs: Rc::new(RefCell::new(HashMap::new())),
+            retained: HashMap::new(),
         }
     }
 
+    /// Returns the message created from the retained notification, if any.
     pub fn subscribe<SubMs: 'static + Clone>(
         &mut self,
         handler: impl FnOnce(SubMs) -> Option<Ms> + Clone + 'static,
-    ) {
-        self.subscribe_with_priority(handler, i8::default());
-    }
-
-    pub(crate) fn subscribe_with_priority<SubMs: 'static + Clone>(
-        &mut self,
-        handler: impl FnOnce(SubMs) -> Option<Ms> + Clone + 'static,
-        priority: i8,
-    ) {
-        let sub = Subscription::new_with_priority(handler, priority);
-        let (type_id, id) = (sub.type_id, sub.id);
-
-        let mut subs = self.subs.borrow_mut();
-        subs.entry(type_id)
-            .or_insert_with(IndexMap::new)
-            .insert(id, sub);
-
-        subs.entry(type_id).and_modify(|subs_group| {
-            subs_group.sort_by(|_, sub_a, _, sub_b| Ord::cmp(&sub_b.priority, &sub_a.priority));
-        });
+        options: SubOptions<SubMs>,
+    ) -> Option<Ms> {
+        let sub = Subscription::new(handler, options);
+        self.add(sub).1
     }
 
+    /// Returns the message created from the retained notification, if any.
     pub fn subscribe_with_handle<SubMs: 'static + Clone>(
         &mut self,
         handler: impl FnOnce(SubMs) -> Option<Ms> + Clone + 'static,
-    ) -> SubHandle {
-        let sub = Subscription::new(handler);
-        let (type_id, id) = (sub.type_id, sub.id);
-
-        let mut subs = self.subs.borrow_mut();
-        subs.entry(type_id)
-            .or_insert_with(IndexMap::new)
-            .insert(id, sub);
-
-        subs.entry(type_id).and_modify(|subs_group| {
-            subs_group.sort_by(|_, sub_a, _, sub_b| Ord::cmp(&sub_b.priority, &sub_a.priority));
-        });
+        options: SubOptions<SubMs>,
+    ) -> (SubHandle, Option<Ms>) {
+        let sub = Subscription::new(handler, options);
+        let ((type_id, id), replayed_msg) = self.add(sub);
 
         let subs = Rc::clone(&self.subs);
-        SubHandle {
+        let sub_handle = SubHandle {
             unsubscriber: Box::new(move || {
                 subs.borrow_mut()
                     .get_mut(&type_id)
@@ -71,10 +53,32 @@ impl<Ms: 'static> SubManager<Ms> {
                     .remove(&id)
                     .expect("remove subscription");
             }),
-        }
+        };
+        (sub_handle, replayed_msg)
+    }
+
+    fn add(&mut self, sub: Subscription<Ms>) -> ((TypeId, Uuid), Option<Ms>) {
+        let (type_id, id) = (sub.type_id, sub.id);
+        let replayed_msg = self
+            .retained
+            .get(&type_id)
+            .and_then(|notification| (sub.handler)(Rc::clone(&notification.message)));
+
+        let mut subs = self.subs.borrow_mut();
+        let subs_group = subs.entry(type_id).or_default();
+        subs_group.insert(id, sub);
+        subs_group.sort_by(|_, sub_a, _, sub_b| Ord::cmp(&sub_b.priority, &sub_a.priority));
+
+        ((type_id, id), replayed_msg)
     }
 
-    pub fn notify(&self, notification: &Notification) -> Vec<Box<dyn FnOnce() -> Option<Ms>>> {
+    /// Returns triggered handlers of the current subscribers.
+    /// The notification is remembered for future subscribers if it's retained.
+    pub fn notify(&mut self, notification: &Notification) -> Vec<Box<dyn FnOnce() -> Option<Ms>>> {
+        if notification.retained {
+            self.retained
+                .insert(notification.type_id, notification.clone());
+        }
         self.subs
             .borrow()
             .get(&notification.type_id)
@@ -94,12 +98,80 @@ impl<Ms: 'static> SubManager<Ms> {
     }
 }
 
+// ------ SubOptions ------
+
+/// Options for `Orders::subscribe_with_options`.
+///
+/// # Example
+///
+/// ```rust,no_run
+///orders.subscribe_with_options(
+///    Msg::UrlChanged,
+///    SubOptions::new()
+///        .priority(10)
+///        .filter(|subs::UrlChanged(url)| url.path().first().map(String::as_str) == Some("admin")),
+///);
+/// ```
+#[allow(clippy::module_name_repetitions)]
+pub struct SubOptions<SubMs> {
+    priority: i8,
+    filter: Option<SubFilter<SubMs>>,
+}
+
+impl<SubMs> SubOptions<SubMs> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Subscriptions with higher priority are notified first. Default is `0`.
+    pub const fn priority(mut self, priority: i8) -> Self {
+        self.priority = priority;
+        self
+    }
+
+    /// The handler is invoked only for notifications passing the `filter`.
+    pub fn filter(mut self, filter: impl Fn(&SubMs) -> bool + 'static) -> Self {
+        self.filter = Some(Rc::new(filter));
+        self
+    }
+}
+
+impl<SubMs> Default for SubOptions<SubMs> {
+    fn default() -> Self {
+        Self {
+            priority: 0,
+            filter: None,
+        }
+    }
+}
+
+impl<SubMs> fmt::Debug for SubOptions<SubMs> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SubOptions")
+            .field("priority", &self.priority)
+            .field(
+                "filter",
+                &self.filter.as_ref().map(|_| "Rc<dyn Fn(&SubMs) -> bool>"),
+            )
+            .finish()
+    }
+}
+
 // ------ SubHandle ------
 
 pub struct SubHandle {
     unsubscriber: Box<dyn Fn()>,
 }
 
+impl SubHandle {
+    /// The handle of a subscription that hasn't been created - e.g. during `Devtools` replay.
+    pub(crate) fn inactive() -> Self {
+        Self {
+            unsubscriber: Box::new(|| ()),
+        }
+    }
+}
+
 impl fmt::Debug for SubHandle {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_struct("SubHandle")
@@ -127,23 +199,22 @@ impl<Ms: 'static> Subscription<Ms> {
     #[allow(clippy::shadow_unrelated)]
     pub fn new<SubMs: 'static + Clone>(
         handler: impl FnOnce(SubMs) -> Option<Ms> + Clone + 'static,
-    ) -> Self {
-        Self::new_with_priority(handler, 0)
-    }
-
-    #[allow(clippy::shadow_unrelated)]
-    pub(crate) fn new_with_priority<SubMs: 'static + Clone>(
-        handler: impl FnOnce(SubMs) -> Option<Ms> + Clone + 'static,
-        priority: i8,
+        options: SubOptions<SubMs>,
     ) -> Self {
         // Convert `FnOnce + Clone` to `Fn`.
         let handler = move |sub_msg: SubMs| handler.clone()(sub_msg);
 
         // Convert `Fn(SubMs)` to `Fn(&Box<dyn Any>)` where `Any` is `SubMs`.
+        let filter = options.filter;
         let handler = move |sub_msg: Rc<dyn Any>| {
             let sub_msg = sub_msg
                 .downcast_ref::<SubMs>()
                 .expect("downcast to `SubMs`");
+            if let Some(filter) = &filter {
+                if !filter(sub_msg) {
+                    return None;
+                }
+            }
             handler(sub_msg.clone())
         };
 
@@ -151,7 +222,7 @@ impl<Ms: 'static> Subscription<Ms> {
             type_id: TypeId::of::<SubMs>(),
             id: Uuid::new_v4(),
             handler: Rc::new(handler),
-            priority,
+            priority: options.priority,
         }
     }
 }
@@ -169,9 +240,11 @@ impl<Ms> fmt::Debug for Subscription<Ms> {
 
 // ------ Notification ------
 
+#[derive(Clone)]
 pub struct Notification {
     type_id: TypeId,
     message: Rc<dyn Any>,
+    retained: bool,
 }
 
 impl Notification {
@@ -179,6 +252,92 @@ impl Notification {
         Self {
             type_id: TypeId::of::<SubMs>(),
             message: Rc::new(message),
+            retained: false,
+        }
+    }
+
+    /// The notification is replayed to subscribers created after it has been sent,
+    /// until another retained notification with the same type is sent.
+    pub fn new_retained<SubMs: 'static + Any + Clone>(message: SubMs) -> Self {
+        Self {
+            retained: true,
+            ..Self::new(message)
         }
     }
+
+    pub(crate) fn downcast_ref<SubMs: 'static>(&self) -> Option<&SubMs> {
+        self.message.downcast_ref()
+    }
+}
+
+// ------ ------ Tests ------ ------
+
+#[cfg(test)]
+mod tests {
+    use crate::prelude::*;
+    use crate::testing::TestApp;
+
+    #[derive(Clone, Debug, PartialEq)]
+    struct User(&'static str);
+
+    #[derive(Clone, Debug)]
+    enum Msg {
+        LogIn(&'static str),
+        Subscribe,
+        UserChanged(User),
+        HighPriority(User),
+        LowPriority(User),
+    }
+
+    fn init(_: Url, orders: &mut impl Orders<Msg>) -> Vec<String> {
+        orders
+            .subscribe_with_options(Msg::LowPriority, SubOptions::new().priority(-1))
+            .subscribe_with_options(
+                Msg::HighPriority,
+                SubOptions::new()
+                    .priority(1)
+                    .filter(|User(name)| *name != "bob"),
+            );
+        Vec::new()
+    }
+
+    fn update(msg: Msg, log: &mut Vec<String>, orders: &mut impl Orders<Msg>) {
+        match msg {
+            Msg::LogIn(name) => {
+                orders.notify_retained(User(name));
+            }
+            Msg::Subscribe => {
+                orders.subscribe(Msg::UserChanged);
+            }
+            Msg::UserChanged(User(name)) => log.push(format!("changed {}", name)),
+            Msg::HighPriority(User(name)) => log.push(format!("high {}", name)),
+            Msg::LowPriority(User(name)) => log.push(format!("low {}", name)),
+        }
+    }
+
+    fn view(_: &Vec<String>) -> Node<Msg> {
+        empty![]
+    }
+
+    #[test]
+    fn priority_and_filter() {
+        let app = TestApp::start(Url::new(), init, update, view);
+        app.notify(User("alice"));
+        app.notify(User("bob"));
+        app.settle();
+        assert_eq!(*app.model(), vec!["high alice", "low alice", "low bob"]);
+    }
+
+    #[test]
+    fn retained_notification_is_replayed_to_new_subscribers() {
+        let app = TestApp::start(Url::new(), init, update, view);
+        app.update(Msg::LogIn("alice"));
+        app.model_mut().clear();
+
+        app.update(Msg::Subscribe);
+        assert_eq!(*app.model(), vec!["changed alice"]);
+
+        app.update(Msg::LogIn("bob"));
+        assert!(app.model().contains(&"changed bob".to_owned()));
+    }
 }
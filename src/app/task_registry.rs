@@ -0,0 +1,86 @@
+use futures::future::{self, abortable, AbortHandle, Future, FutureExt};
+use futures::stream::{self, Stream, StreamExt};
+use std::{
+    cell::{Cell, RefCell},
+    collections::HashMap,
+    rc::Rc,
+};
+
+// ------ TaskRegistry ------
+
+/// Keeps `AbortHandle`s of running cmds and streams, so we can drop them when the app is unmounted.
+#[derive(Default)]
+pub(crate) struct TaskRegistry {
+    handles: Rc<RefCell<HashMap<u64, AbortHandle>>>,
+    next_id: Cell<u64>,
+}
+
+impl TaskRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Make the task abortable by `abort_all`.
+    /// The task unregisters itself when it's finished.
+    pub fn register(&self, task: impl Future<Output = ()> + 'static) -> impl Future<Output = ()> {
+        let id = self.next_id.get();
+        self.next_id.set(id.wrapping_add(1));
+
+        let (task, handle) = abortable(task);
+        self.handles.borrow_mut().insert(id, handle);
+
+        let handles = Rc::downgrade(&self.handles);
+        task.map(move |_| {
+            if let Some(handles) = handles.upgrade() {
+                handles.borrow_mut().remove(&id);
+            }
+        })
+    }
+
+    /// The same as `register`, but for streams.
+    pub fn register_stream(
+        &self,
+        task: impl Stream<Item = ()> + 'static,
+    ) -> impl Stream<Item = ()> {
+        let id = self.next_id.get();
+        self.next_id.set(id.wrapping_add(1));
+
+        let (task, handle) = stream::abortable(task);
+        self.handles.borrow_mut().insert(id, handle);
+
+        let handles = Rc::downgrade(&self.handles);
+        let unregister = stream::once(future::lazy(move |_| {
+            if let Some(handles) = handles.upgrade() {
+                handles.borrow_mut().remove(&id);
+            }
+        }))
+        // The cleanup mustn't yield an item into the registered stream.
+        .filter_map(|()| future::ready(None));
+        task.chain(unregister)
+    }
+
+    /// Abort all registered tasks. They are dropped on their next poll.
+    pub fn abort_all(&self) {
+        for (_, handle) in self.handles.borrow_mut().drain() {
+            handle.abort();
+        }
+    }
+}
+
+// ------ ------ Tests ------ ------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::executor::block_on;
+
+    #[test]
+    fn registered_stream_yields_only_its_items() {
+        let registry = TaskRegistry::new();
+        let task = registry.register_stream(stream::iter(vec![(), ()]));
+        assert_eq!(registry.handles.borrow().len(), 1);
+
+        assert_eq!(block_on(task.collect::<Vec<_>>()), vec![(), ()]);
+        assert!(registry.handles.borrow().is_empty());
+    }
+}
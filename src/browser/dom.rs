@@ -3,10 +3,12 @@
 
 pub mod cast;
 pub mod css_units;
+pub mod dom_error;
 pub mod event_handler;
 pub mod namespace;
 pub mod virtual_dom_bridge;
 
+pub use dom_error::DomError;
 pub use namespace::Namespace;
 
 #[cfg(test)]
@@ -240,4 +242,91 @@ pub mod tests {
 
         assert_eq!(style, result_style)
     }
+
+    #[wasm_bindgen_test]
+    pub fn error_boundary_renders_fallback_of_failed_view() {
+        let expected = "<seed-boundary style=\"display:contents\"><p>no data</p></seed-boundary>";
+
+        let node = el_to_websys(seed::virtual_dom::error_boundary(
+            Err::<Node<Msg>, _>("no data"),
+            |error| p![error.to_string()],
+        ));
+
+        assert_eq!(expected, get_node_html(&node));
+    }
+
+    #[wasm_bindgen_test]
+    pub fn error_boundary_catches_dom_errors() {
+        use std::{cell::RefCell, rc::Rc};
+
+        let document = crate::util::document();
+        let parent = document.create_element("div").unwrap();
+        let mailbox = Mailbox::new(|_: Option<Msg>| {});
+        let app = create_app();
+
+        let calls = Rc::new(RefCell::new(Vec::new()));
+        let log = |calls: &Rc<RefCell<Vec<String>>>, name: &'static str| {
+            let calls = Rc::clone(calls);
+            move |element: Element| {
+                calls
+                    .borrow_mut()
+                    .push(format!("{} {}", name, element.tag_name().to_lowercase()));
+            }
+        };
+
+        let mut node = div![
+            seed::virtual_dom::error_boundary(
+                Ok::<_, String>(div![
+                    attrs! {"invalid name" => "value"},
+                    seed::virtual_dom::on_remove(log(&calls, "remove")),
+                ]),
+                {
+                    let calls = Rc::clone(&calls);
+                    move |_| {
+                        p![
+                            "fallback",
+                            seed::virtual_dom::on_insert(log(&calls, "insert"))
+                        ]
+                    }
+                },
+            ),
+            span!["sibling"],
+        ];
+        let mut hooks = Vec::new();
+        let unhandled = super::dom_error::collect(|| {
+            patch::patch(
+                &document,
+                seed::empty(),
+                &mut node,
+                &parent,
+                None,
+                &mailbox,
+                &app,
+            );
+            crate::virtual_dom::node::boundary::catch_dom_errors(
+                &document,
+                &mailbox,
+                std::slice::from_mut(&mut node),
+                super::dom_error::take_reported(),
+                &mut hooks,
+            )
+        });
+        for hook in hooks {
+            hook.call();
+        }
+
+        assert!(unhandled.is_empty());
+        assert_eq!(*calls.borrow(), vec!["remove div", "insert p"]);
+        assert_eq!(
+            "<div><seed-boundary style=\"display:contents\"><p>fallback</p></seed-boundary>\
+             <span>sibling</span></div>",
+            parent.inner_html()
+        );
+    }
+
+    #[wasm_bindgen_test]
+    pub fn errors_reported_outside_of_render_are_not_kept() {
+        super::dom_error::report(super::DomError::new("remove node", "error", None));
+        assert!(super::dom_error::collect(super::dom_error::take_reported).is_empty());
+    }
 }
@@ -0,0 +1,93 @@
+use std::cell::{Cell, RefCell};
+use std::fmt;
+use wasm_bindgen::JsValue;
+
+thread_local! {
+    /// Errors reported since the last `take_reported` call.
+    static REPORTED: RefCell<Vec<DomError>> = const { RefCell::new(Vec::new()) };
+    /// The number of running `collect` calls.
+    static COLLECTING: Cell<usize> = const { Cell::new(0) };
+}
+
+// ------ DomError ------
+
+/// A failed DOM operation - e.g. an invalid tag or attribute name in a view.
+///
+/// Seed doesn't panic on these errors. They're caught by the nearest `error_boundary`
+/// or sent to the app as a notification.
+///
+/// # Example
+///
+/// ```rust,no_run
+///orders.subscribe(Msg::DomErrorOccurred);
+///...
+///update(... Msg::DomErrorOccurred(error) => log!(error.to_string()),
+/// ```
+#[allow(clippy::module_name_repetitions)]
+#[derive(Debug, Clone)]
+pub struct DomError {
+    /// The failed operation - e.g. "set attribute `foo bar`".
+    pub operation: String,
+    /// The error thrown by the browser.
+    pub error: JsValue,
+    /// The DOM node which was being created or patched.
+    pub node: Option<web_sys::Node>,
+}
+
+impl DomError {
+    pub fn new(
+        operation: impl Into<String>,
+        error: impl Into<JsValue>,
+        node: Option<&web_sys::Node>,
+    ) -> Self {
+        Self {
+            operation: operation.into(),
+            error: error.into(),
+            node: node.cloned(),
+        }
+    }
+
+    /// The virtual node isn't linked to a DOM node - e.g. the DOM has been modified by a foreign script.
+    pub(crate) fn missing_node(operation: impl Into<String>, node: Option<&web_sys::Node>) -> Self {
+        Self::new(operation, "missing DOM node", node)
+    }
+}
+
+impl fmt::Display for DomError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Problem with DOM operation '{}': ", self.operation)?;
+        match self.error.as_string() {
+            Some(error) => write!(f, "{}", error),
+            None => write!(f, "{:?}", self.error),
+        }
+    }
+}
+
+/// Remember the error until the end of the current render.
+///
+/// Errors reported outside of renders (e.g. when listeners are removed on unmount
+/// or a leave transition ends) are logged immediately.
+pub(crate) fn report(error: DomError) {
+    if COLLECTING.with(Cell::get) == 0 {
+        crate::error(error.to_string());
+    } else {
+        REPORTED.with(|reported| reported.borrow_mut().push(error));
+    }
+}
+
+/// Collect errors reported while `f` runs - take them by `take_reported` before `collect` returns.
+pub(crate) fn collect<T>(f: impl FnOnce() -> T) -> T {
+    struct Guard;
+    impl Drop for Guard {
+        fn drop(&mut self) {
+            COLLECTING.with(|collecting| collecting.set(collecting.get() - 1));
+        }
+    }
+    COLLECTING.with(|collecting| collecting.set(collecting.get() + 1));
+    let _guard = Guard;
+    f()
+}
+
+pub(crate) fn take_reported() -> Vec<DomError> {
+    REPORTED.with(|reported| reported.take())
+}
@@ -1,19 +1,20 @@
 //! This file contains interactions with `web_sys`.
 
-use super::Namespace;
-use crate::virtual_dom::{At, AtValue, Attrs, El, Mailbox, Node, Style, Text};
+use super::{dom_error, DomError, Namespace};
+use crate::virtual_dom::style::StyleChange;
+use crate::virtual_dom::{At, AtValue, Attrs, El, Lazy, Mailbox, Node, Portal, Props, Style, Text};
 use std::borrow::Cow;
 use std::cmp::Ordering;
-use wasm_bindgen::JsCast;
+use wasm_bindgen::{JsCast, JsValue};
 use web_sys::Document;
 
 /// Convenience function to reduce repetition
 fn set_style(el_ws: &web_sys::Node, style: &Style) {
-    el_ws
-        .dyn_ref::<web_sys::Element>()
-        .expect("Problem casting Node as Element while setting style")
-        .set_attribute("style", &style.to_string())
-        .expect("Problem setting style");
+    if let Some(element) = el_ws.dyn_ref::<web_sys::Element>() {
+        if let Err(error) = element.set_attribute("style", &style.to_string()) {
+            dom_error::report(DomError::new("set style", error, Some(el_ws)));
+        }
+    }
 }
 
 pub(crate) fn assign_ws_nodes_to_el<Ms>(document: &Document, el: &mut El<Ms>) {
@@ -39,6 +40,12 @@ pub(crate) fn assign_ws_nodes<Ms>(document: &Document, node: &mut Node<Ms>) {
     match node {
         Node::Element(el) => assign_ws_nodes_to_el(document, el),
         Node::Text(text) => assign_ws_nodes_to_text(document, text),
+        Node::Lazy(lazy) => assign_ws_nodes(document, lazy.render()),
+        Node::Portal(portal) => {
+            for child in &mut portal.children {
+                assign_ws_nodes(document, child);
+            }
+        }
         Node::Empty | Node::NoChange => (),
     }
 }
@@ -54,40 +61,24 @@ fn node_to_element(el_ws: &web_sys::Node) -> Result<&web_sys::Element, Cow<str>>
 }
 
 fn set_attr_value(el_ws: &web_sys::Node, at: &At, at_value: &AtValue) {
-    match at_value {
-        AtValue::Some(value) => {
-            node_to_element(el_ws)
-                .and_then(|element| {
-                    element.set_attribute(at.as_str(), value).map_err(|error| {
-                        Cow::from(format!("Problem setting an attribute: {:?}", error))
-                    })
-                })
-                .unwrap_or_else(|err| {
-                    crate::error(err);
-                });
-        }
-        AtValue::None => {
-            node_to_element(el_ws)
-                .and_then(|element| {
-                    element.set_attribute(at.as_str(), "").map_err(|error| {
-                        Cow::from(format!("Problem setting an attribute: {:?}", error))
-                    })
-                })
-                .unwrap_or_else(|err| {
-                    crate::error(err);
-                });
-        }
-        AtValue::Ignored => {
-            node_to_element(el_ws)
-                .and_then(|element| {
-                    element.remove_attribute(at.as_str()).map_err(|error| {
-                        Cow::from(format!("Problem removing an attribute: {:?}", error))
-                    })
-                })
-                .unwrap_or_else(|err| {
-                    crate::error(err);
-                });
+    let element = match node_to_element(el_ws) {
+        Ok(element) => element,
+        Err(err) => {
+            crate::error(err);
+            return;
         }
+    };
+    let (operation, result) = match at_value {
+        AtValue::Some(value) => ("set attribute", element.set_attribute(at.as_str(), value)),
+        AtValue::None => ("set attribute", element.set_attribute(at.as_str(), "")),
+        AtValue::Ignored => ("remove attribute", element.remove_attribute(at.as_str())),
+    };
+    if let Err(error) = result {
+        dom_error::report(DomError::new(
+            format!("{} `{}`", operation, at.as_str()),
+            error,
+            Some(el_ws),
+        ));
     }
 }
 
@@ -102,12 +93,21 @@ pub(crate) fn make_websys_el<Ms>(el: &mut El<Ms>, document: &web_sys::Document)
     let tag = el.tag.as_str();
 
     let el_ws = match el.namespace {
-        Some(ref ns) => document
-            .create_element_ns(Some(ns.as_str()), tag)
-            .expect("Problem creating web-sys element with namespace"),
-        None => document
-            .create_element(tag)
-            .expect("Problem creating web-sys element"),
+        Some(ref ns) => document.create_element_ns(Some(ns.as_str()), tag),
+        None => document.create_element(tag),
+    };
+    let el_ws = match el_ws {
+        Ok(el_ws) => el_ws,
+        Err(error) => {
+            // The empty placeholder keeps the position of the node in the DOM.
+            let placeholder: web_sys::Node = document.create_text_node("").into();
+            dom_error::report(DomError::new(
+                format!("create element `{}`", tag),
+                error,
+                Some(&placeholder),
+            ));
+            return placeholder;
+        }
     };
 
     fix_attrs_order(&mut el.attrs);
@@ -115,11 +115,9 @@ pub(crate) fn make_websys_el<Ms>(el: &mut El<Ms>, document: &web_sys::Document)
         set_attr_value(&el_ws, at, attr_value);
     }
     if let Some(ns) = &el.namespace {
-        el_ws
-            .dyn_ref::<web_sys::Element>()
-            .expect("Problem casting Node as Element while setting an attribute")
-            .set_attribute("xmlns", ns.as_str())
-            .expect("Problem setting xlmns attribute");
+        if let Err(error) = el_ws.set_attribute("xmlns", ns.as_str()) {
+            dom_error::report(DomError::new("set attribute `xmlns`", error, Some(&el_ws)));
+        }
     }
 
     // Style is just an attribute in the actual Dom, but is handled specially in our vdom;
@@ -133,29 +131,34 @@ pub(crate) fn make_websys_el<Ms>(el: &mut El<Ms>, document: &web_sys::Document)
 
 /// Similar to `attach_el_and_children`, but for text nodes
 pub fn attach_text_node(text: &mut Text, parent: &web_sys::Node) {
-    let node_ws = text.node_ws.take().expect("Missing websys node for Text");
-    parent
-        .append_child(&node_ws)
-        .expect("Problem appending text node");
-    text.node_ws.replace(node_ws);
+    let node_ws = match text.node_ws.as_ref() {
+        Some(node_ws) => node_ws,
+        None => return dom_error::report(DomError::missing_node("append text node", Some(parent))),
+    };
+    if let Err(error) = parent.append_child(node_ws) {
+        dom_error::report(DomError::new("append text node", error, Some(parent)));
+    }
 }
 
 /// Similar to `attach_el_and_children`, but without attaching the elemnt. Useful for
 /// patching, where we want to insert the element at a specific place.
 pub fn attach_children<Ms>(el: &mut El<Ms>, mailbox: &Mailbox<Ms>) {
-    let el_ws = el
-        .node_ws
-        .as_ref()
-        .expect("Missing websys el in attach_children");
+    let el_ws = match el.node_ws.as_ref() {
+        Some(el_ws) => el_ws,
+        None => return dom_error::report(DomError::missing_node("attach children", None)),
+    };
     // appending the its children to the el_ws
     for child in &mut el.children {
-        match child {
+        match child.unlazy_mut() {
             // Raise the active level once per recursion.
             Node::Element(child_el) => attach_el_and_children(child_el, el_ws, mailbox),
             Node::Text(child_text) => attach_text_node(child_text, el_ws),
-            Node::Empty | Node::NoChange => (),
+            Node::Portal(child_portal) => attach_portal(child_portal, mailbox),
+            Node::Empty | Node::NoChange | Node::Lazy(_) => (),
         }
     }
+
+    patch_props(&Props::empty(), &el.props, el_ws);
 }
 
 /// Attaches the element, and all children, recursively. Only run this when creating a fresh vdom node, since
@@ -164,16 +167,15 @@ pub fn attach_children<Ms>(el: &mut El<Ms>, mailbox: &Mailbox<Ms>) {
 pub fn attach_el_and_children<Ms>(el: &mut El<Ms>, parent: &web_sys::Node, mailbox: &Mailbox<Ms>) {
     // No parent means we're operating on the top-level element; append it to the main div.
     // This is how we call this function externally, ie not through recursion.
-    let el_ws = el
-        .node_ws
-        .as_ref()
-        .expect("Missing websys el in attach_el_and_children");
+    let el_ws = match el.node_ws.as_ref() {
+        Some(el_ws) => el_ws,
+        None => return dom_error::report(DomError::missing_node("append element", Some(parent))),
+    };
 
     // Append the element
 
-    // todo: This error can occur with raw html elements, but am unsure of the cause.
-    if parent.append_child(el_ws).is_err() {
-        crate::error("Minor problem with html element (append)");
+    if let Err(error) = parent.append_child(el_ws) {
+        dom_error::report(DomError::new("append element", error, Some(parent)));
     }
 
     el.event_handler_manager
@@ -181,29 +183,86 @@ pub fn attach_el_and_children<Ms>(el: &mut El<Ms>, parent: &web_sys::Node, mailb
 
     // appending the its children to the el_ws
     for child in &mut el.children {
-        match child {
+        match child.unlazy_mut() {
             // Raise the active level once per recursion.
             Node::Element(child_el) => attach_el_and_children(child_el, el_ws, mailbox),
             Node::Text(child_text) => attach_text_node(child_text, el_ws),
-            Node::Empty | Node::NoChange => (),
+            Node::Portal(child_portal) => attach_portal(child_portal, mailbox),
+            Node::Empty | Node::NoChange | Node::Lazy(_) => (),
         }
     }
 
+    patch_props(&Props::empty(), &el.props, el_ws);
+
     // Note: Call `set_default_element_state` after child appending,
     // otherwise it breaks autofocus in Firefox
     set_default_element_state(el_ws, el);
 }
 
+/// Attaches portal children to the portal target.
+/// Children have to have assigned `web_sys` nodes - see `assign_ws_nodes`.
+pub(crate) fn attach_portal<Ms>(portal: &mut Portal<Ms>, mailbox: &Mailbox<Ms>) {
+    let target = match portal.find_target() {
+        Ok(target) => target,
+        Err(error) => {
+            crate::error(error);
+            return;
+        }
+    };
+    // The target may be outside of the mount point.
+    if let Some(event_delegation) = &mailbox.event_delegation {
+        event_delegation.add_root(target.clone().into());
+    }
+    for child in &mut portal.children {
+        match child.unlazy_mut() {
+            Node::Element(child_el) => attach_el_and_children(child_el, &target, mailbox),
+            Node::Text(child_text) => attach_text_node(child_text, &target),
+            Node::Portal(child_portal) => attach_portal(child_portal, mailbox),
+            Node::Empty | Node::NoChange | Node::Lazy(_) => (),
+        }
+    }
+    portal.target_ws = Some(target);
+}
+
+/// Removes portal children from the portal target.
+pub(crate) fn detach_portal<Ms>(portal: &Portal<Ms>) {
+    let target = match &portal.target_ws {
+        Some(target) => target,
+        None => return,
+    };
+    for child in &portal.children {
+        remove_portals(child);
+        if let Some(child_ws) = child.node_ws() {
+            remove_node(child_ws, target);
+        }
+    }
+}
+
+/// Detaches all portals in the `node`'s subtree.
+/// Call it for removed nodes - portal children aren't in the removed DOM subtree.
+pub(crate) fn remove_portals<Ms>(node: &Node<Ms>) {
+    match node {
+        Node::Element(el) => el.children.iter().for_each(remove_portals),
+        Node::Lazy(Lazy {
+            node: Some(node), ..
+        }) => remove_portals(node),
+        Node::Portal(portal) => detach_portal(portal),
+        Node::Text(_) | Node::Empty | Node::NoChange | Node::Lazy(_) => (),
+    }
+}
+
 fn set_default_element_state<Ms>(el_ws: &web_sys::Node, el: &El<Ms>) {
     // @TODO handle also other Auto* attributes?
     // Set focus because of attribute "autofocus"
     if let Some(at_value) = el.attrs.vals.get(&At::AutoFocus) {
         match at_value {
-            AtValue::Some(_) | AtValue::None => el_ws
-                .dyn_ref::<web_sys::HtmlElement>()
-                .expect("Problem casting Node as HtmlElement while focusing")
-                .focus()
-                .expect("Problem focusing to an element."),
+            AtValue::Some(_) | AtValue::None => {
+                if let Some(Err(error)) =
+                    el_ws.dyn_ref::<web_sys::HtmlElement>().map(|el| el.focus())
+                {
+                    dom_error::report(DomError::new("focus element", error, Some(el_ws)));
+                }
+            }
             AtValue::Ignored => (),
         }
     }
@@ -220,7 +279,9 @@ fn set_default_element_state<Ms>(el_ws: &web_sys::Node, el: &El<Ms>) {
 /// Recursively remove all children.
 pub fn _remove_children(el: &web_sys::Node) {
     while let Some(child) = el.last_child() {
-        el.remove_child(&child).expect("Problem removing child");
+        if let Err(error) = el.remove_child(&child) {
+            return dom_error::report(DomError::new("remove child", error, Some(el)));
+        }
     }
 }
 
@@ -271,8 +332,13 @@ pub(crate) fn patch_el_details<Ms>(
             // todo get to the bottom of this
             match old_el_ws.dyn_ref::<web_sys::Element>() {
                 Some(el) => {
-                    el.remove_attribute(key.as_str())
-                        .expect("Removing an attribute");
+                    if let Err(error) = el.remove_attribute(key.as_str()) {
+                        dom_error::report(DomError::new(
+                            format!("remove attribute `{}`", key.as_str()),
+                            error,
+                            Some(old_el_ws),
+                        ));
+                    }
 
                     // We handle value in the vdom using attributes, but the DOM needs
                     // to use set_value or set_checked.
@@ -307,10 +373,68 @@ pub(crate) fn patch_el_details<Ms>(
         mailbox,
     );
 
-    // Patch style.
-    if old.style != new.style {
-        // We can't patch each part of style; rewrite the whole attribute.
-        set_style(old_el_ws, &new.style);
+    patch_style(&old.style, &new.style, old_el_ws);
+}
+
+/// Set changed properties. Call it after attributes and children are patched -
+/// e.g. `value` may depend on `type` or on `<select>`'s options.
+pub(crate) fn patch_props(old: &Props, new: &Props, el_ws: &web_sys::Node) {
+    for (name, value) in old.diff(new) {
+        let result = js_sys::Reflect::set(
+            el_ws,
+            &JsValue::from_str(name),
+            value.unwrap_or(&JsValue::UNDEFINED),
+        );
+        let error = match result {
+            Ok(true) => continue,
+            Ok(false) => JsValue::from_str("the property is read-only"),
+            Err(error) => error,
+        };
+        dom_error::report(DomError::new(
+            format!("set property `{}`", name),
+            error,
+            Some(el_ws),
+        ));
+    }
+}
+
+/// Updates only changed style properties - inline styles set by other code are kept.
+fn patch_style(old: &Style, new: &Style, el_ws: &web_sys::Node) {
+    if old == new {
+        return;
+    }
+    // Elements without inline style (e.g. MathML in some browsers) are skipped.
+    let declaration = match js_sys::Reflect::get(el_ws, &JsValue::from_str("style"))
+        .ok()
+        .and_then(|style| style.dyn_into::<web_sys::CssStyleDeclaration>().ok())
+    {
+        Some(declaration) => declaration,
+        None => return,
+    };
+    for change in old.diff(new) {
+        let (operation, name, result) = match change {
+            StyleChange::Set {
+                name,
+                value,
+                priority,
+            } => (
+                "set style property",
+                name,
+                declaration.set_property_with_priority(name, value, priority),
+            ),
+            StyleChange::Remove { name } => (
+                "remove style property",
+                name,
+                declaration.remove_property(name).map(drop),
+            ),
+        };
+        if let Err(error) = result {
+            dom_error::report(DomError::new(
+                format!("{} `{}`", operation, name),
+                error,
+                Some(el_ws),
+            ));
+        }
     }
 }
 
@@ -454,12 +578,15 @@ impl<Ms> From<&web_sys::Element> for El<Ms> {
 
         let children = ws_el.child_nodes();
         for i in 0..children.length() {
-            let child = children
-                .get(i)
-                .expect("Can't find child in raw html element.");
-
-            if let Some(child_vdom) = node_from_ws(&child) {
-                el.children.push(child_vdom);
+            match children.get(i) {
+                Some(child) => {
+                    if let Some(child_vdom) = node_from_ws(&child) {
+                        el.children.push(child_vdom);
+                    }
+                }
+                None => {
+                    dom_error::report(DomError::missing_node("read child", Some(ws_el.as_ref())))
+                }
             }
         }
         el
@@ -475,18 +602,24 @@ impl<Ms> From<&web_sys::Element> for Node<Ms> {
 /// and markdown strings. Includes children, recursively added.
 pub fn node_from_ws<Ms>(node: &web_sys::Node) -> Option<Node<Ms>> {
     match node.node_type() {
-        web_sys::Node::ELEMENT_NODE => {
-            // Element node
-            let ws_el = node
-                .dyn_ref::<web_sys::Element>()
-                .expect("Problem casting Node as Element");
-
-            // Create the Element
-            Some(ws_el.into())
-        }
-        web_sys::Node::TEXT_NODE => Some(Node::new_text(
-            node.text_content().expect("Can't find text"),
-        )),
+        web_sys::Node::ELEMENT_NODE => match node.dyn_ref::<web_sys::Element>() {
+            Some(ws_el) => Some(ws_el.into()),
+            None => {
+                dom_error::report(DomError::new(
+                    "cast node to element",
+                    node.clone(),
+                    Some(node),
+                ));
+                None
+            }
+        },
+        web_sys::Node::TEXT_NODE => match node.text_content() {
+            Some(text) => Some(Node::new_text(text)),
+            None => {
+                dom_error::report(DomError::missing_node("read text", Some(node)));
+                None
+            }
+        },
         web_sys::Node::COMMENT_NODE => None,
         node_type => {
             crate::error(format!(
@@ -504,26 +637,23 @@ pub(crate) fn insert_node(
     parent: &web_sys::Node,
     next: Option<web_sys::Node>,
 ) {
-    match next {
-        Some(n) => {
-            parent
-                .insert_before(node, Some(&n))
-                .expect("Problem inserting node");
-        }
-        None => {
-            parent.append_child(node).expect("Problem inserting node");
-        }
+    let result = match next {
+        Some(n) => parent.insert_before(node, Some(&n)),
+        None => parent.append_child(node),
     };
+    if let Err(error) = result {
+        dom_error::report(DomError::new("insert node", error, Some(parent)));
+    }
 }
 
 pub(crate) fn remove_node(node: &web_sys::Node, parent: &web_sys::Node) {
-    parent
-        .remove_child(node)
-        .expect("Problem removing old el_ws when updating to empty");
+    if let Err(error) = parent.remove_child(node) {
+        dom_error::report(DomError::new("remove node", error, Some(parent)));
+    }
 }
 
 pub(crate) fn replace_child(new: &web_sys::Node, old: &web_sys::Node, parent: &web_sys::Node) {
-    parent
-        .replace_child(new, old)
-        .expect("Problem replacing element");
+    if let Err(error) = parent.replace_child(new, old) {
+        dom_error::report(DomError::new("replace node", error, Some(parent)));
+    }
 }
@@ -50,6 +50,18 @@ pub fn setup_popstate_listener(
     updated_listener(closure);
 }
 
+pub fn remove_popstate_listener(closure: &Closure<dyn FnMut(web_sys::Event)>) {
+    (util::window().as_ref() as &web_sys::EventTarget)
+        .remove_event_listener_with_callback("popstate", closure.as_ref().unchecked_ref())
+        .expect("Problem removing popstate listener");
+}
+
+pub fn remove_hashchange_listener(closure: &Closure<dyn FnMut(web_sys::Event)>) {
+    (util::window().as_ref() as &web_sys::EventTarget)
+        .remove_event_listener_with_callback("hashchange", closure.as_ref().unchecked_ref())
+        .expect("Problem removing hashchange listener");
+}
+
 #[allow(clippy::needless_pass_by_value)]
 pub fn url_request_handler(
     sub_data: subs::UrlRequested,
@@ -81,7 +93,11 @@ pub fn url_request_handler(
 /// attribute, so we can prevent page refresh for internal links, and route
 /// internally. Run this on load.
 #[allow(clippy::option_map_unit_fn)]
-pub fn setup_link_listener(notify: impl Fn(Notification) + 'static) {
+pub fn setup_link_listener(
+    target: &web_sys::EventTarget,
+    updated_listener: impl Fn(Closure<dyn FnMut(web_sys::Event)>) + 'static,
+    notify: impl Fn(Notification) + 'static,
+) {
     let closure = Closure::new(move |event: web_sys::Event| {
         event.target()
             .and_then(|et| et.dyn_into::<web_sys::Element>().ok())
@@ -123,9 +139,18 @@ pub fn setup_link_listener(notify: impl Fn(Notification) + 'static) {
             });
     });
 
-    (util::document().as_ref() as &web_sys::EventTarget)
+    target
         .add_event_listener_with_callback("click", closure.as_ref().unchecked_ref())
         .expect("Problem setting up link interceptor");
 
-    closure.forget(); // todo: Can we store the closure somewhere to avoid using forget?
+    updated_listener(closure);
+}
+
+pub fn remove_link_listener(
+    target: &web_sys::EventTarget,
+    closure: &Closure<dyn FnMut(web_sys::Event)>,
+) {
+    target
+        .remove_event_listener_with_callback("click", closure.as_ref().unchecked_ref())
+        .expect("Problem removing link interceptor");
 }
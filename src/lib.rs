@@ -101,7 +101,7 @@ pub use crate::{
         self, body, canvas, canvas_context_2d, cookies, document, error, history, html_document,
         log, window,
     },
-    virtual_dom::{Attrs, EventHandler, Style},
+    virtual_dom::{Attrs, EventHandler, Props, Style},
 };
 
 #[cfg(feature = "panic-hook")]
@@ -120,6 +120,7 @@ pub mod app;
 pub mod browser;
 pub mod dom_entity_names;
 pub mod helpers;
+pub mod testing;
 pub mod virtual_dom;
 
 /// Create an element flagged in a way that it will not be rendered. Useful
@@ -176,15 +177,17 @@ pub fn set_timeout(handler: Box<dyn Fn()>, timeout: i32) {
 pub mod prelude {
     pub use crate::{
         app::{
-            cmds, streams, subs, App, CmdHandle, GetElement, MessageMapper, Orders, RenderInfo,
-            StreamHandle, SubHandle,
+            cmds, streams, subs, Animation, App, CmdHandle, CmdPolicy, Component, Easing,
+            FrameInfo, GetElement, Instance, Jitter, LinkInterception, MessageMapper, Orders,
+            Persistence, RenderInfo, RenderMode, RetryEvent, RetryPolicy, Spring, StreamHandle,
+            SubHandle, SubOptions, Tween,
         },
         browser::dom::css_units::*,
         browser::dom::event_handler::{
             drag_ev, ev, input_ev, keyboard_ev, mouse_ev, pointer_ev, raw_ev, simple_ev, touch_ev,
             wheel_ev,
         },
-        browser::dom::Namespace,
+        browser::dom::{DomError, Namespace},
         browser::fetch::{self, fetch, FetchError, Header, Method, Request, Response, Status},
         browser::util::{
             request_animation_frame, ClosureNew, RequestAnimationFrameHandle,
@@ -198,9 +201,10 @@ pub mod prelude {
         // https://github.com/rust-lang-nursery/reference/blob/master/src/macros-by-example.md
         shortcuts::*,
         virtual_dom::{
-            el_key, el_ref::el_ref, AsAtValue, At, AtValue, CSSValue, El, ElRef, Ev, EventHandler,
-            IntoNodes, Node, St, Tag, ToClasses, UpdateEl, UpdateElForIterator,
-            UpdateElForOptionIterator, View,
+            el_key, el_ref::el_ref, error_boundary, lazy, lazy_hashed, on_insert, on_remove,
+            on_update, portal, portal_to_body, transition, AsAtValue, At, AtValue, BoundaryError,
+            CSSValue, El, ElRef, Ev, EventHandler, IntoNodes, Node, PropValue, St, Tag, ToClasses,
+            ToHtml, Transition, UpdateEl, UpdateElForIterator, UpdateElForOptionIterator, View,
         },
     };
     pub use indexmap::IndexMap; // for attrs and style to work.
@@ -447,6 +447,34 @@ macro_rules! style {
      };
 }
 
+/// Provide a shortcut for setting JS properties of the element - see `Props`.
+///
+/// # Example
+///
+/// ```rust,no_run
+///input![
+///    attrs! { At::Type => "checkbox" },
+///    props! {
+///        "indeterminate" => model.partially_selected,
+///        "tags" => PropValue::serde(&model.tags),
+///    },
+///]
+/// ```
+#[macro_export]
+macro_rules! props {
+    { $($key:expr => $value:expr $(;)?$(,)?)* } => {
+        {
+            #[allow(unused_imports)]
+            use $crate::virtual_dom::IntoPropValue;
+            let mut vals = IndexMap::new();
+            $(
+                vals.insert($key.into(), ($value).into_prop_value());
+            )*
+            $crate::virtual_dom::Props::new(vals)
+        }
+     };
+}
+
 #[macro_export]
 /// Converts items to `Vec<Node<Ms>` and returns flattened `Vec<Node<Ms>`.
 ///
@@ -0,0 +1,441 @@
+//! Test harness for running `init`, `update` and `view` without a browser.
+//!
+//! `TestApp` drives a headless `App` - effects aren't processed until you `step` or `settle`,
+//! `Orders::perform_cmd` futures and `Orders::stream` streams are recorded and polled only
+//! on demand and `view` output is kept as plain `Node`s.
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//!#[test]
+//!fn increment() {
+//!    let app = TestApp::start(Url::new(), init, update, view);
+//!    app.settle();
+//!
+//!    app.update(Msg::Increment);
+//!
+//!    assert_eq!(app.model().counter, 1);
+//!    assert_eq!(app.render_decisions(), vec![ShouldRender::Render]);
+//!    assert_eq!(app.view()[0].to_html(), "<button>1</button>");
+//!}
+//! ```
+
+pub use crate::app::clock::VirtualClock;
+
+use crate::app::{App, Effect, Notification, OrdersContainer, ShouldRender};
+use crate::browser::Url;
+use crate::virtual_dom::{IntoNodes, Node};
+use futures::future::LocalBoxFuture;
+use futures::task::{noop_waker_ref, Context, Poll};
+use std::cell::{Cell, Ref, RefCell, RefMut};
+use std::collections::VecDeque;
+use std::mem;
+
+// ------ TestApp ------
+
+/// Headless `App` for native tests.
+pub struct TestApp<Ms, Mdl, INodes>
+where
+    Ms: 'static,
+    Mdl: 'static,
+    INodes: IntoNodes<Ms>,
+{
+    app: App<Ms, Mdl, INodes>,
+}
+
+impl<Ms, Mdl, INodes> TestApp<Ms, Mdl, INodes>
+where
+    INodes: IntoNodes<Ms> + 'static,
+{
+    /// Create the headless app and invoke `init`.
+    ///
+    /// Effects created by `init` are queued and the first render is scheduled.
+    pub fn start(
+        url: Url,
+        init: impl FnOnce(Url, &mut OrdersContainer<Ms, Mdl, INodes>) -> Mdl + 'static,
+        update: impl FnOnce(Ms, &mut Mdl, &mut OrdersContainer<Ms, Mdl, INodes>) + Clone + 'static,
+        view: impl FnOnce(&Mdl) -> INodes + Clone + 'static,
+    ) -> Self {
+        Self {
+            app: App::start_headless(url, init, update, view),
+        }
+    }
+
+    /// Returns the underlying `App` - e.g. to test code that uses `App::update` or `Mailbox`.
+    pub fn clone_app(&self) -> App<Ms, Mdl, INodes> {
+        self.app.clone()
+    }
+
+    // ------ Effects ------
+
+    /// Queue the message. Call `step` or `settle` to process it.
+    pub fn send_msg(&self, msg: Ms) {
+        self.app.update(msg);
+    }
+
+    /// Queue the notification. Call `step` or `settle` to process it.
+    pub fn notify<SubMs: 'static + Clone>(&self, message: SubMs) {
+        self.app.notify(message);
+    }
+
+    /// Send the message and process all effects.
+    pub fn update(&self, msg: Ms) {
+        self.send_msg(msg);
+        self.settle();
+    }
+
+    /// Process the first queued effect. New effects are pushed to the end of the queue.
+    ///
+    /// Returns `false` when the queue is empty.
+    pub fn step(&self) -> bool {
+        let effect = self.recorder().effects.borrow_mut().pop_front();
+        match effect {
+            Some(effect) => {
+                let new_effects = self.app.process_effect(effect);
+                self.recorder().record_effects(new_effects);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Process queued effects until the queue is empty.
+    ///
+    /// Returns the number of processed effects.
+    pub fn settle(&self) -> usize {
+        let mut steps = 0;
+        while self.step() {
+            steps += 1;
+        }
+        steps
+    }
+
+    /// The number of effects waiting for `step`.
+    pub fn queued_effects(&self) -> usize {
+        self.recorder().effects.borrow().len()
+    }
+
+    /// Messages waiting for `step`.
+    pub fn queued_msgs(&self) -> Vec<Ms>
+    where
+        Ms: Clone,
+    {
+        self.recorder()
+            .effects
+            .borrow()
+            .iter()
+            .filter_map(|effect| match effect {
+                Effect::Msg(msg) => msg.clone(),
+                Effect::Notification(_) | Effect::TriggeredHandler(_) => None,
+            })
+            .collect()
+    }
+
+    /// All notifications of the given type sent so far, including the queued ones.
+    pub fn notifications<SubMs: 'static + Clone>(&self) -> Vec<SubMs> {
+        self.recorder()
+            .notifications
+            .borrow()
+            .iter()
+            .filter_map(|notification| notification.downcast_ref::<SubMs>().cloned())
+            .collect()
+    }
+
+    /// `ShouldRender` of each processed message, in order.
+    pub fn render_decisions(&self) -> Vec<ShouldRender> {
+        self.recorder().render_decisions.borrow().clone()
+    }
+
+    // ------ Cmds & Streams ------
+
+    /// The number of running cmds.
+    pub fn pending_cmds(&self) -> usize {
+        self.recorder().cmds.borrow().len()
+    }
+
+    /// The number of running streams.
+    pub fn pending_streams(&self) -> usize {
+        self.recorder().streams.borrow().len()
+    }
+
+    /// Poll all running cmds once. Messages of the finished cmds are queued.
+    ///
+    /// Returns the number of finished cmds.
+    pub fn run_cmds(&self) -> usize {
+        poll_tasks(&self.recorder().cmds)
+    }
+
+    /// Poll all running streams until they are pending. Streamed messages are queued.
+    ///
+    /// Returns the number of finished streams.
+    pub fn run_streams(&self) -> usize {
+        poll_tasks(&self.recorder().streams)
+    }
+
+    // ------ Rendering ------
+
+    /// Returns `true` if a render has been scheduled and not performed yet.
+    pub fn render_scheduled(&self) -> bool {
+        self.recorder().render_scheduled.get()
+    }
+
+    /// Render (invoke `view`) and queue `Orders::after_next_render` callbacks.
+    pub fn render(&self) {
+        self.app.cancel_scheduled_render();
+        self.app.rerender_vdom();
+    }
+
+    /// Nodes from the last render.
+    pub fn rendered(&self) -> Option<Vec<Node<Ms>>> {
+        self.app
+            .data
+            .root_el
+            .borrow()
+            .as_ref()
+            .map(|root_el| root_el.children.clone())
+    }
+
+    /// Invoke `view` with the current model.
+    pub fn view(&self) -> Vec<Node<Ms>> {
+        (self.app.cfg.view)(&self.model()).into_nodes()
+    }
+
+    // ------ Model ------
+
+    pub fn model(&self) -> Ref<'_, Mdl> {
+        Ref::map(self.app.data.model.borrow(), |model| {
+            model.as_ref().expect("get model")
+        })
+    }
+
+    pub fn model_mut(&self) -> RefMut<'_, Mdl> {
+        RefMut::map(self.app.data.model.borrow_mut(), |model| {
+            model.as_mut().expect("get model")
+        })
+    }
+
+    fn recorder(&self) -> &Recorder<Ms> {
+        self.app
+            .data
+            .recorder
+            .as_ref()
+            .expect("headless app has recorder")
+    }
+}
+
+/// Polls tasks once with a no-op waker and drops the finished ones.
+fn poll_tasks(tasks: &RefCell<Vec<LocalBoxFuture<'static, ()>>>) -> usize {
+    // Tasks can't be borrowed while polled - they may record new tasks.
+    let polled_tasks = mem::take(&mut *tasks.borrow_mut());
+    let mut cx = Context::from_waker(noop_waker_ref());
+
+    let mut pending_tasks = Vec::with_capacity(polled_tasks.len());
+    let mut finished = 0;
+    for mut task in polled_tasks {
+        match task.as_mut().poll(&mut cx) {
+            Poll::Pending => pending_tasks.push(task),
+            Poll::Ready(()) => finished += 1,
+        }
+    }
+
+    let mut tasks = tasks.borrow_mut();
+    pending_tasks.append(&mut tasks);
+    *tasks = pending_tasks;
+    finished
+}
+
+// ------ Recorder ------
+
+/// Stores effects, cmds and streams of the headless `App`.
+pub(crate) struct Recorder<Ms: 'static> {
+    pub(crate) effects: RefCell<VecDeque<Effect<Ms>>>,
+    pub(crate) notifications: RefCell<Vec<Notification>>,
+    pub(crate) render_decisions: RefCell<Vec<ShouldRender>>,
+    pub(crate) render_scheduled: Cell<bool>,
+    pub(crate) cmds: RefCell<Vec<LocalBoxFuture<'static, ()>>>,
+    pub(crate) streams: RefCell<Vec<LocalBoxFuture<'static, ()>>>,
+}
+
+impl<Ms> Recorder<Ms> {
+    pub(crate) fn new() -> Self {
+        Self {
+            effects: RefCell::new(VecDeque::new()),
+            notifications: RefCell::new(Vec::new()),
+            render_decisions: RefCell::new(Vec::new()),
+            render_scheduled: Cell::new(false),
+            cmds: RefCell::new(Vec::new()),
+            streams: RefCell::new(Vec::new()),
+        }
+    }
+
+    pub(crate) fn record_effects(&self, mut effects: VecDeque<Effect<Ms>>) {
+        self.notifications
+            .borrow_mut()
+            .extend(effects.iter().filter_map(|effect| match effect {
+                Effect::Notification(notification) => Some(notification.clone()),
+                Effect::Msg(_) | Effect::TriggeredHandler(_) => None,
+            }));
+        self.effects.borrow_mut().append(&mut effects);
+    }
+
+    pub(crate) fn record_render_decision(&self, should_render: ShouldRender) {
+        self.render_decisions.borrow_mut().push(should_render);
+    }
+
+    pub(crate) fn record_cmd(&self, cmd: LocalBoxFuture<'static, ()>) {
+        self.cmds.borrow_mut().push(cmd);
+    }
+
+    pub(crate) fn record_stream(&self, stream: LocalBoxFuture<'static, ()>) {
+        self.streams.borrow_mut().push(stream);
+    }
+
+    pub(crate) fn clear_tasks(&self) {
+        self.cmds.borrow_mut().clear();
+        self.streams.borrow_mut().clear();
+    }
+}
+
+// ------ ------ Tests ------ ------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::*;
+    use futures::future;
+
+    #[derive(Clone, Debug, PartialEq)]
+    enum Msg {
+        Increment,
+        IncrementLater,
+        Incremented(u32),
+        Rendered,
+    }
+
+    #[derive(Clone, Debug, PartialEq)]
+    struct CounterChanged(u32);
+
+    struct Model {
+        counter: u32,
+        rendered: bool,
+    }
+
+    fn init(_: Url, orders: &mut impl Orders<Msg>) -> Model {
+        orders.send_msg(Msg::Increment);
+        Model {
+            counter: 0,
+            rendered: false,
+        }
+    }
+
+    fn update(msg: Msg, model: &mut Model, orders: &mut impl Orders<Msg>) {
+        match msg {
+            Msg::Increment => {
+                model.counter += 1;
+                orders.notify(CounterChanged(model.counter));
+            }
+            Msg::IncrementLater => {
+                orders
+                    .skip()
+                    .perform_cmd(future::ready(Msg::Incremented(model.counter + 1)));
+            }
+            Msg::Incremented(counter) => {
+                model.counter = counter;
+                orders
+                    .force_render_now()
+                    .after_next_render(|_| Msg::Rendered);
+            }
+            Msg::Rendered => {
+                model.rendered = true;
+                orders.skip();
+            }
+        }
+    }
+
+    fn view(model: &Model) -> Node<Msg> {
+        button![model.counter, ev(Ev::Click, |_| Msg::Increment)]
+    }
+
+    #[test]
+    fn init_effects_are_queued() {
+        let app = TestApp::start(Url::new(), init, update, view);
+
+        assert_eq!(app.queued_msgs(), vec![Msg::Increment]);
+        assert!(app.render_scheduled());
+        assert!(app.rendered().is_none());
+
+        assert!(app.step());
+        assert_eq!(app.model().counter, 1);
+        assert_eq!(app.queued_effects(), 1);
+        assert_eq!(
+            app.notifications::<CounterChanged>(),
+            vec![CounterChanged(1)]
+        );
+
+        assert_eq!(app.settle(), 1);
+        assert!(!app.step());
+
+        app.render();
+        assert!(!app.render_scheduled());
+        assert_eq!(app.rendered().unwrap()[0].to_html(), "<button>1</button>");
+    }
+
+    #[test]
+    fn unmount_stops_app() {
+        let app = TestApp::start(Url::new(), init, update, view);
+        app.settle();
+        app.update(Msg::IncrementLater);
+        assert_eq!(app.pending_cmds(), 1);
+
+        app.clone_app().unmount();
+        assert_eq!(app.pending_cmds(), 0);
+        assert!(!app.render_scheduled());
+
+        app.send_msg(Msg::Increment);
+        assert_eq!(app.queued_effects(), 0);
+        app.render();
+        assert!(app.rendered().is_none());
+    }
+
+    #[test]
+    fn cmds_and_render_decisions() {
+        let app = TestApp::start(Url::new(), init, update, view);
+        app.settle();
+
+        app.update(Msg::IncrementLater);
+        assert_eq!(app.model().counter, 1);
+        assert_eq!(app.pending_cmds(), 1);
+
+        assert_eq!(app.run_cmds(), 1);
+        assert_eq!(app.pending_cmds(), 0);
+        assert_eq!(app.queued_msgs(), vec![Msg::Incremented(2)]);
+
+        app.settle();
+        assert_eq!(app.model().counter, 2);
+        assert!(app.model().rendered);
+        assert_eq!(app.view()[0].to_html(), "<button>2</button>");
+        assert_eq!(
+            app.render_decisions(),
+            vec![
+                ShouldRender::Render,
+                ShouldRender::Skip,
+                ShouldRender::ForceRenderNow,
+                ShouldRender::Skip,
+            ]
+        );
+    }
+
+    #[test]
+    fn headless_app_runs_without_dom() {
+        // Native tests would panic on any `web_sys` call.
+        let app = App::start_headless(Url::new(), init, update, view);
+        app.update(Msg::Increment);
+        app.rerender_vdom();
+
+        let recorder = app.data.recorder.as_ref().expect("recorder");
+        assert_eq!(recorder.effects.borrow().len(), 2);
+        let root_el = app.data.root_el.borrow();
+        let rendered = &root_el.as_ref().expect("rendered root").children;
+        assert_eq!(rendered.to_html(), "<button>0</button>");
+    }
+}
@@ -1,22 +1,32 @@
 pub mod attrs;
 pub mod el_ref;
 pub mod event_handler_manager;
+pub mod lifecycle;
 pub mod mailbox;
 pub mod node;
 pub mod patch;
+pub mod props;
 pub mod style;
 pub mod to_classes;
+pub mod to_html;
 pub mod update_el;
 pub mod values;
 pub mod view;
 
 pub use attrs::Attrs;
 pub use el_ref::{el_ref, ElRef, SharedNodeWs};
-pub use event_handler_manager::{EventHandler, EventHandlerManager, Listener};
+pub(crate) use event_handler_manager::EventDelegation;
+pub use event_handler_manager::{EventHandler, EventHandlerManager, Listener, ListenerOptions};
+pub use lifecycle::{on_insert, on_remove, on_update, Lifecycle, LifecycleHook};
 pub use mailbox::Mailbox;
-pub use node::{el_key, El, ElKey, IntoNodes, Node, Text};
+pub use node::{
+    el_key, error_boundary, lazy, lazy_hashed, portal, portal_to_body, transition, Boundary,
+    BoundaryError, El, ElKey, IntoNodes, Lazy, Node, Portal, Text, Transition,
+};
+pub use props::{IntoPropValue, PropValue, Props};
 pub use style::Style;
 pub use to_classes::ToClasses;
+pub use to_html::ToHtml;
 pub use update_el::{UpdateEl, UpdateElForIterator, UpdateElForOptionIterator};
 pub use values::{AsAtValue, AtValue, CSSValue};
 pub use view::View;
@@ -756,6 +766,48 @@ pub mod tests {
         }
     }
 
+    /// Test that reordered keyed elements keep their DOM nodes.
+    #[wasm_bindgen_test]
+    fn el_key_reorder_keeps_nodes() {
+        let app = create_app();
+        let mailbox = Mailbox::new(|_msg: Option<Msg>| {});
+
+        let doc = util::document();
+        let parent = doc.create_element("div").unwrap();
+
+        let mut vdom = Node::Element(El::empty(Tag::Div));
+        virtual_dom_bridge::assign_ws_nodes(&doc, &mut vdom);
+        let old_ws = vdom.node_ws().unwrap().clone();
+        parent.append_child(&old_ws).unwrap();
+
+        let list = |keys: &[u32]| -> Node<Msg> {
+            div![keys.iter().map(|key| li![el_key(key), key.to_string()])]
+        };
+
+        vdom = call_patch(&doc, &parent, &mailbox, vdom, list(&[1, 2, 3, 4, 5]), &app);
+        let nodes: Vec<_> = iter_child_nodes(&old_ws).collect();
+
+        // Move the last item to the front and remove the middle one.
+        app.set_instrumentation(true);
+        call_patch(&doc, &parent, &mailbox, vdom, list(&[5, 1, 2, 4, 6]), &app);
+
+        let texts: Vec<_> = iter_child_nodes(&old_ws)
+            .map(|node| node.text_content().unwrap())
+            .collect();
+        assert_eq!(texts, vec!["5", "1", "2", "4", "6"]);
+
+        let new_nodes: Vec<_> = iter_child_nodes(&old_ws).collect();
+        for (new_index, old_index) in [(0, 4), (1, 0), (2, 1), (3, 3)].iter() {
+            assert!(new_nodes[*new_index].is_same_node(Some(&nodes[*old_index])));
+        }
+
+        let counts = app.data.render_stats.get().unwrap().patch_counts;
+        assert_eq!(counts.move_node, 1);
+        assert_eq!(counts.append_el, 1);
+        assert_eq!(counts.remove_el, 1);
+        assert_eq!(counts.replace_el_by_el, 0);
+    }
+
     /// Tests an update() function that repeatedly sends messages or performs commands.
     #[wasm_bindgen_test(async)]
     async fn update_promises() {
@@ -842,4 +894,640 @@ pub mod tests {
             })
             .expect("test_value_sender.send probably wasn't called!");
     }
+
+    #[wasm_bindgen_test]
+    fn hydration_adopts_prerendered_nodes() {
+        let mailbox = Mailbox::new(|_msg: Option<Msg>| {});
+        let doc = util::document();
+        let parent = doc.create_element("div").expect("parent");
+        parent.set_inner_html(
+            "<div class=\"a\">\n  <p>Hello world</p>\n  <span>outdated</span>\n</div>",
+        );
+        let div_ws = parent.first_child().expect("div");
+        let p_ws = div_ws.child_nodes().item(1).expect("p");
+
+        let mut new_vdom: Vec<Node<Msg>> =
+            vec![div![C!["a"], p!["Hello ", "world"], button!["new"],]];
+        let mut counts = crate::app::PatchCounts::default();
+        patch::hydrate_els(&doc, &mailbox, &parent, &mut new_vdom, &mut counts);
+
+        assert!(div_ws.is_same_node(parent.first_child().as_ref()));
+        assert_eq!(div_ws.child_nodes().length(), 2);
+        assert!(p_ws.is_same_node(div_ws.first_child().as_ref()));
+        assert_eq!(p_ws.child_nodes().length(), 2);
+        assert_eq!(
+            p_ws.text_content().expect("p's text_content"),
+            "Hello world"
+        );
+        assert_eq!(div_ws.last_child().expect("button").node_name(), "BUTTON");
+        assert_eq!(
+            counts,
+            crate::app::PatchCounts {
+                patch_el: 2,
+                patch_text: 2,
+                insert_el: 1,
+                remove_el: 1,
+                ..crate::app::PatchCounts::default()
+            }
+        );
+
+        if let Node::Element(div_el) = &new_vdom[0] {
+            assert!(div_ws.is_same_node(div_el.node_ws.as_ref()));
+        } else {
+            panic!("Node not Element")
+        }
+    }
+
+    #[wasm_bindgen_test]
+    fn empty_mount_point_is_appended_to() {
+        let mailbox = Mailbox::new(|_msg: Option<Msg>| {});
+        let doc = util::document();
+        let parent = doc.create_element("div").expect("parent");
+        parent.set_inner_html("<!-- app -->\n");
+
+        let mut new_vdom: Vec<Node<Msg>> = vec![div!["a"], Node::new_text("b")];
+        let mut counts = crate::app::PatchCounts::default();
+        patch::hydrate_mount_point(&doc, &mailbox, &parent, &mut new_vdom, &mut counts);
+
+        assert_eq!(parent.inner_html(), "<!-- app -->\n<div>a</div>b");
+        assert_eq!((counts.append_el, counts.append_text), (1, 1));
+    }
+
+    #[wasm_bindgen_test]
+    fn lazy_reuses_unchanged_subtree() {
+        let app = create_app();
+        let mailbox = Mailbox::new(|_msg: Option<Msg>| {});
+        let doc = util::document();
+        let parent = doc.create_element("div").expect("parent");
+
+        let view_calls = std::rc::Rc::new(std::cell::Cell::new(0));
+        let view_list = |items: Vec<u32>| -> Node<Msg> {
+            let view_calls = view_calls.clone();
+            lazy(&"list", items, move |items: &Vec<u32>| {
+                view_calls.set(view_calls.get() + 1);
+                ul![items.iter().map(|item| li![item])]
+            })
+        };
+
+        let mut vdom = Node::Element(El::empty(Tag::Div));
+        virtual_dom_bridge::assign_ws_nodes(&doc, &mut vdom);
+        let old_ws = vdom.node_ws().expect("node_ws").clone();
+        parent.append_child(&old_ws).expect("successful appending");
+
+        vdom = call_patch(
+            &doc,
+            &parent,
+            &mailbox,
+            vdom,
+            div![view_list(vec![1, 2])],
+            &app,
+        );
+        assert_eq!(view_calls.get(), 1);
+        let ul_ws = old_ws.first_child().expect("ul");
+        assert_eq!(ul_ws.child_nodes().length(), 2);
+
+        vdom = call_patch(
+            &doc,
+            &parent,
+            &mailbox,
+            vdom,
+            div![view_list(vec![1, 2])],
+            &app,
+        );
+        assert_eq!(view_calls.get(), 1);
+        assert!(ul_ws.is_same_node(old_ws.first_child().as_ref()));
+
+        call_patch(
+            &doc,
+            &parent,
+            &mailbox,
+            vdom,
+            div![view_list(vec![1, 2, 3])],
+            &app,
+        );
+        assert_eq!(view_calls.get(), 2);
+        assert!(ul_ws.is_same_node(old_ws.first_child().as_ref()));
+        assert_eq!(ul_ws.child_nodes().length(), 3);
+    }
+
+    #[wasm_bindgen_test]
+    fn portal_is_patched_and_removed_with_parent() {
+        let app = create_app();
+        let mailbox = Mailbox::new(|_msg: Option<Msg>| {});
+        let doc = util::document();
+        let parent = doc.create_element("div").expect("parent");
+        let target = doc.create_element("div").expect("target");
+
+        let mut vdom = Node::Element(El::empty(Tag::Div));
+        virtual_dom_bridge::assign_ws_nodes(&doc, &mut vdom);
+        let old_ws = vdom.node_ws().expect("node_ws").clone();
+        parent.append_child(&old_ws).expect("successful appending");
+
+        let view = |modal: Option<&str>| -> Node<Msg> {
+            div![
+                "content",
+                section![modal.map(|text| portal(target.clone(), div![text]))],
+            ]
+        };
+
+        vdom = call_patch(&doc, &parent, &mailbox, vdom, view(Some("A")), &app);
+        assert_eq!(old_ws.text_content().as_deref(), Some("content"));
+        assert_eq!(target.inner_html(), "<div>A</div>");
+        let modal_ws = target.first_child().expect("modal");
+
+        vdom = call_patch(&doc, &parent, &mailbox, vdom, view(Some("B")), &app);
+        assert_eq!(target.inner_html(), "<div>B</div>");
+        assert!(modal_ws.is_same_node(target.first_child().as_ref()));
+
+        vdom = call_patch(&doc, &parent, &mailbox, vdom, view(None), &app);
+        assert_eq!(target.child_nodes().length(), 0);
+
+        vdom = call_patch(&doc, &parent, &mailbox, vdom, view(Some("C")), &app);
+        assert_eq!(target.inner_html(), "<div>C</div>");
+
+        // The portal is removed together with its parent.
+        call_patch(&doc, &parent, &mailbox, vdom, div!["content"], &app);
+        assert_eq!(target.child_nodes().length(), 0);
+    }
+
+    #[wasm_bindgen_test]
+    fn style_is_patched_per_property() {
+        let app = create_app();
+        let mailbox = Mailbox::new(|_msg: Option<Msg>| {});
+        let doc = util::document();
+        let parent = doc.create_element("div").expect("parent");
+
+        let mut vdom = Node::Element(El::empty(Tag::Div));
+        virtual_dom_bridge::assign_ws_nodes(&doc, &mut vdom);
+        let el_ws = vdom.node_ws().expect("node_ws").clone();
+        parent.append_child(&el_ws).expect("successful appending");
+        let declaration = el_ws
+            .dyn_ref::<web_sys::HtmlElement>()
+            .expect("html element")
+            .style();
+
+        vdom = call_patch(
+            &doc,
+            &parent,
+            &mailbox,
+            vdom,
+            div![style! {St::Color => "red", St::Margin => px(1)}],
+            &app,
+        );
+        // Set by a third-party library.
+        declaration.set_property("opacity", "0.5").unwrap();
+
+        call_patch(
+            &doc,
+            &parent,
+            &mailbox,
+            vdom,
+            div![style! {St::Color => "blue !important", "--accent" => "teal"}],
+            &app,
+        );
+        assert_eq!(declaration.get_property_value("color").unwrap(), "blue");
+        assert_eq!(declaration.get_property_priority("color"), "important");
+        assert_eq!(declaration.get_property_value("--accent").unwrap(), "teal");
+        assert_eq!(declaration.get_property_value("margin").unwrap(), "");
+        assert_eq!(declaration.get_property_value("opacity").unwrap(), "0.5");
+    }
+
+    #[wasm_bindgen_test]
+    fn shorthand_style_properties_keep_longhands() {
+        let app = create_app();
+        let mailbox = Mailbox::new(|_msg: Option<Msg>| {});
+        let doc = util::document();
+        let parent = doc.create_element("div").expect("parent");
+        let margins = |vdom: &Node<Msg>| {
+            let style = vdom
+                .node_ws()
+                .expect("node_ws")
+                .dyn_ref::<web_sys::HtmlElement>()
+                .expect("html element")
+                .style();
+            (
+                style.get_property_value("margin-top").unwrap(),
+                style.get_property_value("margin-left").unwrap(),
+            )
+        };
+
+        let mut vdom = Node::Element(El::empty(Tag::Div));
+        virtual_dom_bridge::assign_ws_nodes(&doc, &mut vdom);
+        parent
+            .append_child(vdom.node_ws().expect("node_ws"))
+            .expect("successful appending");
+
+        let view =
+            |margin: Option<&str>| div![style! {St::Margin => margin, St::MarginTop => "5px"}];
+        vdom = call_patch(&doc, &parent, &mailbox, vdom, view(Some("0px")), &app);
+        assert_eq!(margins(&vdom), ("5px".to_owned(), "0px".to_owned()));
+
+        // The changed shorthand is set before the unchanged longhand.
+        vdom = call_patch(&doc, &parent, &mailbox, vdom, view(Some("1px")), &app);
+        assert_eq!(margins(&vdom), ("5px".to_owned(), "1px".to_owned()));
+
+        // Removing the shorthand removes the longhand too.
+        vdom = call_patch(&doc, &parent, &mailbox, vdom, view(None), &app);
+        assert_eq!(margins(&vdom), ("5px".to_owned(), String::new()));
+    }
+
+    #[wasm_bindgen_test]
+    fn props_are_set_and_diffed() {
+        use crate::virtual_dom::PropValue;
+        use js_sys::Reflect;
+
+        let app = create_app();
+        let mailbox = Mailbox::new(|_msg: Option<Msg>| {});
+        let doc = util::document();
+        let parent = doc.create_element("div").expect("parent");
+        let get = |node: &web_sys::Node, name: &str| {
+            Reflect::get(node, &JsValue::from_str(name)).expect("get property")
+        };
+
+        let mut vdom = Node::Element(El::empty(Tag::Input));
+        virtual_dom_bridge::assign_ws_nodes(&doc, &mut vdom);
+        let el_ws = vdom.node_ws().expect("node_ws").clone();
+        parent.append_child(&el_ws).expect("successful appending");
+
+        vdom = call_patch(
+            &doc,
+            &parent,
+            &mailbox,
+            vdom,
+            input![props! {
+                "indeterminate" => true,
+                "tags" => PropValue::serde(&["a", "b"]),
+            }],
+            &app,
+        );
+        assert_eq!(get(&el_ws, "indeterminate"), JsValue::TRUE);
+        assert!(el_ws
+            .dyn_ref::<Element>()
+            .unwrap()
+            .get_attribute("indeterminate")
+            .is_none());
+        let tags = get(&el_ws, "tags");
+        assert_eq!(
+            js_sys::Array::from(&tags).join(",").as_string().unwrap(),
+            "a,b"
+        );
+
+        call_patch(
+            &doc,
+            &parent,
+            &mailbox,
+            vdom,
+            input![props! {"tags" => PropValue::serde(&["a", "b"])}],
+            &app,
+        );
+        // The equal serialized value isn't set again.
+        assert_eq!(get(&el_ws, "tags"), tags);
+        assert_eq!(get(&el_ws, "indeterminate"), JsValue::FALSE);
+    }
+
+    #[wasm_bindgen_test]
+    fn select_value_prop_is_set_after_options() {
+        let app = create_app();
+        let mailbox = Mailbox::new(|_msg: Option<Msg>| {});
+        let doc = util::document();
+        let parent = doc.create_element("div").expect("parent");
+        let view = |values: &[&'static str], value: &'static str| {
+            div![select![
+                props! {"value" => value},
+                values
+                    .iter()
+                    .map(|value| option![attrs! {At::Value => value}, value]),
+            ]]
+        };
+        let select_value = |vdom: &Node<Msg>| {
+            vdom.node_ws()
+                .and_then(web_sys::Node::first_child)
+                .expect("select")
+                .dyn_into::<web_sys::HtmlSelectElement>()
+                .expect("select element")
+                .value()
+        };
+
+        let mut vdom = Node::Element(El::empty(Tag::Div));
+        virtual_dom_bridge::assign_ws_nodes(&doc, &mut vdom);
+        parent
+            .append_child(vdom.node_ws().expect("node_ws"))
+            .expect("successful appending");
+
+        vdom = call_patch(&doc, &parent, &mailbox, vdom, view(&["a", "b"], "b"), &app);
+        assert_eq!(select_value(&vdom), "b");
+
+        // The new option is appended before the value is set.
+        vdom = call_patch(
+            &doc,
+            &parent,
+            &mailbox,
+            vdom,
+            view(&["a", "b", "c"], "c"),
+            &app,
+        );
+        assert_eq!(select_value(&vdom), "c");
+    }
+
+    #[wasm_bindgen_test]
+    fn once_handler_is_called_once() {
+        use std::{cell::Cell, rc::Rc};
+
+        let app = create_app();
+        let mailbox = Mailbox::new(|_msg: Option<Msg>| {});
+        let doc = util::document();
+        let parent = doc.create_element("div").expect("parent");
+        let clicks = Rc::new(Cell::new(0));
+
+        let mut vdom = Node::Element(El::empty(Tag::Div));
+        virtual_dom_bridge::assign_ws_nodes(&doc, &mut vdom);
+        let el_ws = vdom.node_ws().expect("node_ws").clone();
+        parent.append_child(&el_ws).expect("successful appending");
+
+        let on_click = |clicks: &Rc<Cell<i32>>| {
+            let clicks = Rc::clone(clicks);
+            ev(Ev::Click, move |_| clicks.set(clicks.get() + 1)).once()
+        };
+        vdom = call_patch(&doc, &parent, &mailbox, vdom, div![on_click(&clicks)], &app);
+        let el_ws = el_ws.dyn_into::<web_sys::HtmlElement>().unwrap();
+        el_ws.click();
+        el_ws.click();
+        // The listener is reused.
+        call_patch(&doc, &parent, &mailbox, vdom, div![on_click(&clicks)], &app);
+        el_ws.click();
+        assert_eq!(clicks.get(), 1);
+    }
+
+    #[wasm_bindgen_test]
+    fn delegated_handlers_are_called_from_target_up() {
+        use crate::virtual_dom::EventDelegation;
+        use std::{cell::RefCell, rc::Rc};
+
+        let app = create_app();
+        let doc = util::document();
+        let parent = doc.create_element("div").expect("parent");
+        let mut mailbox = Mailbox::new(|_msg: Option<Msg>| {});
+        mailbox.event_delegation = Some(EventDelegation::new(parent.clone().into()));
+        let calls = Rc::new(RefCell::new(Vec::new()));
+        let on_click = |name: &'static str, stop: bool| {
+            let calls = Rc::clone(&calls);
+            ev(Ev::Click, move |event| {
+                calls.borrow_mut().push(name);
+                if stop {
+                    event.stop_propagation();
+                }
+            })
+        };
+        let click = |selector: &str| {
+            parent
+                .query_selector(selector)
+                .unwrap()
+                .expect("element")
+                .dyn_into::<web_sys::HtmlElement>()
+                .unwrap()
+                .click();
+        };
+
+        let mut vdom = Node::Element(El::empty(Tag::Ul));
+        virtual_dom_bridge::assign_ws_nodes(&doc, &mut vdom);
+        parent
+            .append_child(vdom.node_ws().expect("node_ws"))
+            .expect("successful appending");
+
+        vdom = call_patch(
+            &doc,
+            &parent,
+            &mailbox,
+            vdom,
+            ul![
+                on_click("ul", false),
+                li![C!["a"], on_click("a", false), span!["A"]],
+                li![C!["b"], on_click("b", true)],
+            ],
+            &app,
+        );
+        click("span");
+        click(".b");
+        assert_eq!(*calls.borrow(), vec!["a", "ul", "b"]);
+
+        calls.borrow_mut().clear();
+        call_patch(
+            &doc,
+            &parent,
+            &mailbox,
+            vdom,
+            ul![
+                on_click("ul", false),
+                li![C!["a"], on_click("a2", false), span!["A"]],
+                li![C!["b"]],
+            ],
+            &app,
+        );
+        click("span");
+        click(".b");
+        assert_eq!(*calls.borrow(), vec!["a2", "ul", "ul"]);
+    }
+
+    #[wasm_bindgen_test]
+    fn delegated_handlers_are_called_after_target_listeners() {
+        use crate::virtual_dom::EventDelegation;
+        use std::{cell::RefCell, rc::Rc};
+
+        let app = create_app();
+        let doc = util::document();
+        let parent = doc.create_element("div").expect("parent");
+        let mut mailbox = Mailbox::new(|_msg: Option<Msg>| {});
+        mailbox.event_delegation = Some(EventDelegation::new(parent.clone().into()));
+        let calls = Rc::new(RefCell::new(Vec::new()));
+        let on_click = |name: &'static str, stop: bool| {
+            let calls = Rc::clone(&calls);
+            ev(Ev::Click, move |event| {
+                calls.borrow_mut().push(name);
+                if stop {
+                    event.stop_propagation();
+                }
+            })
+        };
+
+        let mut vdom = Node::Element(El::empty(Tag::Ul));
+        virtual_dom_bridge::assign_ws_nodes(&doc, &mut vdom);
+        parent
+            .append_child(vdom.node_ws().expect("node_ws"))
+            .expect("successful appending");
+
+        call_patch(
+            &doc,
+            &parent,
+            &mailbox,
+            vdom,
+            ul![
+                // Delegated.
+                on_click("ul", true),
+                // Handlers with options aren't delegated.
+                li![on_click("li", false).passive()],
+            ],
+            &app,
+        );
+        parent
+            .query_selector("li")
+            .unwrap()
+            .expect("li")
+            .dyn_into::<web_sys::HtmlElement>()
+            .unwrap()
+            .click();
+        assert_eq!(*calls.borrow(), vec!["li", "ul"]);
+    }
+
+    #[wasm_bindgen_test]
+    fn lifecycle_hooks_are_called_after_patch() {
+        use std::{cell::RefCell, rc::Rc};
+
+        let app = create_app();
+        let mailbox = Mailbox::new(|_msg: Option<Msg>| {});
+        let doc = util::document();
+        let parent = doc.create_element("div").expect("parent");
+        let calls = Rc::new(RefCell::new(Vec::new()));
+        let log = |name: &'static str| {
+            let calls = Rc::clone(&calls);
+            move |element: web_sys::Element| {
+                calls
+                    .borrow_mut()
+                    .push(format!("{} {}", name, element.tag_name().to_lowercase()));
+            }
+        };
+        let call_hooks = || {
+            for hook in app.data.lifecycle_hooks.replace(Vec::new()) {
+                hook.call();
+            }
+            calls.replace(Vec::new())
+        };
+
+        let mut vdom = Node::Element(El::empty(Tag::Div));
+        virtual_dom_bridge::assign_ws_nodes(&doc, &mut vdom);
+        parent
+            .append_child(vdom.node_ws().expect("node_ws"))
+            .expect("successful appending");
+
+        vdom = call_patch(
+            &doc,
+            &parent,
+            &mailbox,
+            vdom,
+            div![ul![
+                on_insert(log("insert")),
+                on_remove(log("remove")),
+                li![on_insert(log("insert")), on_update(log("update"))],
+            ]],
+            &app,
+        );
+        assert_eq!(call_hooks(), vec!["insert li", "insert ul"]);
+
+        vdom = call_patch(
+            &doc,
+            &parent,
+            &mailbox,
+            vdom,
+            div![ul![
+                on_remove(log("remove")),
+                li![on_update(log("update")), on_remove(log("remove"))],
+            ]],
+            &app,
+        );
+        assert_eq!(call_hooks(), vec!["update li"]);
+
+        call_patch(&doc, &parent, &mailbox, vdom, div![], &app);
+        assert_eq!(call_hooks(), vec!["remove ul", "remove li"]);
+    }
+
+    #[wasm_bindgen_test]
+    fn transition_defers_removal_of_children() {
+        let app = create_app();
+        let mailbox = Mailbox::new(|_msg: Option<Msg>| {});
+        let doc = util::document();
+        let parent = doc.create_element("div").expect("parent");
+
+        let mut vdom = Node::Element(El::empty(Tag::Div));
+        virtual_dom_bridge::assign_ws_nodes(&doc, &mut vdom);
+        parent
+            .append_child(vdom.node_ws().expect("node_ws"))
+            .expect("successful appending");
+
+        let view = |keys: &[&str]| {
+            div![transition(
+                "fade",
+                keys.iter()
+                    .map(|key| li![el_key(key), *key])
+                    .collect::<Vec<_>>()
+            )]
+        };
+        let items = || parent.query_selector_all("li").expect("query li");
+
+        vdom = call_patch(&doc, &parent, &mailbox, vdom, view(&["a"]), &app);
+        vdom = call_patch(&doc, &parent, &mailbox, vdom, view(&["a", "b"]), &app);
+        let entering = items().item(1).expect("b").dyn_into::<Element>().unwrap();
+        assert_eq!(entering.class_name(), "fade-enter-from fade-enter-active");
+
+        call_patch(&doc, &parent, &mailbox, vdom, view(&["b"]), &app);
+        assert_eq!(items().length(), 2);
+        let leaving = items().item(0).expect("a").dyn_into::<Element>().unwrap();
+        assert_eq!(leaving.text_content().as_deref(), Some("a"));
+        assert_eq!(leaving.class_name(), "fade-leave-from fade-leave-active");
+    }
+
+    #[wasm_bindgen_test]
+    async fn transition_removes_leaving_child_after_duration() {
+        use crate::testing::VirtualClock;
+        use futures::StreamExt;
+        use std::{cell::RefCell, rc::Rc};
+
+        let clock = VirtualClock::install();
+        let app = create_app();
+        let mailbox = Mailbox::new(|_msg: Option<Msg>| {});
+        let doc = util::document();
+        let parent = doc.create_element("div").expect("parent");
+        let removed = Rc::new(RefCell::new(Vec::new()));
+
+        let mut vdom = Node::Element(El::empty(Tag::Div));
+        virtual_dom_bridge::assign_ws_nodes(&doc, &mut vdom);
+        parent
+            .append_child(vdom.node_ws().expect("node_ws"))
+            .expect("successful appending");
+
+        let view = |keys: &[&'static str]| {
+            div![transition(
+                Transition::new("fade").duration_ms(300),
+                keys.iter()
+                    .map(|&key| {
+                        let removed = Rc::clone(&removed);
+                        li![
+                            el_key(&key),
+                            key,
+                            on_remove(move |_| removed.borrow_mut().push(key)),
+                        ]
+                    })
+                    .collect::<Vec<_>>()
+            )]
+        };
+        let items = || parent.query_selector_all("li").expect("query li").length();
+        let mut frames = seed::app::streams::animation_frame(|_| ());
+
+        vdom = call_patch(&doc, &parent, &mailbox, vdom, view(&["a", "b"]), &app);
+        call_patch(&doc, &parent, &mailbox, vdom, view(&["b"]), &app);
+        assert!(app.data.lifecycle_hooks.borrow().is_empty());
+        // Let the leave transition reach its `leave-to` stage and start waiting.
+        for _ in 0..3 {
+            frames.next().await;
+        }
+        assert_eq!(items(), 2);
+
+        clock.advance(299);
+        frames.next().await;
+        assert_eq!(items(), 2);
+        assert!(removed.borrow().is_empty());
+
+        clock.advance(1);
+        frames.next().await;
+        assert_eq!(items(), 1);
+        assert_eq!(*removed.borrow(), vec!["a"]);
+    }
 }
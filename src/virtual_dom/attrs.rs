@@ -1,4 +1,4 @@
-use super::{At, AtValue};
+use super::{to_html, At, AtValue};
 use indexmap::IndexMap;
 use std::fmt;
 
@@ -9,20 +9,10 @@ pub struct Attrs {
     pub vals: IndexMap<At, AtValue>,
 }
 
-/// Create an HTML-compatible string representation
+/// Create an HTML-compatible string representation - values are escaped.
 impl fmt::Display for Attrs {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let string = self
-            .vals
-            .iter()
-            .filter_map(|(k, v)| match v {
-                AtValue::Ignored => None,
-                AtValue::None => Some(k.to_string()),
-                AtValue::Some(value) => Some(format!("{}=\"{}\"", k.as_str(), value)),
-            })
-            .collect::<Vec<_>>()
-            .join(" ");
-        write!(f, "{}", string)
+        to_html::write_attrs(f, self)
     }
 }
 
@@ -1,11 +1,14 @@
 use crate::app::MessageMapper;
 use crate::virtual_dom::{Ev, Mailbox};
 use std::{cell::RefCell, collections::BTreeMap, rc::Rc};
+use wasm_bindgen::JsCast;
 
+pub(crate) mod delegation;
 pub mod event_handler;
 pub mod listener;
 
-pub use event_handler::EventHandler;
+pub(crate) use delegation::EventDelegation;
+pub use event_handler::{EventHandler, ListenerOptions};
 pub use listener::Listener;
 
 // ------ EventHandlerManager ------
@@ -13,7 +16,7 @@ pub use listener::Listener;
 #[derive(Debug, Default)]
 /// Manages event handlers and listeners for elements.
 pub struct EventHandlerManager<Ms> {
-    groups: BTreeMap<Ev, Group<Ms>>,
+    groups: BTreeMap<(Ev, ListenerOptions), Group<Ms>>,
 }
 
 // @TODO remove custom impl once https://github.com/rust-lang/rust/issues/26925 is fixed
@@ -45,6 +48,9 @@ impl<Ms> EventHandlerManager<Ms> {
 
     /// Creates missing listeners and attaches them to the given `event_target`.
     /// It can reuse listeners from the `old_manager`.
+    ///
+    /// Handlers without options on DOM nodes are delegated when the `mailbox` has
+    /// `EventDelegation` - see `app::Builder::event_delegation`.
     pub fn attach_listeners(
         &mut self,
         event_target: impl Into<web_sys::EventTarget> + 'static,
@@ -53,21 +59,44 @@ impl<Ms> EventHandlerManager<Ms> {
     ) {
         let event_target = event_target.into();
 
-        for (trigger, group) in &mut self.groups {
+        for ((trigger, options), group) in &mut self.groups {
             if group.listener.is_none() {
                 group.listener = old_manager
                     .as_mut()
                     .and_then(|old_manager| {
-                        old_manager
-                            .take_and_setup_listener(trigger, Rc::clone(&group.event_handlers))
+                        old_manager.take_and_setup_listener_with_options(
+                            trigger,
+                            *options,
+                            Rc::clone(&group.event_handlers),
+                        )
                     })
                     .or_else(|| {
-                        Some(Listener::new(
-                            trigger.clone(),
-                            event_target.clone(),
-                            Rc::clone(&group.event_handlers),
-                            mailbox.clone(),
-                        ))
+                        let event_handlers = Rc::clone(&group.event_handlers);
+                        Some(
+                            match (
+                                &mailbox.event_delegation,
+                                event_target.dyn_ref::<web_sys::Node>(),
+                            ) {
+                                (Some(delegation), Some(node))
+                                    if *options == ListenerOptions::default() =>
+                                {
+                                    Listener::delegated(
+                                        trigger.clone(),
+                                        node,
+                                        event_handlers,
+                                        delegation.clone(),
+                                        mailbox,
+                                    )
+                                }
+                                _ => Listener::with_options(
+                                    trigger.clone(),
+                                    *options,
+                                    event_target.clone(),
+                                    event_handlers,
+                                    mailbox.clone(),
+                                ),
+                            },
+                        )
                     });
             }
         }
@@ -77,11 +106,12 @@ impl<Ms> EventHandlerManager<Ms> {
     /// It doesn't create listeners automatically - you have to call `attach_listeners`.
     pub fn add_event_handlers(&mut self, event_handlers: Vec<EventHandler<Ms>>) {
         for handler in event_handlers {
-            if let Some(group) = self.groups.get_mut(&handler.trigger) {
+            let key = (handler.trigger.clone(), handler.options);
+            if let Some(group) = self.groups.get_mut(&key) {
                 group.event_handlers.borrow_mut().push(handler);
             } else {
                 self.groups.insert(
-                    handler.trigger.clone(),
+                    key,
                     Group {
                         event_handlers: Rc::new(RefCell::new(vec![handler])),
                         listener: None,
@@ -96,9 +126,23 @@ impl<Ms> EventHandlerManager<Ms> {
         &mut self,
         trigger: &Ev,
         event_handlers: Rc<RefCell<Vec<EventHandler<Ms>>>>,
+    ) -> Option<Listener<Ms>> {
+        self.take_and_setup_listener_with_options(
+            trigger,
+            ListenerOptions::default(),
+            event_handlers,
+        )
+    }
+
+    /// The same as `take_and_setup_listener`, but for the listener with `options`.
+    pub fn take_and_setup_listener_with_options(
+        &mut self,
+        trigger: &Ev,
+        options: ListenerOptions,
+        event_handlers: Rc<RefCell<Vec<EventHandler<Ms>>>>,
     ) -> Option<Listener<Ms>> {
         self.groups
-            .get_mut(trigger)
+            .get_mut(&(trigger.clone(), options))
             .and_then(|group| group.listener.take())
             .map(|listener| {
                 listener.set_event_handlers(event_handlers);
@@ -119,7 +163,7 @@ impl<Ms: 'static, OtherMs: 'static> MessageMapper<Ms, OtherMs> for EventHandlerM
             groups: self
                 .groups
                 .into_iter()
-                .map(|(trigger, group)| (trigger, group.map_msg(f.clone())))
+                .map(|(key, group)| (key, group.map_msg(f.clone())))
                 .collect(),
         }
     }
@@ -0,0 +1,289 @@
+//! Event delegation - see `app::Builder::event_delegation`.
+//!
+//! Listeners per event type are attached to roots (the mount point and portal targets).
+//! They walk from the event target up and call handlers of elements with delegated listeners.
+//! Elements are found by the id stored in their property.
+//!
+//! Bubbling events are handled in the bubble phase, so listeners of the target are called first.
+//! Events that don't bubble (e.g. `focus` or `mouseenter`) are handled in the capture phase
+//! because they don't reach roots otherwise.
+
+use super::EventHandler;
+use crate::browser::dom::{dom_error, DomError};
+use crate::virtual_dom::{Ev, Mailbox};
+use std::{
+    cell::{Cell, RefCell},
+    collections::{BTreeMap, HashMap},
+    mem,
+    rc::{Rc, Weak},
+};
+use wasm_bindgen::{closure::Closure, JsCast, JsValue};
+
+/// The element's property with the id of its delegated handlers.
+const NODE_ID_PROPERTY: &str = "__seedNodeId";
+
+thread_local! {
+    // Ids are unique across apps - an event may pass through elements of more apps.
+    static NEXT_NODE_ID: Cell<u32> = const { Cell::new(0) };
+}
+
+type Handlers<Ms> = Rc<RefCell<Vec<EventHandler<Ms>>>>;
+type HandlerCallback<Ms> = Rc<dyn Fn(web_sys::Event) -> Option<Ms>>;
+type RootCallback = Closure<dyn FnMut(web_sys::Event)>;
+
+/// The capture phase callback handles only events that don't bubble,
+/// the bubble phase callback only bubbling events.
+struct RootCallbacks {
+    capture: RootCallback,
+    bubble: RootCallback,
+}
+
+// ------ EventDelegation ------
+
+/// Delegated event handlers of the app and root listeners calling them.
+pub(crate) struct EventDelegation<Ms>(Rc<RefCell<State<Ms>>>);
+
+impl<Ms> Clone for EventDelegation<Ms> {
+    fn clone(&self) -> Self {
+        Self(Rc::clone(&self.0))
+    }
+}
+
+struct State<Ms> {
+    roots: Vec<web_sys::EventTarget>,
+    /// Callbacks per event type - they're attached to all roots.
+    callbacks: BTreeMap<Ev, RootCallbacks>,
+    handlers: HashMap<u32, BTreeMap<Ev, Handlers<Ms>>>,
+}
+
+impl<Ms> EventDelegation<Ms> {
+    pub(crate) fn new(root: web_sys::EventTarget) -> Self {
+        Self(Rc::new(RefCell::new(State {
+            roots: vec![root],
+            callbacks: BTreeMap::new(),
+            handlers: HashMap::new(),
+        })))
+    }
+
+    /// Portal targets are added as roots because they may be outside of the mount point.
+    /// Roots are kept until the app is unmounted.
+    pub(crate) fn add_root(&self, root: web_sys::EventTarget) {
+        let mut state = self.0.borrow_mut();
+        if state.roots.contains(&root) {
+            return;
+        }
+        for (trigger, callbacks) in &state.callbacks {
+            add_root_listeners(&root, trigger, callbacks);
+        }
+        state.roots.push(root);
+    }
+
+    /// Register `node`'s handlers and return the node id.
+    pub(crate) fn register(
+        &self,
+        node: &web_sys::Node,
+        trigger: &Ev,
+        handlers: Handlers<Ms>,
+        mailbox: &Mailbox<Ms>,
+    ) -> u32 {
+        let node_id = node_id(node);
+        let mut state = self.0.borrow_mut();
+        if !state.callbacks.contains_key(trigger) {
+            let callbacks = RootCallbacks {
+                capture: root_callback(
+                    Rc::downgrade(&self.0),
+                    trigger.clone(),
+                    mailbox.clone(),
+                    true,
+                ),
+                bubble: root_callback(
+                    Rc::downgrade(&self.0),
+                    trigger.clone(),
+                    mailbox.clone(),
+                    false,
+                ),
+            };
+            for root in &state.roots {
+                add_root_listeners(root, trigger, &callbacks);
+            }
+            state.callbacks.insert(trigger.clone(), callbacks);
+        }
+        state
+            .handlers
+            .entry(node_id)
+            .or_default()
+            .insert(trigger.clone(), handlers);
+        node_id
+    }
+
+    pub(crate) fn set_handlers(&self, node_id: u32, trigger: &Ev, handlers: Handlers<Ms>) {
+        if let Some(node_handlers) = self.0.borrow_mut().handlers.get_mut(&node_id) {
+            node_handlers.insert(trigger.clone(), handlers);
+        }
+    }
+
+    pub(crate) fn unregister(&self, node_id: u32, trigger: &Ev) {
+        let mut state = self.0.borrow_mut();
+        if let Some(node_handlers) = state.handlers.get_mut(&node_id) {
+            node_handlers.remove(trigger);
+            if node_handlers.is_empty() {
+                state.handlers.remove(&node_id);
+            }
+        }
+    }
+
+    /// Detach root listeners and forget all handlers - see `App::unmount`.
+    pub(crate) fn clear(&self) {
+        let state = &mut *self.0.borrow_mut();
+        for (trigger, callbacks) in mem::take(&mut state.callbacks) {
+            for root in &state.roots {
+                remove_root_listeners(root, &trigger, &callbacks);
+            }
+        }
+        state.handlers.clear();
+    }
+}
+
+impl<Ms> State<Ms> {
+    /// Callbacks of the event target and its ancestors, the target's callbacks first.
+    ///
+    /// Nested roots receive the same event - only the outermost root returns callbacks.
+    fn handler_callbacks(
+        &self,
+        trigger: &Ev,
+        event: &web_sys::Event,
+    ) -> Vec<Vec<HandlerCallback<Ms>>> {
+        let current_target: Option<JsValue> = event.current_target().map(Into::into);
+        let mut passed_current_target = false;
+        // E.g. `focus` or `mouseenter` don't bubble - only the target's callbacks are called.
+        let mut collect = true;
+
+        let mut callbacks_by_node = Vec::new();
+        let mut node = event
+            .target()
+            .and_then(|target| target.dyn_into::<web_sys::Node>().ok());
+        while let Some(current) = node {
+            let current_js: &JsValue = current.as_ref();
+            if passed_current_target
+                && self
+                    .roots
+                    .iter()
+                    .any(|root| AsRef::<JsValue>::as_ref(root) == current_js)
+            {
+                return Vec::new();
+            }
+            passed_current_target |= current_target.as_ref() == Some(current_js);
+
+            if let Some(handlers) = read_node_id(&current)
+                .filter(|_| collect)
+                .and_then(|node_id| self.handlers.get(&node_id))
+                .and_then(|node_handlers| node_handlers.get(trigger))
+            {
+                callbacks_by_node.push(
+                    handlers
+                        .borrow()
+                        .iter()
+                        .map(|handler| Rc::clone(&handler.callback))
+                        .collect(),
+                );
+            }
+            collect = event.bubbles();
+            node = current.parent_node();
+        }
+        callbacks_by_node
+    }
+}
+
+/// `capture` - whether the callback is attached in the capture phase - see `RootCallbacks`.
+fn root_callback<Ms>(
+    state: Weak<RefCell<State<Ms>>>,
+    trigger: Ev,
+    mailbox: Mailbox<Ms>,
+    capture: bool,
+) -> RootCallback {
+    Closure::new(move |event: web_sys::Event| {
+        // Both callbacks are called when the root is the event target.
+        if event.bubbles() == capture {
+            return;
+        }
+        let state = match state.upgrade() {
+            Some(state) => state,
+            None => return,
+        };
+        // Callbacks are called after the borrow is released - they may patch the DOM.
+        let callbacks_by_node = state.borrow().handler_callbacks(&trigger, &event);
+        for callbacks in callbacks_by_node {
+            for callback in callbacks {
+                mailbox.send(callback(event.clone()));
+            }
+            if event.cancel_bubble() {
+                break;
+            }
+        }
+    })
+}
+
+fn add_root_listeners(root: &web_sys::EventTarget, trigger: &Ev, callbacks: &RootCallbacks) {
+    for (callback, capture) in [(&callbacks.capture, true), (&callbacks.bubble, false)] {
+        if let Err(error) = root.add_event_listener_with_callback_and_bool(
+            trigger.as_str(),
+            callback.as_ref().unchecked_ref(),
+            capture,
+        ) {
+            dom_error::report(DomError::new(
+                format!("attach delegated listener `{}`", trigger.as_str()),
+                error,
+                root.dyn_ref(),
+            ));
+        }
+    }
+}
+
+fn remove_root_listeners(root: &web_sys::EventTarget, trigger: &Ev, callbacks: &RootCallbacks) {
+    for (callback, capture) in [(&callbacks.capture, true), (&callbacks.bubble, false)] {
+        if let Err(error) = root.remove_event_listener_with_callback_and_bool(
+            trigger.as_str(),
+            callback.as_ref().unchecked_ref(),
+            capture,
+        ) {
+            dom_error::report(DomError::new(
+                format!("detach delegated listener `{}`", trigger.as_str()),
+                error,
+                root.dyn_ref(),
+            ));
+        }
+    }
+}
+
+fn read_node_id(node: &web_sys::Node) -> Option<u32> {
+    let node_id = js_sys::Reflect::get(node, &JsValue::from_str(NODE_ID_PROPERTY))
+        .ok()?
+        .as_f64()?;
+    // Ids are stored from `u32`.
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    Some(node_id as u32)
+}
+
+/// Return the node's id; a new id is assigned to the node if it doesn't have one.
+fn node_id(node: &web_sys::Node) -> u32 {
+    if let Some(node_id) = read_node_id(node) {
+        return node_id;
+    }
+    let node_id = NEXT_NODE_ID.with(|next_node_id| {
+        let node_id = next_node_id.get();
+        next_node_id.set(node_id.wrapping_add(1));
+        node_id
+    });
+    if let Err(error) = js_sys::Reflect::set(
+        node,
+        &JsValue::from_str(NODE_ID_PROPERTY),
+        &JsValue::from(node_id),
+    ) {
+        dom_error::report(DomError::new(
+            format!("set property `{}`", NODE_ID_PROPERTY),
+            error,
+            Some(node),
+        ));
+    }
+    node_id
+}
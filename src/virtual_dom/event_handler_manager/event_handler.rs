@@ -6,6 +6,8 @@ use std::{fmt, rc::Rc};
 pub struct EventHandler<Ms> {
     pub trigger: Ev,
     pub callback: Rc<dyn Fn(web_sys::Event) -> Option<Ms>>,
+    /// Handlers with different options are called by different listeners.
+    pub options: ListenerOptions,
 }
 
 // @TODO remove custom impl once https://github.com/rust-lang/rust/issues/26925 is fixed
@@ -14,6 +16,7 @@ impl<Ms> Clone for EventHandler<Ms> {
         Self {
             trigger: self.trigger.clone(),
             callback: Rc::clone(&self.callback),
+            options: self.options,
         }
     }
 }
@@ -26,8 +29,34 @@ impl<Ms> EventHandler<Ms> {
         Self {
             trigger: trigger.into(),
             callback: Rc::new(callback),
+            options: ListenerOptions::default(),
         }
     }
+
+    /// The handler is called in the capture phase - i.e. before handlers of descendants.
+    pub const fn capture(mut self) -> Self {
+        self.options.capture = true;
+        self
+    }
+
+    /// The handler can't prevent the default action - e.g. scrolling on `touchmove`.
+    /// Browsers don't have to wait for the handler and can scroll immediately.
+    pub const fn passive(mut self) -> Self {
+        self.options.passive = true;
+        self
+    }
+
+    /// The handler is called only for the first event. The listener is reused
+    /// while the element is patched, so the handler isn't called again even after rerender.
+    pub const fn once(mut self) -> Self {
+        self.options.once = true;
+        self
+    }
+
+    pub const fn options(mut self, options: ListenerOptions) -> Self {
+        self.options = options;
+        self
+    }
 }
 
 impl<Ms: 'static, OtherMs: 'static> MessageMapper<Ms, OtherMs> for EventHandler<Ms> {
@@ -44,6 +73,7 @@ impl<Ms: 'static, OtherMs: 'static> MessageMapper<Ms, OtherMs> for EventHandler<
         EventHandler {
             trigger: self.trigger,
             callback: Rc::new(new_callback),
+            options: self.options,
         }
     }
 }
@@ -53,3 +83,27 @@ impl<Ms> fmt::Debug for EventHandler<Ms> {
         write!(f, "EventHandler('{}')", self.trigger.as_str())
     }
 }
+
+// ------ ListenerOptions ------
+
+/// Options of the DOM event listener - see `EventHandler::capture`, `EventHandler::passive`
+/// and `EventHandler::once`.
+///
+/// [MDN reference](https://developer.mozilla.org/en-US/docs/Web/API/EventTarget/addEventListener#parameters)
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ListenerOptions {
+    pub capture: bool,
+    pub passive: bool,
+    pub once: bool,
+}
+
+impl ListenerOptions {
+    pub(crate) fn to_web_sys(self) -> web_sys::AddEventListenerOptions {
+        let mut options = web_sys::AddEventListenerOptions::new();
+        options
+            .capture(self.capture)
+            .passive(self.passive)
+            .once(self.once);
+        options
+    }
+}
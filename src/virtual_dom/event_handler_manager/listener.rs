@@ -1,3 +1,5 @@
+use super::{delegation::EventDelegation, event_handler::ListenerOptions};
+use crate::browser::dom::{dom_error, DomError};
 use crate::browser::util::ClosureNew;
 use crate::virtual_dom::{Ev, EventHandler, Mailbox};
 use enclose::enc;
@@ -14,12 +16,24 @@ use wasm_bindgen::{closure::Closure, JsCast};
 pub struct Listener<Ms> {
     // Event to listen to.
     trigger: Ev,
-    // "portal" to event handlers - it allows to call event handlers from the JS world.
-    portal: Portal<Rc<RefCell<Vec<EventHandler<Ms>>>>>,
-    // `callback` is invoked from the JS world and calls event handlers in the `portal`.
-    callback: Closure<dyn FnMut(web_sys::Event)>,
-    // Element where the listener is attached.
-    event_target: web_sys::EventTarget,
+    options: ListenerOptions,
+    kind: ListenerKind<Ms>,
+}
+
+enum ListenerKind<Ms> {
+    Element {
+        // "portal" to event handlers - it allows to call event handlers from the JS world.
+        portal: Portal<Rc<RefCell<Vec<EventHandler<Ms>>>>>,
+        // `callback` is invoked from the JS world and calls event handlers in the `portal`.
+        callback: Closure<dyn FnMut(web_sys::Event)>,
+        // Element where the listener is attached.
+        event_target: web_sys::EventTarget,
+    },
+    /// Event handlers are called by the app's root listener - see `EventDelegation`.
+    Delegated {
+        delegation: EventDelegation<Ms>,
+        node_id: u32,
+    },
 }
 
 impl<Ms> Listener<Ms> {
@@ -29,6 +43,23 @@ impl<Ms> Listener<Ms> {
         event_target: web_sys::EventTarget,
         event_handlers: Rc<RefCell<Vec<EventHandler<Ms>>>>,
         mailbox: Mailbox<Ms>,
+    ) -> Self {
+        Self::with_options(
+            trigger,
+            ListenerOptions::default(),
+            event_target,
+            event_handlers,
+            mailbox,
+        )
+    }
+
+    /// Create a new listener with `options` and attach it to the element.
+    pub fn with_options(
+        trigger: Ev,
+        options: ListenerOptions,
+        event_target: web_sys::EventTarget,
+        event_handlers: Rc<RefCell<Vec<EventHandler<Ms>>>>,
+        mailbox: Mailbox<Ms>,
     ) -> Self {
         let portal_to_event_handlers = Portal::new(event_handlers);
 
@@ -51,37 +82,99 @@ impl<Ms> Listener<Ms> {
             }),
         );
 
-        event_target
-            .add_event_listener_with_callback(trigger.as_str(), callback.as_ref().unchecked_ref())
-            .expect("attach listener");
+        if let Err(error) = event_target
+            .add_event_listener_with_callback_and_add_event_listener_options(
+                trigger.as_str(),
+                callback.as_ref().unchecked_ref(),
+                &options.to_web_sys(),
+            )
+        {
+            dom_error::report(DomError::new(
+                format!("attach listener `{}`", trigger.as_str()),
+                error,
+                event_target.dyn_ref(),
+            ));
+        }
 
         Self {
             trigger,
-            callback,
-            event_target,
-            portal: portal_to_event_handlers,
+            options,
+            kind: ListenerKind::Element {
+                callback,
+                event_target,
+                portal: portal_to_event_handlers,
+            },
+        }
+    }
+
+    /// Register event handlers in the app's `EventDelegation` instead of attaching a listener.
+    pub(crate) fn delegated(
+        trigger: Ev,
+        node: &web_sys::Node,
+        event_handlers: Rc<RefCell<Vec<EventHandler<Ms>>>>,
+        delegation: EventDelegation<Ms>,
+        mailbox: &Mailbox<Ms>,
+    ) -> Self {
+        let node_id = delegation.register(node, &trigger, event_handlers, mailbox);
+        Self {
+            trigger,
+            options: ListenerOptions::default(),
+            kind: ListenerKind::Delegated {
+                delegation,
+                node_id,
+            },
         }
     }
 
     pub fn set_event_handlers(&self, event_handlers: Rc<RefCell<Vec<EventHandler<Ms>>>>) {
-        self.portal.update(|_| event_handlers);
+        match &self.kind {
+            ListenerKind::Element { portal, .. } => portal.update(|_| event_handlers),
+            ListenerKind::Delegated {
+                delegation,
+                node_id,
+            } => delegation.set_handlers(*node_id, &self.trigger, event_handlers),
+        }
     }
 }
 
 impl<Ms> Drop for Listener<Ms> {
     fn drop(&mut self) {
-        self.event_target
-            .remove_event_listener_with_callback(
-                self.trigger.as_str(),
-                self.callback.as_ref().unchecked_ref(),
-            )
-            .expect("detach listener");
+        let (callback, event_target) = match &self.kind {
+            ListenerKind::Element {
+                callback,
+                event_target,
+                ..
+            } => (callback, event_target),
+            ListenerKind::Delegated {
+                delegation,
+                node_id,
+            } => {
+                delegation.unregister(*node_id, &self.trigger);
+                return;
+            }
+        };
+        if let Err(error) = event_target.remove_event_listener_with_callback_and_bool(
+            self.trigger.as_str(),
+            callback.as_ref().unchecked_ref(),
+            self.options.capture,
+        ) {
+            dom_error::report(DomError::new(
+                format!("detach listener `{}`", self.trigger.as_str()),
+                error,
+                event_target.dyn_ref(),
+            ));
+        }
     }
 }
 
 impl<Ms> fmt::Debug for Listener<Ms> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "Listener('{}')", self.trigger.as_str())
+        match self.kind {
+            ListenerKind::Element { .. } => write!(f, "Listener('{}')", self.trigger.as_str()),
+            ListenerKind::Delegated { .. } => {
+                write!(f, "Listener('{}', delegated)", self.trigger.as_str())
+            }
+        }
     }
 }
 
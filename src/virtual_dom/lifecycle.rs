@@ -0,0 +1,176 @@
+//! Element lifecycle hooks - see `on_insert`, `on_update` and `on_remove`.
+
+use super::{El, Node};
+use crate::app::MessageMapper;
+use std::{fmt, rc::Rc};
+use wasm_bindgen::JsCast;
+
+/// Call `handler` with the element when it's been inserted into the DOM -
+/// e.g. to initialize a JS widget.
+///
+/// Hooks are called after the render, so the element is connected and its children are rendered.
+/// Hooks of children are called before the hook of their parent.
+///
+/// # Example
+///
+/// ```rust,no_run
+///div![
+///    on_insert(|element| Msg::ChartInserted(element)),
+///    on_remove(|_| Msg::ChartRemoved),
+///]
+/// ```
+pub fn on_insert<Ms: 'static, MsU: 'static>(
+    handler: impl FnOnce(web_sys::Element) -> MsU + 'static + Clone,
+) -> LifecycleHook<Ms> {
+    LifecycleHook::new(Lifecycle::Insert, handler)
+}
+
+/// Call `handler` with the element when it's been patched - i.e. after every render
+/// that keeps the element, unless the element is in an unchanged `lazy` subtree.
+pub fn on_update<Ms: 'static, MsU: 'static>(
+    handler: impl FnOnce(web_sys::Element) -> MsU + 'static + Clone,
+) -> LifecycleHook<Ms> {
+    LifecycleHook::new(Lifecycle::Update, handler)
+}
+
+/// Call `handler` with the element when it's been removed from the DOM -
+/// e.g. to destroy a JS widget.
+///
+/// Hooks of removed descendants are called, too - parent's hook first.
+/// Hooks of children leaving with `transition` are called when the leave transition ends.
+/// The hooks are also called when the app is unmounted; their messages are ignored then.
+pub fn on_remove<Ms: 'static, MsU: 'static>(
+    handler: impl FnOnce(web_sys::Element) -> MsU + 'static + Clone,
+) -> LifecycleHook<Ms> {
+    LifecycleHook::new(Lifecycle::Remove, handler)
+}
+
+// ------ Lifecycle ------
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Lifecycle {
+    Insert,
+    Update,
+    Remove,
+}
+
+// ------ LifecycleHook ------
+
+/// See `on_insert`, `on_update` and `on_remove`.
+pub struct LifecycleHook<Ms> {
+    pub lifecycle: Lifecycle,
+    pub callback: Rc<dyn Fn(web_sys::Element) -> Option<Ms>>,
+}
+
+// @TODO remove custom impl once https://github.com/rust-lang/rust/issues/26925 is fixed
+impl<Ms> Clone for LifecycleHook<Ms> {
+    fn clone(&self) -> Self {
+        Self {
+            lifecycle: self.lifecycle,
+            callback: Rc::clone(&self.callback),
+        }
+    }
+}
+
+impl<Ms: 'static> LifecycleHook<Ms> {
+    fn new<MsU: 'static>(
+        lifecycle: Lifecycle,
+        handler: impl FnOnce(web_sys::Element) -> MsU + 'static + Clone,
+    ) -> Self {
+        let callback = map_callback_return_to_option_ms!(
+            dyn Fn(web_sys::Element) -> Option<Ms>,
+            handler.clone(),
+            "Lifecycle hook can return only Msg, Option<Msg> or ()!",
+            Rc
+        );
+        Self {
+            lifecycle,
+            callback,
+        }
+    }
+}
+
+impl<Ms: 'static, OtherMs: 'static> MessageMapper<Ms, OtherMs> for LifecycleHook<Ms> {
+    type SelfWithOtherMs = LifecycleHook<OtherMs>;
+    fn map_msg(self, f: impl FnOnce(Ms) -> OtherMs + 'static + Clone) -> LifecycleHook<OtherMs> {
+        let callback = self.callback;
+        LifecycleHook {
+            lifecycle: self.lifecycle,
+            callback: Rc::new(move |element| callback(element).map(f.clone())),
+        }
+    }
+}
+
+impl<Ms> fmt::Debug for LifecycleHook<Ms> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "LifecycleHook({:?})", self.lifecycle)
+    }
+}
+
+// ------ HookCall ------
+
+/// The hook queued while patching - hooks are called after the render.
+pub(crate) struct HookCall<Ms> {
+    callback: Rc<dyn Fn(web_sys::Element) -> Option<Ms>>,
+    element: web_sys::Element,
+}
+
+impl<Ms> HookCall<Ms> {
+    pub(crate) fn call(self) -> Option<Ms> {
+        (self.callback)(self.element)
+    }
+}
+
+/// Queue `el`'s hooks for `lifecycle`.
+pub(crate) fn queue_hooks<Ms>(el: &El<Ms>, lifecycle: Lifecycle, queue: &mut Vec<HookCall<Ms>>) {
+    if el.hooks.is_empty() {
+        return;
+    }
+    let element = match el.node_ws.as_ref().and_then(JsCast::dyn_ref) {
+        Some(element) => element,
+        None => return,
+    };
+    queue.extend(
+        el.hooks
+            .iter()
+            .filter(|hook| hook.lifecycle == lifecycle)
+            .map(|hook| HookCall {
+                callback: Rc::clone(&hook.callback),
+                element: web_sys::Element::clone(element),
+            }),
+    );
+}
+
+/// Queue hooks of `node` and its descendants for `Lifecycle::Insert` or `Lifecycle::Remove`.
+pub(crate) fn queue_subtree_hooks<Ms>(
+    node: &Node<Ms>,
+    lifecycle: Lifecycle,
+    queue: &mut Vec<HookCall<Ms>>,
+) {
+    match node.unlazy() {
+        Node::Element(el) => queue_el_subtree_hooks(el, lifecycle, queue),
+        Node::Portal(portal) => {
+            for child in &portal.children {
+                queue_subtree_hooks(child, lifecycle, queue);
+            }
+        }
+        Node::Text(_) | Node::Empty | Node::NoChange | Node::Lazy(_) => (),
+    }
+}
+
+/// See `queue_subtree_hooks`.
+pub(crate) fn queue_el_subtree_hooks<Ms>(
+    el: &El<Ms>,
+    lifecycle: Lifecycle,
+    queue: &mut Vec<HookCall<Ms>>,
+) {
+    if lifecycle == Lifecycle::Remove {
+        queue_hooks(el, lifecycle, queue);
+    }
+    for child in &el.children {
+        queue_subtree_hooks(child, lifecycle, queue);
+    }
+    if lifecycle == Lifecycle::Insert {
+        queue_hooks(el, lifecycle, queue);
+    }
+}
@@ -1,13 +1,17 @@
+use super::event_handler_manager::EventDelegation;
 use std::rc::Rc;
 
 pub struct Mailbox<Message: 'static> {
     func: Rc<dyn Fn(Option<Message>)>,
+    /// Listeners are delegated to the app's root when it's `Some`.
+    pub(crate) event_delegation: Option<EventDelegation<Message>>,
 }
 
 impl<Ms> Mailbox<Ms> {
     pub fn new(func: impl Fn(Option<Ms>) + 'static) -> Self {
         Mailbox {
             func: Rc::new(func),
+            event_delegation: None,
         }
     }
 
@@ -20,6 +24,7 @@ impl<Ms> Clone for Mailbox<Ms> {
     fn clone(&self) -> Self {
         Mailbox {
             func: self.func.clone(),
+            event_delegation: self.event_delegation.clone(),
         }
     }
 }
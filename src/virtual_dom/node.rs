@@ -1,16 +1,24 @@
-use super::{AtValue, CSSValue, EventHandler, St};
+use super::{AtValue, CSSValue, EventHandler, St, ToHtml};
 use crate::app::MessageMapper;
 use crate::browser::dom::Namespace;
 use std::borrow::Cow;
 use std::fmt;
 
+pub mod boundary;
 pub mod el;
 pub mod into_nodes;
+pub mod lazy;
+pub mod portal;
 pub mod text;
+pub mod transition;
 
+pub use boundary::{error_boundary, Boundary, BoundaryError};
 pub use el::{el_key, El, ElKey};
 pub use into_nodes::IntoNodes;
+pub use lazy::{lazy, lazy_hashed, Lazy};
+pub use portal::{portal, portal_to_body, Portal};
 pub use text::Text;
+pub use transition::{transition, Transition};
 
 /// A component in our virtual DOM.
 /// [MDN reference](https://developer.mozilla.org/en-US/docs/Web/API/Node)
@@ -22,6 +30,10 @@ pub enum Node<Ms> {
     Text(Text),
     Empty,
     NoChange,
+    /// See `lazy`.
+    Lazy(Lazy<Ms>),
+    /// See `portal`.
+    Portal(Portal<Ms>),
 }
 
 // @TODO remove custom impl once https://github.com/rust-lang/rust/issues/26925 is fixed
@@ -32,6 +44,8 @@ impl<Ms> Clone for Node<Ms> {
             Self::Text(text) => Self::Text(text.clone()),
             Self::Empty => Self::Empty,
             Self::NoChange => Self::NoChange,
+            Self::Lazy(lazy) => Self::Lazy(lazy.clone()),
+            Self::Portal(portal) => Self::Portal(portal.clone()),
         }
     }
 }
@@ -39,9 +53,9 @@ impl<Ms> Clone for Node<Ms> {
 impl<Ms> fmt::Display for Node<Ms> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
-            Self::Element(element) => write!(f, "{}", element),
-            Self::Text(text) => write!(f, "{}", text),
-            Self::Empty => write!(f, ""),
+            Self::Element(_) | Self::Text(_) | Self::Empty | Self::Lazy(_) | Self::Portal(_) => {
+                self.write_html(f)
+            }
             Self::NoChange => write!(f, "[NoChange]"),
         }
     }
@@ -125,15 +139,17 @@ impl<Ms> Node<Ms> {
         match self {
             Node::Element(el) => el.get_text(),
             Node::Text(text) => text.text.to_string(),
+            Node::Lazy(lazy) => lazy.rendered().get_text(),
             _ => "".to_string(),
         }
     }
 
-    /// Retrive `key` attached to the `El`
+    /// Retrive `key` attached to the `El` or the `Lazy` node.
     #[allow(clippy::missing_const_for_fn)]
     pub fn el_key(&self) -> Option<&ElKey> {
         match self {
             Node::Element(el) => el.key.as_ref(),
+            Node::Lazy(lazy) => Some(&lazy.key),
             _ => None,
         }
     }
@@ -177,14 +193,34 @@ impl<Ms> Node<Ms> {
         match self {
             Node::Text(t) => t.strip_ws_node(),
             Node::Element(e) => e.strip_ws_nodes_from_self_and_children(),
+            Node::Lazy(lazy) => {
+                if let Some(node) = lazy.node.as_mut() {
+                    node.strip_ws_nodes_from_self_and_children();
+                }
+            }
+            Node::Portal(portal) => {
+                portal.target_ws = None;
+                for child in &mut portal.children {
+                    child.strip_ws_nodes_from_self_and_children();
+                }
+            }
             Node::Empty | Node::NoChange => (),
         }
     }
 
     #[cfg(debug_assertions)]
     pub fn warn_about_script_tags(&self) {
-        if let Node::Element(e) = self {
-            e.warn_about_script_tags();
+        match self {
+            Node::Element(e) => e.warn_about_script_tags(),
+            Node::Lazy(Lazy {
+                node: Some(node), ..
+            }) => node.warn_about_script_tags(),
+            Node::Portal(portal) => {
+                for child in &portal.children {
+                    child.warn_about_script_tags();
+                }
+            }
+            _ => (),
         }
     }
 
@@ -194,9 +230,35 @@ impl<Ms> Node<Ms> {
             Self::Element(El { node_ws: val, .. }) | Self::Text(Text { node_ws: val, .. }) => {
                 val.as_ref()
             }
+            Self::Lazy(lazy) => lazy.node.as_ref()?.node_ws(),
             _ => None,
         }
     }
+
+    /// Render `Lazy` nodes and return the node that represents `self` in the DOM.
+    pub(crate) fn unlazy_mut(&mut self) -> &mut Self {
+        match self {
+            Self::Lazy(lazy) => lazy.render().unlazy_mut(),
+            node => node,
+        }
+    }
+
+    /// The same as `unlazy_mut`, but it stops at a `Lazy` node that hasn't been rendered yet.
+    pub(crate) fn unlazy(&self) -> &Self {
+        match self {
+            Self::Lazy(Lazy {
+                node: Some(node), ..
+            }) => node.unlazy(),
+            node => node,
+        }
+    }
+
+    pub(crate) fn into_unlazy(self) -> Self {
+        match self {
+            Self::Lazy(lazy) => lazy.into_node().into_unlazy(),
+            node => node,
+        }
+    }
 }
 
 impl<Ms: 'static, OtherMs: 'static> MessageMapper<Ms, OtherMs> for Node<Ms> {
@@ -208,6 +270,8 @@ impl<Ms: 'static, OtherMs: 'static> MessageMapper<Ms, OtherMs> for Node<Ms> {
             Node::Text(text) => Node::Text(text),
             Node::Empty => Node::Empty,
             Node::NoChange => Node::NoChange,
+            Node::Lazy(lazy) => Node::Lazy(lazy.map_msg(f)),
+            Node::Portal(portal) => Node::Portal(portal.map_msg(f)),
         }
     }
 }
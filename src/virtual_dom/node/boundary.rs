@@ -0,0 +1,188 @@
+use super::{El, IntoNodes, Lazy, Node};
+use crate::app::MessageMapper;
+use crate::browser::dom::{virtual_dom_bridge, DomError};
+use crate::virtual_dom::lifecycle::{self, HookCall};
+use crate::virtual_dom::{Lifecycle, Mailbox, St, Tag};
+use std::fmt;
+use std::rc::Rc;
+use web_sys::Document;
+
+/// Render `content` or `fallback` if `content` is an error or the DOM operations
+/// on its subtree fail while rendering.
+///
+/// The boundary is rendered as a `seed-boundary` element with `display: contents`
+/// so it doesn't affect the layout. Failed content is tried again on the next render.
+///
+/// Errors caught by a boundary aren't sent to the app - see `DomError`.
+///
+/// # Example
+///
+/// ```rust,no_run
+///fn view(model: &Model) -> Node<Msg> {
+///    div![
+///        error_boundary(view_chart(&model.chart), |error| {
+///            div!["The chart cannot be displayed: ", error.to_string()]
+///        }),
+///    ]
+///}
+///
+///fn view_chart(chart: &Chart) -> Result<Node<Msg>, ChartError> {
+///    ...
+///}
+/// ```
+pub fn error_boundary<Ms: 'static, E: fmt::Display>(
+    content: Result<impl IntoNodes<Ms>, E>,
+    fallback: impl Fn(&BoundaryError) -> Node<Ms> + 'static,
+) -> Node<Ms> {
+    let boundary = Boundary {
+        fallback: Rc::new(fallback),
+    };
+    let mut el = El::empty(Tag::Custom("seed-boundary".into()));
+    el.style.add(St::Display, "contents");
+    el.children = match content {
+        Ok(content) => content.into_nodes(),
+        Err(error) => vec![(boundary.fallback)(&BoundaryError::View(error.to_string()))],
+    };
+    el.boundary = Some(boundary);
+    Node::Element(el)
+}
+
+// ------ BoundaryError ------
+
+#[allow(clippy::module_name_repetitions)]
+#[derive(Debug, Clone)]
+pub enum BoundaryError {
+    /// The content passed to `error_boundary` is an error.
+    View(String),
+    /// DOM operations failed while rendering the content.
+    Dom(Vec<DomError>),
+}
+
+impl fmt::Display for BoundaryError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::View(error) => write!(f, "{}", error),
+            Self::Dom(errors) => {
+                for (index, error) in errors.iter().enumerate() {
+                    if index > 0 {
+                        writeln!(f)?;
+                    }
+                    write!(f, "{}", error)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+// ------ Boundary ------
+
+type Fallback<Ms> = Rc<dyn Fn(&BoundaryError) -> Node<Ms>>;
+
+/// Fallback of an error boundary - see `error_boundary`.
+pub struct Boundary<Ms> {
+    fallback: Fallback<Ms>,
+}
+
+// @TODO remove custom impl once https://github.com/rust-lang/rust/issues/26925 is fixed
+impl<Ms> Clone for Boundary<Ms> {
+    fn clone(&self) -> Self {
+        Self {
+            fallback: Rc::clone(&self.fallback),
+        }
+    }
+}
+
+impl<Ms> fmt::Debug for Boundary<Ms> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Boundary")
+    }
+}
+
+impl<Ms: 'static, OtherMs: 'static> MessageMapper<Ms, OtherMs> for Boundary<Ms> {
+    type SelfWithOtherMs = Boundary<OtherMs>;
+    fn map_msg(self, f: impl FnOnce(Ms) -> OtherMs + 'static + Clone) -> Boundary<OtherMs> {
+        let fallback = self.fallback;
+        Boundary {
+            fallback: Rc::new(move |error| fallback(error).map_msg(f.clone())),
+        }
+    }
+}
+
+/// Replace content of the innermost boundaries around the nodes where `errors` occurred
+/// with their fallbacks. Returns errors outside of all boundaries.
+///
+/// Hooks of the replaced content and of the fallbacks are queued into `hooks`.
+pub(crate) fn catch_dom_errors<Ms>(
+    document: &Document,
+    mailbox: &Mailbox<Ms>,
+    nodes: &mut [Node<Ms>],
+    mut errors: Vec<DomError>,
+    hooks: &mut Vec<HookCall<Ms>>,
+) -> Vec<DomError> {
+    for node in nodes {
+        if errors.is_empty() {
+            break;
+        }
+        catch_in_node(document, mailbox, node, &mut errors, hooks);
+    }
+    errors
+}
+
+fn catch_in_node<Ms>(
+    document: &Document,
+    mailbox: &Mailbox<Ms>,
+    node: &mut Node<Ms>,
+    errors: &mut Vec<DomError>,
+    hooks: &mut Vec<HookCall<Ms>>,
+) {
+    let el = match node {
+        Node::Element(el) => el,
+        Node::Lazy(Lazy {
+            node: Some(node), ..
+        }) => return catch_in_node(document, mailbox, node, errors, hooks),
+        _ => return,
+    };
+    let el_ws = match &el.node_ws {
+        Some(el_ws) => el_ws.clone(),
+        None => return,
+    };
+
+    let (inside, outside): (Vec<_>, Vec<_>) = errors
+        .drain(..)
+        .partition(|error| el_ws.contains(error.node.as_ref()));
+    *errors = outside;
+
+    let inside = catch_dom_errors(document, mailbox, &mut el.children, inside, hooks);
+    if inside.is_empty() {
+        return;
+    }
+    let boundary = match &el.boundary {
+        Some(boundary) => boundary.clone(),
+        None => return errors.extend(inside),
+    };
+
+    for child in el.children.drain(..) {
+        lifecycle::queue_subtree_hooks(&child, Lifecycle::Remove, hooks);
+        virtual_dom_bridge::remove_portals(&child);
+        if let Some(child_ws) = child.node_ws() {
+            if child_ws.parent_node().as_ref() == Some(&el_ws) {
+                virtual_dom_bridge::remove_node(child_ws, &el_ws);
+            }
+        }
+    }
+    let mut fallback = (boundary.fallback)(&BoundaryError::Dom(inside));
+    virtual_dom_bridge::assign_ws_nodes(document, &mut fallback);
+    match fallback.unlazy_mut() {
+        Node::Element(fallback_el) => {
+            virtual_dom_bridge::attach_el_and_children(fallback_el, &el_ws, mailbox);
+        }
+        Node::Text(fallback_text) => virtual_dom_bridge::attach_text_node(fallback_text, &el_ws),
+        Node::Portal(fallback_portal) => {
+            virtual_dom_bridge::attach_portal(fallback_portal, mailbox)
+        }
+        Node::Empty | Node::NoChange | Node::Lazy(_) => (),
+    }
+    lifecycle::queue_subtree_hooks(&fallback, Lifecycle::Insert, hooks);
+    el.children.push(fallback);
+}
@@ -1,6 +1,6 @@
 use super::super::{
-    At, AtValue, Attrs, CSSValue, EventHandler, EventHandlerManager, Node, SharedNodeWs, St, Style,
-    Tag, Text,
+    At, AtValue, Attrs, Boundary, CSSValue, EventHandler, EventHandlerManager, LifecycleHook, Node,
+    Props, SharedNodeWs, St, Style, Tag, Text, ToHtml, Transition,
 };
 use crate::app::MessageMapper;
 use crate::browser::{
@@ -41,13 +41,21 @@ pub struct El<Ms> {
     pub tag: Tag,
     pub attrs: Attrs,
     pub style: Style,
+    /// JS properties - see `props!`.
+    pub props: Props,
     pub event_handler_manager: EventHandlerManager<Ms>,
     pub children: Vec<Node<Ms>>,
     pub namespace: Option<Namespace>,
     /// The actual DOM element/node.
     pub node_ws: Option<web_sys::Node>,
     pub refs: Vec<SharedNodeWs>,
+    /// See `on_insert`, `on_update` and `on_remove`.
+    pub hooks: Vec<LifecycleHook<Ms>>,
     pub key: Option<ElKey>,
+    /// Fallback rendered when the subtree fails - see `error_boundary`.
+    pub boundary: Option<Boundary<Ms>>,
+    /// Animation of inserted, removed and moved children - see `transition`.
+    pub transition: Option<Transition>,
 }
 
 // @TODO remove custom impl once https://github.com/rust-lang/rust/issues/26925 is fixed
@@ -57,53 +65,23 @@ impl<Ms> Clone for El<Ms> {
             tag: self.tag.clone(),
             attrs: self.attrs.clone(),
             style: self.style.clone(),
+            props: self.props.clone(),
             event_handler_manager: self.event_handler_manager.clone(),
             children: self.children.clone(),
             namespace: self.namespace.clone(),
             node_ws: self.node_ws.clone(),
             refs: self.refs.clone(),
+            hooks: self.hooks.clone(),
             key: self.key.clone(),
+            boundary: self.boundary.clone(),
+            transition: self.transition.clone(),
         }
     }
 }
 
 impl<Ms> fmt::Display for El<Ms> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        let tag = self.tag.to_string();
-        let mut output = format!("<{}", &tag);
-
-        let mut attrs = self.attrs.clone();
-
-        let style = self.style.to_string();
-        if !style.is_empty() {
-            attrs.add(At::Style, style);
-        }
-
-        if let Some(namespace) = self.namespace.as_ref() {
-            attrs.add(At::Xmlns, namespace.as_str());
-        }
-
-        let attributes = attrs.to_string();
-        if !attributes.is_empty() {
-            output += &format!(" {}", attributes);
-        }
-
-        output += ">";
-
-        for child in &self.children {
-            output += &child.to_string();
-        }
-
-        // https://developer.mozilla.org/en-US/docs/Glossary/empty_element
-        let empty_elements = [
-            "area", "base", "br", "col", "embed", "hr", "img", "input", "link", "meta", "param",
-            "source", "track", "wbr",
-        ];
-        if !empty_elements.contains(&tag.to_lowercase().as_str()) {
-            output += &format!("</{}>", self.tag);
-        }
-
-        write!(f, "{}", output)
+        self.write_html(f)
     }
 }
 
@@ -122,6 +100,7 @@ impl<Ms: 'static, OtherMs: 'static> MessageMapper<Ms, OtherMs> for El<Ms> {
             tag: self.tag,
             attrs: self.attrs,
             style: self.style,
+            props: self.props,
             children: self
                 .children
                 .into_iter()
@@ -129,9 +108,16 @@ impl<Ms: 'static, OtherMs: 'static> MessageMapper<Ms, OtherMs> for El<Ms> {
                 .collect(),
             node_ws: self.node_ws,
             namespace: self.namespace,
-            event_handler_manager: self.event_handler_manager.map_msg(f),
+            event_handler_manager: self.event_handler_manager.map_msg(f.clone()),
             refs: self.refs,
+            hooks: self
+                .hooks
+                .into_iter()
+                .map(|hook| hook.map_msg(f.clone()))
+                .collect(),
             key: self.key,
+            boundary: self.boundary.map(|boundary| boundary.map_msg(f)),
+            transition: self.transition,
         }
     }
 }
@@ -150,12 +136,16 @@ impl<Ms> El<Ms> {
             tag,
             attrs: Attrs::empty(),
             style: Style::empty(),
+            props: Props::empty(),
             event_handler_manager: EventHandlerManager::new(),
             children: Vec::new(),
             namespace: None,
             node_ws: None,
             refs: Vec::new(),
+            hooks: Vec::new(),
             key: None,
+            boundary: None,
+            transition: None,
         }
     }
 
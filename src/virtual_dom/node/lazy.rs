@@ -0,0 +1,165 @@
+use super::{el_key, ElKey, Node};
+use crate::app::MessageMapper;
+use std::any::{Any, TypeId};
+use std::borrow::Cow;
+use std::collections::hash_map::DefaultHasher;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::rc::Rc;
+
+/// Render `view(&inputs)` only when `inputs` have changed since the previous render.
+///
+/// When the previous render contains a lazy node with the same `key`, the same `view` function
+/// and equal `inputs`, `view` isn't called at all and the previous subtree (incl. DOM nodes
+/// and listeners) is reused without diffing.
+///
+/// `view` should depend only on `inputs` - values captured by a closure aren't compared.
+/// Also the message mapping applied to the lazy node (`map_msg`) is reused with the subtree.
+///
+/// # Example
+///
+/// ```rust,no_run
+///fn view(model: &Model) -> Node<Msg> {
+///    div![
+///        view_header(&model.user),
+///        lazy(&"table", model.rows.clone(), view_table),
+///    ]
+///}
+///
+///fn view_table(rows: &Vec<Row>) -> Node<Msg> {
+///    table![rows.iter().map(view_row)]
+///}
+/// ```
+pub fn lazy<Ms, T, F>(key: &impl ToString, inputs: T, view: F) -> Node<Ms>
+where
+    Ms: 'static,
+    T: PartialEq + 'static,
+    F: Fn(&T) -> Node<Ms> + 'static,
+{
+    let inputs = Rc::new(inputs);
+    Node::Lazy(Lazy {
+        key: el_key(key),
+        view_id: TypeId::of::<F>(),
+        fingerprint: Rc::clone(&inputs) as Rc<dyn Any>,
+        eq: eq_fingerprints::<T>,
+        view: Rc::new(move || view(&inputs)),
+        node: None,
+    })
+}
+
+/// The same as `lazy`, but `inputs` are compared by their hashes.
+///
+/// It's useful for inputs without `PartialEq` or when comparing them would be expensive.
+pub fn lazy_hashed<Ms, T, F>(key: &impl ToString, inputs: T, view: F) -> Node<Ms>
+where
+    Ms: 'static,
+    T: Hash + 'static,
+    F: Fn(&T) -> Node<Ms> + 'static,
+{
+    let mut hasher = DefaultHasher::new();
+    inputs.hash(&mut hasher);
+    Node::Lazy(Lazy {
+        key: el_key(key),
+        view_id: TypeId::of::<F>(),
+        fingerprint: Rc::new(hasher.finish()),
+        eq: eq_fingerprints::<u64>,
+        view: Rc::new(move || view(&inputs)),
+        node: None,
+    })
+}
+
+fn eq_fingerprints<T: PartialEq + 'static>(old: &dyn Any, new: &dyn Any) -> bool {
+    match (old.downcast_ref::<T>(), new.downcast_ref::<T>()) {
+        (Some(old), Some(new)) => old == new,
+        _ => false,
+    }
+}
+
+// ------ Lazy ------
+
+/// A memoized subtree - see `lazy` and `lazy_hashed`.
+pub struct Lazy<Ms> {
+    pub(crate) key: ElKey,
+    /// The type of the view function.
+    view_id: TypeId,
+    /// Inputs or their hash.
+    fingerprint: Rc<dyn Any>,
+    eq: fn(&dyn Any, &dyn Any) -> bool,
+    view: Rc<dyn Fn() -> Node<Ms>>,
+    /// The rendered subtree. It's `None` until the node is patched or its content is needed.
+    pub(crate) node: Option<Box<Node<Ms>>>,
+}
+
+// @TODO remove custom impl once https://github.com/rust-lang/rust/issues/26925 is fixed
+impl<Ms> Clone for Lazy<Ms> {
+    fn clone(&self) -> Self {
+        Self {
+            key: self.key.clone(),
+            view_id: self.view_id,
+            fingerprint: Rc::clone(&self.fingerprint),
+            eq: self.eq,
+            view: Rc::clone(&self.view),
+            node: self.node.clone(),
+        }
+    }
+}
+
+impl<Ms: fmt::Debug> fmt::Debug for Lazy<Ms> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Lazy")
+            .field("key", &self.key)
+            .field("node", &self.node)
+            .finish()
+    }
+}
+
+impl<Ms> Lazy<Ms> {
+    pub const fn key(&self) -> &ElKey {
+        &self.key
+    }
+
+    /// The rendered subtree or a temporary one if the node hasn't been rendered yet.
+    pub fn rendered(&self) -> Cow<'_, Node<Ms>> {
+        match &self.node {
+            Some(node) => Cow::Borrowed(node),
+            None => Cow::Owned((self.view)()),
+        }
+    }
+
+    /// Call `view` if the node hasn't been rendered yet.
+    pub(crate) fn render(&mut self) -> &mut Node<Ms> {
+        let view = &self.view;
+        self.node.get_or_insert_with(|| Box::new(view()))
+    }
+
+    pub(crate) fn into_node(self) -> Node<Ms> {
+        match self.node {
+            Some(node) => *node,
+            None => (self.view)(),
+        }
+    }
+
+    /// `new` can take over the rendered subtree of `self`.
+    pub(crate) fn is_reusable_by(&self, new: &Self) -> bool {
+        self.node.is_some()
+            && self.key == new.key
+            && self.view_id == new.view_id
+            && (new.eq)(self.fingerprint.as_ref(), new.fingerprint.as_ref())
+    }
+}
+
+impl<Ms: 'static, OtherMs: 'static> MessageMapper<Ms, OtherMs> for Lazy<Ms> {
+    type SelfWithOtherMs = Lazy<OtherMs>;
+    fn map_msg(self, f: impl FnOnce(Ms) -> OtherMs + 'static + Clone) -> Lazy<OtherMs> {
+        let view = self.view;
+        let view_f = f.clone();
+        Lazy {
+            key: self.key,
+            view_id: self.view_id,
+            fingerprint: self.fingerprint,
+            eq: self.eq,
+            view: Rc::new(move || view().map_msg(view_f.clone())),
+            node: self.node.map(|node| Box::new(node.map_msg(f))),
+        }
+    }
+}
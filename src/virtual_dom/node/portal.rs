@@ -0,0 +1,105 @@
+use super::{IntoNodes, Node};
+use crate::app::{GetElement, MessageMapper};
+use crate::browser::util::document;
+use std::fmt;
+
+/// Render `children` into the `target` element instead of the parent element.
+///
+/// It's useful for modals, toasts and tooltips that have to escape `overflow: hidden`
+/// or `z-index` of their ancestors. Children are patched and removed together with
+/// the portal and their events are handled by the app as usual.
+///
+/// # Example
+///
+/// ```rust,no_run
+///fn view(model: &Model) -> Node<Msg> {
+///    div![
+///        C!["card"],
+///        IF!(model.modal_open => portal("modals", view_modal())),
+///    ]
+///}
+/// ```
+pub fn portal<Ms>(target: impl GetElement, children: impl IntoNodes<Ms>) -> Node<Ms> {
+    Node::Portal(Portal {
+        target: PortalTarget::Element(target.get_element()),
+        children: children.into_nodes(),
+        target_ws: None,
+    })
+}
+
+/// The same as `portal`, but children are rendered into `document.body`.
+pub fn portal_to_body<Ms>(children: impl IntoNodes<Ms>) -> Node<Ms> {
+    Node::Portal(Portal {
+        target: PortalTarget::Body,
+        children: children.into_nodes(),
+        target_ws: None,
+    })
+}
+
+// ------ PortalTarget ------
+
+#[derive(Clone, Debug)]
+enum PortalTarget {
+    /// `document.body` - it's resolved when the portal is rendered.
+    Body,
+    Element(Result<web_sys::Element, String>),
+}
+
+// ------ Portal ------
+
+/// A subtree rendered outside of its parent - see `portal` and `portal_to_body`.
+pub struct Portal<Ms> {
+    target: PortalTarget,
+    pub children: Vec<Node<Ms>>,
+    /// The element the children are attached to. It's `None` until the portal is rendered.
+    pub(crate) target_ws: Option<web_sys::Node>,
+}
+
+// @TODO remove custom impl once https://github.com/rust-lang/rust/issues/26925 is fixed
+impl<Ms> Clone for Portal<Ms> {
+    fn clone(&self) -> Self {
+        Self {
+            target: self.target.clone(),
+            children: self.children.clone(),
+            target_ws: self.target_ws.clone(),
+        }
+    }
+}
+
+impl<Ms: fmt::Debug> fmt::Debug for Portal<Ms> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Portal")
+            .field("target", &self.target)
+            .field("children", &self.children)
+            .finish()
+    }
+}
+
+impl<Ms> Portal<Ms> {
+    /// Returns the target element.
+    ///
+    /// # Errors
+    ///
+    /// Returns error if the target element cannot be found.
+    pub(crate) fn find_target(&self) -> Result<web_sys::Node, String> {
+        match &self.target {
+            PortalTarget::Body => document()
+                .body()
+                .map(Into::into)
+                .ok_or_else(|| "cannot find portal target `document.body`".to_owned()),
+            PortalTarget::Element(Ok(element)) => Ok(element.clone().into()),
+            PortalTarget::Element(Err(error)) => Err(format!("portal target: {}", error)),
+        }
+    }
+}
+
+impl<Ms: 'static, OtherMs: 'static> MessageMapper<Ms, OtherMs> for Portal<Ms> {
+    type SelfWithOtherMs = Portal<OtherMs>;
+    fn map_msg(self, f: impl FnOnce(Ms) -> OtherMs + 'static + Clone) -> Portal<OtherMs> {
+        Portal {
+            target: self.target,
+            children: self.children.map_msg(f),
+            target_ws: self.target_ws,
+        }
+    }
+}
@@ -1,3 +1,4 @@
+use super::super::ToHtml;
 use std::borrow::Cow;
 use std::fmt;
 
@@ -16,9 +17,10 @@ impl PartialEq for Text {
     }
 }
 
+/// Create an HTML-compatible string representation - the text is escaped.
 impl fmt::Display for Text {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "{}", self.text)
+        self.write_html(f)
     }
 }
 
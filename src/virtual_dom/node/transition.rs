@@ -0,0 +1,438 @@
+use super::{El, IntoNodes, Node};
+use crate::app::{clock, streams};
+use crate::browser::dom::{dom_error, DomError};
+use crate::browser::util::{body, window};
+use crate::virtual_dom::{Ev, St, Tag};
+use futures::future::{self, FutureExt};
+use futures::stream::StreamExt;
+use std::borrow::Cow;
+use wasm_bindgen::{JsCast, JsValue};
+use wasm_bindgen_futures::spawn_local;
+
+/// Animate children when they're inserted, removed or moved - similar to Vue's `<transition>`.
+///
+/// The transition is rendered as a `seed-transition` element with `display: contents`.
+/// Its element children get CSS classes in stages:
+///
+/// - Inserted: `{name}-enter-from` and `{name}-enter-active`, then `{name}-enter-to`
+///   on the next frame. The classes are removed when the transition ends.
+/// - Removed: `{name}-leave-from` and `{name}-leave-active`, then `{name}-leave-to`.
+///   The element stays in the DOM until the transition ends and its `on_remove` hooks
+///   are called after that.
+/// - Moved (see `Transition::moves`): `{name}-move` while the element slides to its new position.
+///
+/// A transition ends with `transitionend` or `animationend`, or after the duration
+/// read from the computed style. Elements without CSS transitions aren't delayed.
+///
+/// Only direct children are animated and the `transition` node itself has to stay rendered.
+/// Initially rendered children aren't animated.
+///
+/// # Example
+///
+/// ```rust,no_run
+///transition(
+///    Transition::new("list").moves(),
+///    model.todos.iter().map(|todo| li![el_key(&todo.id), &todo.title]),
+///)
+///
+///// .list-enter-active, .list-leave-active, .list-move { transition: all 0.3s; }
+///// .list-enter-from, .list-leave-to { opacity: 0; transform: translateX(30px); }
+/// ```
+pub fn transition<Ms>(transition: impl Into<Transition>, children: impl IntoNodes<Ms>) -> Node<Ms> {
+    let mut el = El::empty(Tag::Custom("seed-transition".into()));
+    el.style.add(St::Display, "contents");
+    el.children = children.into_nodes();
+    el.transition = Some(transition.into());
+    Node::Element(el)
+}
+
+// ------ Transition ------
+
+/// CSS classes and timing of a transition - see `transition`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Transition {
+    name: Cow<'static, str>,
+    duration_ms: Option<u32>,
+    moves: bool,
+}
+
+impl Transition {
+    /// `name` is the prefix of the CSS classes - e.g. `fade` for `fade-enter-active`.
+    pub fn new(name: impl Into<Cow<'static, str>>) -> Self {
+        Self {
+            name: name.into(),
+            duration_ms: None,
+            moves: false,
+        }
+    }
+
+    /// Wait `ms` milliseconds instead of waiting for `transitionend` or `animationend`.
+    pub const fn duration_ms(mut self, ms: u32) -> Self {
+        self.duration_ms = Some(ms);
+        self
+    }
+
+    /// Animate children moved by a patch (e.g. a reordered keyed list) by the FLIP technique.
+    ///
+    /// Positions of children are measured before and after every patch of the transition,
+    /// so enable it only for lists.
+    pub const fn moves(mut self) -> Self {
+        self.moves = true;
+        self
+    }
+
+    fn class(&self, stage: &str) -> String {
+        format!("{}-{}", self.name, stage)
+    }
+
+    /// Start the enter transition of the inserted `el`.
+    pub(crate) fn enter<Ms>(&self, el: &El<Ms>) {
+        if let Some(element) = el.node_ws.as_ref().and_then(JsCast::dyn_ref) {
+            self.start(web_sys::Element::clone(element), Phase::Enter, || ());
+        }
+    }
+
+    /// Start the leave transition of the removed `node` - it's removed from the DOM when
+    /// the transition ends and then `on_removed` is called.
+    pub(crate) fn leave(&self, node: web_sys::Node, on_removed: impl FnOnce() + 'static) {
+        match node.dyn_into::<web_sys::Element>() {
+            Ok(element) => {
+                let leaving = element.clone();
+                self.start(element, Phase::Leave, move || {
+                    leaving.remove();
+                    on_removed();
+                });
+            }
+            Err(node) => {
+                if let Some(parent) = node.parent_node() {
+                    if let Err(error) = parent.remove_child(&node) {
+                        dom_error::report(DomError::new("remove child", error, Some(&node)));
+                    }
+                }
+                on_removed();
+            }
+        }
+    }
+
+    /// Apply the classes of the `phase` in stages and call `on_end` when the transition ends.
+    fn start(&self, element: web_sys::Element, phase: Phase, on_end: impl FnOnce() + 'static) {
+        let from = self.class(&format!("{}-from", phase.as_str()));
+        let active = self.class(&format!("{}-active", phase.as_str()));
+        let to = self.class(&format!("{}-to", phase.as_str()));
+        let duration_ms = self.duration_ms;
+
+        // The first stage is applied before the browser renders the patched DOM.
+        set_classes(&element, &[&from, &active], &[]);
+        spawn_local(async move {
+            next_frame().await;
+            set_classes(&element, &[&to], &[&from]);
+            wait_for_end(&element, duration_ms).await;
+            set_classes(&element, &[], &[&active, &to]);
+            on_end();
+        });
+    }
+
+    /// Positions of `parent`'s element children before the patch - see `Transition::moves`.
+    /// Leaving children are skipped. Returns `None` if moves aren't animated.
+    pub(crate) fn child_positions(
+        &self,
+        parent: &web_sys::Node,
+    ) -> Option<Vec<(web_sys::HtmlElement, web_sys::DomRect)>> {
+        if !self.moves {
+            return None;
+        }
+        let leave_active = self.class("leave-active");
+        let children = parent.child_nodes();
+        (0..children.length())
+            .filter_map(|index| {
+                children
+                    .item(index)?
+                    .dyn_into::<web_sys::HtmlElement>()
+                    .ok()
+            })
+            .filter(|element| !element.class_list().contains(&leave_active))
+            .map(|element| {
+                let rect = element.get_bounding_client_rect();
+                (element, rect)
+            })
+            .collect::<Vec<_>>()
+            .into()
+    }
+
+    /// Animate children of `parent` from their `positions` before the patch to the current ones.
+    ///
+    /// Inline `transform` and `transition-duration` of moved children are restored
+    /// when their transition starts.
+    pub(crate) fn move_children(
+        &self,
+        parent: &web_sys::Node,
+        positions: Vec<(web_sys::HtmlElement, web_sys::DomRect)>,
+    ) {
+        // Invert - render moved children at their old positions.
+        let moved = positions
+            .into_iter()
+            .filter(|(element, _)| element.parent_node().as_ref() == Some(parent))
+            .filter_map(|(element, old)| {
+                let new = element.get_bounding_client_rect();
+                let (dx, dy) = (old.left() - new.left(), old.top() - new.top());
+                if dx.abs() < f64::EPSILON && dy.abs() < f64::EPSILON {
+                    return None;
+                }
+                let inline_styles = [
+                    InlineStyle::read(&element, "transform"),
+                    InlineStyle::read(&element, "transition-duration"),
+                ];
+                set_style(
+                    &element,
+                    "transform",
+                    &format!("translate({}px, {}px)", dx, dy),
+                );
+                set_style(&element, "transition-duration", "0s");
+                Some((element, inline_styles))
+            })
+            .collect::<Vec<_>>();
+        if moved.is_empty() {
+            return;
+        }
+        // Reading the layout applies the old positions before the transition starts.
+        body().offset_height();
+
+        // Play - let children transition to their new positions.
+        let move_class = self.class("move");
+        for (element, inline_styles) in moved {
+            set_classes(&element, &[&move_class], &[]);
+            for inline_style in &inline_styles {
+                inline_style.restore(&element);
+            }
+            let move_class = move_class.clone();
+            let duration_ms = self.duration_ms;
+            spawn_local(async move {
+                wait_for_end(&element, duration_ms).await;
+                set_classes(&element, &[], &[&move_class]);
+            });
+        }
+    }
+}
+
+impl From<&'static str> for Transition {
+    fn from(name: &'static str) -> Self {
+        Self::new(name)
+    }
+}
+
+impl From<String> for Transition {
+    fn from(name: String) -> Self {
+        Self::new(name)
+    }
+}
+
+// ------ Phase ------
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum Phase {
+    Enter,
+    Leave,
+}
+
+impl Phase {
+    const fn as_str(self) -> &'static str {
+        match self {
+            Self::Enter => "enter",
+            Self::Leave => "leave",
+        }
+    }
+}
+
+// ------ InlineStyle ------
+
+/// The inline style property of the element overridden during the move transition.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct InlineStyle {
+    property: &'static str,
+    value: String,
+    priority: String,
+}
+
+impl InlineStyle {
+    fn read(element: &web_sys::HtmlElement, property: &'static str) -> Self {
+        let style = element.style();
+        Self {
+            property,
+            value: style.get_property_value(property).unwrap_or_default(),
+            priority: style.get_property_priority(property),
+        }
+    }
+
+    fn restore(&self, element: &web_sys::HtmlElement) {
+        if let Err(error) =
+            element
+                .style()
+                .set_property_with_priority(self.property, &self.value, &self.priority)
+        {
+            dom_error::report(DomError::new(
+                format!("set style `{}`", self.property),
+                error,
+                Some(element.as_ref()),
+            ));
+        }
+    }
+}
+
+// ------ CssTiming ------
+
+/// Transition or animation of the element read from its computed style.
+#[derive(Debug, Clone, PartialEq)]
+struct CssTiming {
+    end_event: Ev,
+    /// The number of end events fired when all transitions or animations end.
+    end_count: usize,
+    timeout_ms: f64,
+}
+
+impl CssTiming {
+    /// Returns `None` if the element isn't transitioned or animated.
+    fn detect(element: &web_sys::Element) -> Option<Self> {
+        let style = window().get_computed_style(element).ok()??;
+        let times = |property: &str| parse_css_times(&style.get_property_value(property).ok()?);
+        let timing = |end_event, prefix| {
+            let durations = times(&format!("{}-duration", prefix)).unwrap_or_default();
+            let delays = times(&format!("{}-delay", prefix)).unwrap_or_default();
+            Self {
+                end_event,
+                end_count: durations.len(),
+                timeout_ms: max_timeout(&delays, &durations),
+            }
+        };
+        let transition = timing(Ev::TransitionEnd, "transition");
+        let animation = timing(Ev::AnimationEnd, "animation");
+
+        let timing = if transition.timeout_ms >= animation.timeout_ms {
+            transition
+        } else {
+            animation
+        };
+        if timing.timeout_ms > 0. {
+            Some(timing)
+        } else {
+            None
+        }
+    }
+}
+
+/// Parse a computed time list - e.g. `0.3s, 150ms`. Returns `None` for invalid values.
+fn parse_css_times(value: &str) -> Option<Vec<f64>> {
+    value
+        .split(',')
+        .map(|time| {
+            let time = time.trim();
+            if let Some(ms) = time.strip_suffix("ms") {
+                ms.parse().ok()
+            } else {
+                time.strip_suffix('s')?
+                    .parse()
+                    .ok()
+                    .map(|seconds: f64| seconds * 1000.)
+            }
+        })
+        .collect()
+}
+
+/// The longest delay + duration. Delays are repeated when there are less delays than durations.
+fn max_timeout(delays: &[f64], durations: &[f64]) -> f64 {
+    durations
+        .iter()
+        .enumerate()
+        .map(|(index, duration)| {
+            let delay = if delays.is_empty() {
+                0.
+            } else {
+                delays[index % delays.len()]
+            };
+            duration + delay
+        })
+        .fold(0., f64::max)
+}
+
+// ------ Helpers ------
+
+/// Wait for the next frame after the one being rendered.
+async fn next_frame() {
+    let mut frames = streams::animation_frame(|_| ());
+    frames.next().await;
+    frames.next().await;
+}
+
+/// Wait until the element's transitions or animations end.
+async fn wait_for_end(element: &web_sys::Element, duration_ms: Option<u32>) {
+    if let Some(duration_ms) = duration_ms {
+        return clock::sleep(duration_ms).await;
+    }
+    let timing = match CssTiming::detect(element) {
+        Some(timing) => timing,
+        None => return,
+    };
+    let target = JsValue::from(element.clone());
+    let ends = streams::EventStream::<web_sys::Event>::new(element, timing.end_event)
+        // Ends of descendants' transitions bubble up to the element.
+        .filter(move |event| future::ready(event.target().map(JsValue::from).as_ref() == Some(&target)))
+        .take(timing.end_count)
+        .for_each(|_| future::ready(()));
+    // End events aren't fired for interrupted transitions.
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    let timeout = clock::sleep(timing.timeout_ms.ceil() as u32 + 1);
+    future::select(ends.boxed_local(), timeout).await;
+}
+
+fn set_classes(element: &web_sys::Element, add: &[&str], remove: &[&str]) {
+    let class_list = element.class_list();
+    for class in remove {
+        if let Err(error) = class_list.remove_1(class) {
+            dom_error::report(DomError::new(
+                format!("remove class `{}`", class),
+                error,
+                Some(element.as_ref()),
+            ));
+        }
+    }
+    for class in add {
+        if let Err(error) = class_list.add_1(class) {
+            dom_error::report(DomError::new(
+                format!("add class `{}`", class),
+                error,
+                Some(element.as_ref()),
+            ));
+        }
+    }
+}
+
+fn set_style(element: &web_sys::HtmlElement, property: &str, value: &str) {
+    if let Err(error) = element.style().set_property(property, value) {
+        dom_error::report(DomError::new(
+            format!("set style `{}`", property),
+            error,
+            Some(element.as_ref()),
+        ));
+    }
+}
+
+// ------ ------ Tests ------ ------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wasm_bindgen_test::*;
+
+    #[wasm_bindgen_test]
+    fn css_times_are_parsed() {
+        assert_eq!(parse_css_times("0s"), Some(vec![0.]));
+        assert_eq!(parse_css_times("0.3s, 150ms"), Some(vec![300., 150.]));
+        assert_eq!(parse_css_times("auto"), None);
+    }
+
+    #[wasm_bindgen_test]
+    fn max_timeout_repeats_delays() {
+        assert_eq!(max_timeout(&[], &[]), 0.);
+        assert_eq!(max_timeout(&[100.], &[300., 500.]), 600.);
+        assert_eq!(max_timeout(&[0., 400.], &[300., 200., 100.]), 600.);
+    }
+}
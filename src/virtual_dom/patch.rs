@@ -1,13 +1,19 @@
 //! This module contains code related to patching the VDOM. It can be considered
 //! a subset of the `vdom` module.
 
-use super::{El, IntoNodes, Mailbox, Node, Text};
+use super::lifecycle::{self, Lifecycle};
+use super::{El, IntoNodes, Mailbox, Node, Portal, Text, Transition};
 use crate::app::App;
-use crate::browser::dom::virtual_dom_bridge;
+use crate::browser::dom::{dom_error, virtual_dom_bridge, DomError};
 use web_sys::Document;
 
+mod hydrate;
 mod patch_gen;
-use patch_gen::{PatchCommand, PatchGen};
+#[cfg(test)]
+pub(crate) use hydrate::hydrate_els;
+pub(crate) use hydrate::hydrate_mount_point;
+pub(crate) use patch_gen::PatchCommand;
+use patch_gen::PatchGen;
 
 // We assume that when we run this, the new vdom doesn't have assigned `web_sys::Node`s -
 // assign them here when we create them.
@@ -37,10 +43,10 @@ fn insert_el<'a, Ms>(
 ) {
     virtual_dom_bridge::assign_ws_nodes_to_el(document, new);
     virtual_dom_bridge::attach_children(new, mailbox);
-    let new_node = new
-        .node_ws
-        .take()
-        .expect("Missing websys el when patching Text to Element");
+    let new_node = match new.node_ws.take() {
+        Some(new_node) => new_node,
+        None => return dom_error::report(DomError::missing_node("insert element", Some(parent))),
+    };
     virtual_dom_bridge::insert_node(&new_node, parent, Some(next_node));
 
     for ref_ in &mut new.refs {
@@ -60,11 +66,10 @@ fn insert_text<'a>(
     next_node: web_sys::Node,
 ) {
     virtual_dom_bridge::assign_ws_nodes_to_text(document, new);
-    let new_node_ws = new
-        .node_ws
-        .as_ref()
-        .expect("new_node_ws missing when patching Empty to Text");
-    virtual_dom_bridge::insert_node(new_node_ws, parent, Some(next_node));
+    match new.node_ws.as_ref() {
+        Some(new_node_ws) => virtual_dom_bridge::insert_node(new_node_ws, parent, Some(next_node)),
+        None => dom_error::report(DomError::missing_node("insert text", Some(parent))),
+    }
 }
 
 fn patch_el<'a, Ms, Mdl, INodes>(
@@ -83,11 +88,10 @@ fn patch_el<'a, Ms, Mdl, INodes>(
     // Assume old el vdom's elements are still attached.
     // @TODO: "Split" `Node` into 2 structs - one without native nodes and one with them (?).
 
-    let old_el_ws = old
-        .node_ws
-        .as_ref()
-        .expect("missing old el_ws when patching non-empty el")
-        .clone();
+    let old_el_ws = match old.node_ws.clone() {
+        Some(old_el_ws) => old_el_ws,
+        None => return dom_error::report(DomError::missing_node("patch element", None)),
+    };
     virtual_dom_bridge::patch_el_details(&mut old, new, &old_el_ws, mailbox);
 
     for ref_ in &mut new.refs {
@@ -102,17 +106,57 @@ fn patch_el<'a, Ms, Mdl, INodes>(
         mailbox,
         app,
         &old_el_ws,
+        new.transition.as_ref(),
         old_children_iter,
         new_children_iter,
     );
+    virtual_dom_bridge::patch_props(&old.props, &new.props, &old_el_ws);
     new.node_ws = Some(old_el_ws);
 }
 
+fn append_portal<Ms>(document: &Document, new: &mut Portal<Ms>, mailbox: &Mailbox<Ms>) {
+    for child in &mut new.children {
+        virtual_dom_bridge::assign_ws_nodes(document, child);
+    }
+    virtual_dom_bridge::attach_portal(new, mailbox);
+}
+
+fn patch_portal<Ms, Mdl, INodes>(
+    document: &Document,
+    old: Portal<Ms>,
+    new: &mut Portal<Ms>,
+    mailbox: &Mailbox<Ms>,
+    app: &App<Ms, Mdl, INodes>,
+) where
+    INodes: IntoNodes<Ms>,
+{
+    let target = match (&old.target_ws, new.find_target()) {
+        (Some(old_target), Ok(new_target)) if old_target == &new_target => new_target,
+        // Move the content to the new target.
+        _ => {
+            queue_portal_hooks(app, &old, Lifecycle::Remove);
+            virtual_dom_bridge::detach_portal(&old);
+            append_portal(document, new, mailbox);
+            return queue_portal_hooks(app, new, Lifecycle::Insert);
+        }
+    };
+    patch_els(
+        document,
+        mailbox,
+        app,
+        &target,
+        None,
+        old.children.into_iter(),
+        new.children.iter_mut(),
+    );
+    new.target_ws = Some(target);
+}
+
 fn patch_text(mut old: Text, new: &mut Text) {
-    let old_node_ws = old
-        .node_ws
-        .take()
-        .expect("old_node_ws missing when changing text");
+    let old_node_ws = match old.node_ws.take() {
+        Some(old_node_ws) => old_node_ws,
+        None => return dom_error::report(DomError::missing_node("patch text", None)),
+    };
 
     if new != &old {
         old_node_ws.set_text_content(Some(&new.text));
@@ -131,14 +175,12 @@ fn replace_by_el<'a, Ms>(
     for ref_ in &mut new.refs {
         ref_.set(new_node.clone());
     }
-    new.node_ws = Some(new_node);
+    new.node_ws = Some(new_node.clone());
     for child in &mut new.children {
         virtual_dom_bridge::assign_ws_nodes(document, child);
     }
     virtual_dom_bridge::attach_el_and_children(new, parent, mailbox);
-
-    let new_ws = new.node_ws.as_ref().expect("Missing websys el");
-    virtual_dom_bridge::replace_child(new_ws, old_node, parent);
+    virtual_dom_bridge::replace_child(&new_node, old_node, parent);
 }
 
 fn replace_by_text<'a>(
@@ -148,12 +190,10 @@ fn replace_by_text<'a>(
     parent: &web_sys::Node,
 ) {
     virtual_dom_bridge::assign_ws_nodes_to_text(document, new);
-    let new_node_ws = new
-        .node_ws
-        .as_ref()
-        .expect("old el_ws missing when replacing with text node");
-
-    virtual_dom_bridge::replace_child(new_node_ws, old_node, parent);
+    match new.node_ws.as_ref() {
+        Some(new_node_ws) => virtual_dom_bridge::replace_child(new_node_ws, old_node, parent),
+        None => dom_error::report(DomError::missing_node("replace by text", Some(parent))),
+    }
 }
 
 fn replace_el_by_el<'a, Ms>(
@@ -163,10 +203,16 @@ fn replace_el_by_el<'a, Ms>(
     parent: &web_sys::Node,
     mailbox: &Mailbox<Ms>,
 ) {
-    let old_node = old
-        .node_ws
-        .take()
-        .expect("old el_ws missing when replacing element with new element");
+    let old_node = match old.node_ws.take() {
+        Some(old_node) => old_node,
+        None => {
+            dom_error::report(DomError::missing_node("replace element", Some(parent)));
+            return append_el(document, new, parent, mailbox);
+        }
+    };
+    old.children
+        .iter()
+        .for_each(virtual_dom_bridge::remove_portals);
     replace_by_el(document, &old_node, new, parent, mailbox);
 }
 
@@ -176,10 +222,16 @@ fn replace_el_by_text<'a, Ms>(
     new: &'a mut Text,
     parent: &web_sys::Node,
 ) {
-    let old_node = old
-        .node_ws
-        .take()
-        .expect("old el_ws missing when replacing element with text node");
+    let old_node = match old.node_ws.take() {
+        Some(old_node) => old_node,
+        None => {
+            dom_error::report(DomError::missing_node("replace element", Some(parent)));
+            return append_text(document, new, parent);
+        }
+    };
+    old.children
+        .iter()
+        .for_each(virtual_dom_bridge::remove_portals);
     replace_by_text(document, &old_node, new, parent);
 }
 
@@ -190,23 +242,74 @@ fn replace_text_by_el<'a, Ms>(
     parent: &web_sys::Node,
     mailbox: &Mailbox<Ms>,
 ) {
-    let old_node = old
-        .node_ws
-        .take()
-        .expect("old el_ws missing when replacing text node with element");
+    let old_node = match old.node_ws.take() {
+        Some(old_node) => old_node,
+        None => {
+            dom_error::report(DomError::missing_node("replace text", Some(parent)));
+            return append_el(document, new, parent, mailbox);
+        }
+    };
     replace_by_el(document, &old_node, new, parent, mailbox);
 }
 
-fn remove_el<Ms>(mut old: El<Ms>, parent: &web_sys::Node) {
-    let old_node = old.node_ws.take().expect("Missing child node_ws");
-    virtual_dom_bridge::remove_node(&old_node, parent);
-    old.node_ws.replace(old_node);
+fn remove_el<Ms>(old: El<Ms>, parent: &web_sys::Node) {
+    old.children
+        .iter()
+        .for_each(virtual_dom_bridge::remove_portals);
+    match old.node_ws.as_ref() {
+        Some(old_node) => virtual_dom_bridge::remove_node(old_node, parent),
+        None => dom_error::report(DomError::missing_node("remove element", Some(parent))),
+    }
+}
+
+/// Remove the element when its leave transition ends - see `transition`.
+/// `on_remove` hooks are called after the removal.
+fn leave_el<Ms: 'static>(mut old: El<Ms>, transition: &Transition, mailbox: &Mailbox<Ms>) {
+    let mut hooks = Vec::new();
+    lifecycle::queue_el_subtree_hooks(&old, Lifecycle::Remove, &mut hooks);
+    old.children
+        .iter()
+        .for_each(virtual_dom_bridge::remove_portals);
+    match old.node_ws.take() {
+        Some(old_node) => {
+            let mailbox = mailbox.clone();
+            transition.leave(old_node, move || {
+                for hook in hooks {
+                    mailbox.send(hook.call());
+                }
+            });
+        }
+        None => dom_error::report(DomError::missing_node("remove element", None)),
+    }
+}
+
+fn remove_text(old: Text, parent: &web_sys::Node) {
+    match old.node_ws.as_ref() {
+        Some(old_node) => virtual_dom_bridge::remove_node(old_node, parent),
+        None => dom_error::report(DomError::missing_node("remove text", Some(parent))),
+    }
 }
 
-fn remove_text(mut old: Text, parent: &web_sys::Node) {
-    let old_node = old.node_ws.take().expect("Missing child node_ws");
-    virtual_dom_bridge::remove_node(&old_node, parent);
-    old.node_ws.replace(old_node);
+/// Queue hooks of the element and its descendants - see `on_insert` and `on_remove`.
+fn queue_el_hooks<Ms, Mdl, INodes: IntoNodes<Ms>>(
+    app: &App<Ms, Mdl, INodes>,
+    el: &El<Ms>,
+    lifecycle: Lifecycle,
+) {
+    let queue = &mut app.data.lifecycle_hooks.borrow_mut();
+    lifecycle::queue_el_subtree_hooks(el, lifecycle, queue);
+}
+
+/// See `queue_el_hooks`.
+fn queue_portal_hooks<Ms, Mdl, INodes: IntoNodes<Ms>>(
+    app: &App<Ms, Mdl, INodes>,
+    portal: &Portal<Ms>,
+    lifecycle: Lifecycle,
+) {
+    let queue = &mut app.data.lifecycle_hooks.borrow_mut();
+    for child in &portal.children {
+        lifecycle::queue_subtree_hooks(child, lifecycle, queue);
+    }
 }
 
 pub(crate) fn patch_els<'a, Ms, Mdl, INodes, OI, NI>(
@@ -214,6 +317,7 @@ pub(crate) fn patch_els<'a, Ms, Mdl, INodes, OI, NI>(
     mailbox: &Mailbox<Ms>,
     app: &App<Ms, Mdl, INodes>,
     old_el_ws: &web_sys::Node,
+    transition: Option<&Transition>,
     old_children_iter: OI,
     new_children_iter: NI,
 ) where
@@ -221,34 +325,104 @@ pub(crate) fn patch_els<'a, Ms, Mdl, INodes, OI, NI>(
     OI: Iterator<Item = Node<Ms>>,
     NI: Iterator<Item = &'a mut Node<Ms>>,
 {
+    let positions = transition.and_then(|transition| transition.child_positions(old_el_ws));
+    let enter = |el: &El<Ms>| {
+        if let Some(transition) = transition {
+            transition.enter(el);
+        }
+    };
+
     for command in PatchGen::new(old_children_iter, new_children_iter) {
+        if let Some(mut stats) = app.data.render_stats.get() {
+            stats.patch_counts.count(&command);
+            app.data.render_stats.set(Some(stats));
+        }
         match command {
-            PatchCommand::AppendEl { el_new } => append_el(document, el_new, old_el_ws, mailbox),
+            PatchCommand::AppendEl { el_new } => {
+                append_el(document, el_new, old_el_ws, mailbox);
+                queue_el_hooks(app, el_new, Lifecycle::Insert);
+                enter(el_new);
+            }
             PatchCommand::AppendText { text_new } => append_text(document, text_new, old_el_ws),
             PatchCommand::InsertEl { el_new, next_node } => {
                 insert_el(document, el_new, old_el_ws, next_node, mailbox);
+                queue_el_hooks(app, el_new, Lifecycle::Insert);
+                enter(el_new);
             }
             PatchCommand::InsertText {
                 text_new,
                 next_node,
             } => insert_text(document, text_new, old_el_ws, next_node),
+            PatchCommand::MoveNode { node, next_node } => {
+                virtual_dom_bridge::insert_node(&node, old_el_ws, next_node);
+            }
+            PatchCommand::AppendPortal { portal_new } => {
+                append_portal(document, portal_new, mailbox);
+                queue_portal_hooks(app, portal_new, Lifecycle::Insert);
+            }
+            PatchCommand::PatchPortal {
+                portal_old,
+                portal_new,
+            } => patch_portal(document, portal_old, portal_new, mailbox, app),
+            PatchCommand::RemovePortal { portal_old } => {
+                queue_portal_hooks(app, &portal_old, Lifecycle::Remove);
+                virtual_dom_bridge::detach_portal(&portal_old);
+            }
             PatchCommand::PatchEl { el_old, el_new } => {
                 patch_el(document, el_old, el_new, mailbox, app);
+                lifecycle::queue_hooks(
+                    el_new,
+                    Lifecycle::Update,
+                    &mut app.data.lifecycle_hooks.borrow_mut(),
+                );
             }
             PatchCommand::PatchText { text_old, text_new } => patch_text(text_old, text_new),
             PatchCommand::ReplaceElByEl { el_old, el_new } => {
-                replace_el_by_el(document, el_old, el_new, old_el_ws, mailbox);
+                match (transition, el_old.node_ws.clone()) {
+                    // The new element is inserted before the leaving one.
+                    (Some(transition), Some(next_node)) => {
+                        insert_el(document, el_new, old_el_ws, next_node, mailbox);
+                        leave_el(el_old, transition, mailbox);
+                    }
+                    _ => {
+                        queue_el_hooks(app, &el_old, Lifecycle::Remove);
+                        replace_el_by_el(document, el_old, el_new, old_el_ws, mailbox);
+                    }
+                }
+                queue_el_hooks(app, el_new, Lifecycle::Insert);
+                enter(el_new);
             }
             PatchCommand::ReplaceTextByEl { text_old, el_new } => {
                 replace_text_by_el(document, text_old, el_new, old_el_ws, mailbox);
+                queue_el_hooks(app, el_new, Lifecycle::Insert);
+                enter(el_new);
             }
             PatchCommand::ReplaceElByText { el_old, text_new } => {
-                replace_el_by_text(document, el_old, text_new, old_el_ws);
+                match (transition, el_old.node_ws.clone()) {
+                    (Some(transition), Some(next_node)) => {
+                        insert_text(document, text_new, old_el_ws, next_node);
+                        leave_el(el_old, transition, mailbox);
+                    }
+                    _ => {
+                        queue_el_hooks(app, &el_old, Lifecycle::Remove);
+                        replace_el_by_text(document, el_old, text_new, old_el_ws);
+                    }
+                }
             }
-            PatchCommand::RemoveEl { el_old } => remove_el(el_old, old_el_ws),
+            PatchCommand::RemoveEl { el_old } => match transition {
+                Some(transition) => leave_el(el_old, transition, mailbox),
+                None => {
+                    queue_el_hooks(app, &el_old, Lifecycle::Remove);
+                    remove_el(el_old, old_el_ws);
+                }
+            },
             PatchCommand::RemoveText { text_old } => remove_text(text_old, old_el_ws),
         };
     }
+
+    if let (Some(transition), Some(positions)) = (transition, positions) {
+        transition.move_children(old_el_ws, positions);
+    }
 }
 
 /// Routes patching through different channels, depending on the Node variant of old and new.
@@ -274,7 +448,8 @@ pub(crate) fn patch<'a, Ms, Mdl, INodes: IntoNodes<Ms>>(
 
     // @TODO Do we realy need this function? This function could be replaced by calling
     // `patch_els` with `std::iter::once` for old and new nodes.
-    match old {
+    let new = new.unlazy_mut();
+    match old.into_unlazy() {
         Node::Element(old_el) => match new {
             Node::Element(new_el) => {
                 if patch_gen::el_can_be_patched(&old_el, new_el) {
@@ -288,6 +463,11 @@ pub(crate) fn patch<'a, Ms, Mdl, INodes: IntoNodes<Ms>>(
             Node::NoChange => {
                 *new = Node::Element(old_el);
             }
+            Node::Portal(new_portal) => {
+                remove_el(old_el, parent);
+                append_portal(document, new_portal, mailbox);
+            }
+            Node::Lazy(_) => unreachable!("new node is rendered"),
         },
         Node::Empty => {
             match new {
@@ -308,8 +488,10 @@ pub(crate) fn patch<'a, Ms, Mdl, INodes: IntoNodes<Ms>>(
                 // If new and old are empty, we don't need to do anything.
                 Node::Empty => (),
                 Node::NoChange => {
-                    *new = old;
+                    *new = Node::Empty;
                 }
+                Node::Portal(new_portal) => append_portal(document, new_portal, mailbox),
+                Node::Lazy(_) => unreachable!("new node is rendered"),
             }
         }
         Node::Text(old_text) => {
@@ -323,9 +505,27 @@ pub(crate) fn patch<'a, Ms, Mdl, INodes: IntoNodes<Ms>>(
                 Node::NoChange => {
                     *new = Node::Text(old_text);
                 }
+                Node::Portal(new_portal) => {
+                    remove_text(old_text, parent);
+                    append_portal(document, new_portal, mailbox);
+                }
+                Node::Lazy(_) => unreachable!("new node is rendered"),
             }
         }
+        Node::Portal(old_portal) => match new {
+            Node::Portal(new_portal) => {
+                patch_portal(document, old_portal, new_portal, mailbox, app)
+            }
+            Node::NoChange => {
+                *new = Node::Portal(old_portal);
+            }
+            new => {
+                virtual_dom_bridge::detach_portal(&old_portal);
+                return patch(document, Node::Empty, new, parent, next_node, mailbox, app);
+            }
+        },
         Node::NoChange => panic!("Node::NoChange cannot be an old VDOM node!"),
+        Node::Lazy(_) => unreachable!("old node is rendered"),
     };
     new.node_ws()
 }
@@ -0,0 +1,361 @@
+//! Hydration - the first render into a mount point with prerendered content.
+//!
+//! We walk the existing DOM alongside the first `view` output and adopt matching `web_sys::Node`s,
+//! so the prerendered page doesn't flash and it doesn't lose focus or scroll position.
+//! Only the nodes that don't match are created, replaced or removed.
+//! Mismatches are reported to the console in debug builds.
+//! DOM operations are counted in `PatchCounts` like the patch operations - an adopted element
+//! is counted as `patch_el`, an adopted text as `patch_text`.
+
+use super::{append_el, append_portal, append_text, insert_el, insert_text};
+use crate::app::PatchCounts;
+use crate::browser::dom::{virtual_dom_bridge, Namespace};
+use crate::virtual_dom::{At, AtValue, Attrs, El, Mailbox, Node, Style, Text};
+use std::convert::TryFrom;
+use wasm_bindgen::JsCast;
+use web_sys::Document;
+
+/// Hydrates the mount point. When it doesn't contain any prerendered content,
+/// `new_children` are only appended - i.e. ordinary apps don't report mismatches.
+pub(crate) fn hydrate_mount_point<Ms>(
+    document: &Document,
+    mailbox: &Mailbox<Ms>,
+    mount_point: &web_sys::Node,
+    new_children: &mut [Node<Ms>],
+    counts: &mut PatchCounts,
+) {
+    if has_prerendered_content(mount_point) {
+        return hydrate_els(document, mailbox, mount_point, new_children, counts);
+    }
+    for child in new_children {
+        match child.unlazy_mut() {
+            Node::Element(el_new) => {
+                append_el(document, el_new, mount_point, mailbox);
+                counts.append_el += 1;
+            }
+            Node::Text(text_new) => {
+                append_text(document, text_new, mount_point);
+                counts.append_text += 1;
+            }
+            Node::Portal(portal_new) => {
+                append_portal(document, portal_new, mailbox);
+                counts.append_portal += 1;
+            }
+            Node::Empty | Node::NoChange | Node::Lazy(_) => (),
+        }
+    }
+}
+
+/// Adopts `parent`'s children as `new_children` and fixes the differences.
+pub(crate) fn hydrate_els<Ms>(
+    document: &Document,
+    mailbox: &Mailbox<Ms>,
+    parent: &web_sys::Node,
+    new_children: &mut [Node<Ms>],
+    counts: &mut PatchCounts,
+) {
+    let mut cursor = parent.first_child();
+
+    // Following siblings are compared with the DOM - see `following_matches_ws`.
+    for child in new_children.iter_mut() {
+        child.unlazy_mut();
+    }
+
+    for index in 0..new_children.len() {
+        let (current, following) = new_children[index..]
+            .split_first_mut()
+            .expect("get the current child");
+
+        cursor = match current.unlazy_mut() {
+            Node::Element(el_new) => {
+                hydrate_el(document, mailbox, parent, cursor, el_new, following, counts)
+            }
+            Node::Text(text_new) => hydrate_text(document, parent, cursor, text_new, counts),
+            // Portal content isn't prerendered.
+            Node::Portal(portal_new) => {
+                append_portal(document, portal_new, mailbox);
+                counts.append_portal += 1;
+                cursor
+            }
+            Node::Empty | Node::NoChange | Node::Lazy(_) => cursor,
+        };
+    }
+
+    // Remove prerendered nodes that aren't in the VDOM.
+    while let Some(node) = cursor {
+        cursor = node.next_sibling();
+        if node.node_type() == web_sys::Node::COMMENT_NODE {
+            continue;
+        }
+        if !is_whitespace_text(&node) {
+            report_mismatch(|| format!("removing unexpected {}", describe_ws(&node)));
+        }
+        remove_node(&node, parent, counts);
+    }
+}
+
+fn remove_node(node: &web_sys::Node, parent: &web_sys::Node, counts: &mut PatchCounts) {
+    virtual_dom_bridge::remove_node(node, parent);
+    if node.node_type() == web_sys::Node::ELEMENT_NODE {
+        counts.remove_el += 1;
+    } else {
+        counts.remove_text += 1;
+    }
+}
+
+/// Returns the next node to hydrate.
+fn hydrate_el<Ms>(
+    document: &Document,
+    mailbox: &Mailbox<Ms>,
+    parent: &web_sys::Node,
+    cursor: Option<web_sys::Node>,
+    el_new: &mut El<Ms>,
+    following: &[Node<Ms>],
+    counts: &mut PatchCounts,
+) -> Option<web_sys::Node> {
+    // Formatting whitespace between prerendered elements isn't a part of the VDOM.
+    let candidate = match skip_comments_and_whitespace(parent, cursor, true) {
+        Some(candidate) => candidate,
+        None => {
+            report_mismatch(|| format!("missing element `{}`", el_new.tag));
+            append_el(document, el_new, parent, mailbox);
+            counts.append_el += 1;
+            return None;
+        }
+    };
+
+    if el_matches_ws(el_new, &candidate) {
+        let next = candidate.next_sibling();
+        adopt_el(document, mailbox, candidate, el_new, counts);
+        return next;
+    }
+
+    // The prerendered node is redundant.
+    let next_candidate = skip_comments_and_whitespace(parent, candidate.next_sibling(), true);
+    if let Some(next_candidate) = next_candidate.filter(|node| el_matches_ws(el_new, node)) {
+        report_mismatch(|| format!("removing unexpected {}", describe_ws(&candidate)));
+        remove_node(&candidate, parent, counts);
+        let next = next_candidate.next_sibling();
+        adopt_el(document, mailbox, next_candidate, el_new, counts);
+        return next;
+    }
+
+    report_mismatch(|| {
+        format!(
+            "expected element `{}`, found {}",
+            el_new.tag,
+            describe_ws(&candidate)
+        )
+    });
+    insert_el(document, el_new, parent, candidate.clone(), mailbox);
+    counts.insert_el += 1;
+
+    // The new element is missing in the prerendered content.
+    if following_matches_ws(following, &candidate) {
+        return Some(candidate);
+    }
+    // The prerendered node is outdated.
+    let next = candidate.next_sibling();
+    remove_node(&candidate, parent, counts);
+    next
+}
+
+/// Returns the next node to hydrate.
+fn hydrate_text(
+    document: &Document,
+    parent: &web_sys::Node,
+    cursor: Option<web_sys::Node>,
+    text_new: &mut Text,
+    counts: &mut PatchCounts,
+) -> Option<web_sys::Node> {
+    let candidate = match skip_comments_and_whitespace(parent, cursor, false) {
+        Some(candidate) => candidate,
+        None => {
+            if !text_new.text.is_empty() {
+                report_mismatch(|| format!("missing text {:?}", text_new.text));
+            }
+            append_text(document, text_new, parent);
+            counts.append_text += 1;
+            return None;
+        }
+    };
+
+    // Empty texts aren't present in the prerendered HTML.
+    let text_ws = match candidate.dyn_ref::<web_sys::Text>() {
+        Some(text_ws) if !text_new.text.is_empty() => text_ws.clone(),
+        _ => {
+            if !text_new.text.is_empty() {
+                report_mismatch(|| {
+                    format!(
+                        "expected text {:?}, found {}",
+                        text_new.text,
+                        describe_ws(&candidate)
+                    )
+                });
+            }
+            insert_text(document, text_new, parent, candidate.clone());
+            counts.insert_text += 1;
+            return Some(candidate);
+        }
+    };
+
+    let data = text_ws.data();
+    let next = if data == text_new.text {
+        text_ws.next_sibling()
+    } else if data.starts_with(text_new.text.as_ref()) {
+        // Adjacent VDOM texts have been merged into one DOM text node by the HTML parser.
+        let offset =
+            u32::try_from(text_new.text.encode_utf16().count()).expect("text offset fits into u32");
+        let rest = text_ws
+            .split_text(offset)
+            .expect("split prerendered text node");
+        Some(rest.into())
+    } else {
+        report_mismatch(|| format!("expected text {:?}, found {:?}", text_new.text, data));
+        text_ws.set_data(&text_new.text);
+        text_ws.next_sibling()
+    };
+    text_new.node_ws = Some(text_ws.into());
+    counts.patch_text += 1;
+    next
+}
+
+fn adopt_el<Ms>(
+    document: &Document,
+    mailbox: &Mailbox<Ms>,
+    node_ws: web_sys::Node,
+    el_new: &mut El<Ms>,
+    counts: &mut PatchCounts,
+) {
+    let element = node_ws
+        .dyn_ref::<web_sys::Element>()
+        .expect("Problem casting Node as Element while hydrating");
+
+    // Only attributes are needed to patch the element - children are hydrated below.
+    let mut el_old = El::empty(el_new.tag.clone());
+    el_old.attrs = attrs_from_ws(element);
+
+    // `El::style` is rendered into the `style` attribute.
+    // Style properties are patched one by one, so the old ones have to be known.
+    if !el_new.style.vals.is_empty() {
+        if let Some(AtValue::Some(style)) = el_old.attrs.vals.shift_remove(&At::Style) {
+            el_old.style = if style == el_new.style.to_string() {
+                el_new.style.clone()
+            } else {
+                Style::from_declarations(&style)
+            };
+        }
+    }
+
+    // Listeners are attached here because the old `EventHandlerManager` is empty.
+    virtual_dom_bridge::patch_el_details(&mut el_old, el_new, &node_ws, mailbox);
+    counts.patch_el += 1;
+
+    for ref_ in &mut el_new.refs {
+        ref_.set(node_ws.clone());
+    }
+
+    // Textarea's content is its value, set through `At::Value`.
+    let is_textarea = el_new.tag.as_str().eq_ignore_ascii_case("textarea");
+    if !(is_textarea && el_new.children.is_empty()) {
+        hydrate_els(document, mailbox, &node_ws, &mut el_new.children, counts);
+    }
+    virtual_dom_bridge::patch_props(&el_old.props, &el_new.props, &node_ws);
+
+    el_new.node_ws = Some(node_ws);
+}
+
+fn attrs_from_ws(element: &web_sys::Element) -> Attrs {
+    let mut attrs = Attrs::empty();
+    element
+        .get_attribute_names()
+        .for_each(&mut |attr_name, _, _| {
+            let attr_name = attr_name
+                .as_string()
+                .expect("problem converting attr to string");
+            if let Some(attr_val) = element.get_attribute(&attr_name) {
+                attrs.add(attr_name.into(), &attr_val);
+            }
+        });
+    attrs
+}
+
+fn el_matches_ws<Ms>(el: &El<Ms>, node: &web_sys::Node) -> bool {
+    let element = match node.dyn_ref::<web_sys::Element>() {
+        Some(element) => element,
+        None => return false,
+    };
+    match el.namespace.as_ref() {
+        None | Some(Namespace::Html) => {
+            element.namespace_uri().as_deref() == Some(Namespace::Html.as_str())
+                && element.local_name().eq_ignore_ascii_case(el.tag.as_str())
+        }
+        // Foreign tag names are case-sensitive - e.g. `linearGradient`.
+        Some(namespace) => {
+            element.namespace_uri().as_deref() == Some(namespace.as_str())
+                && element.local_name() == el.tag.as_str()
+        }
+    }
+}
+
+fn following_matches_ws<Ms>(following: &[Node<Ms>], node: &web_sys::Node) -> bool {
+    following
+        .iter()
+        .find_map(|child| match child.unlazy() {
+            Node::Element(el) => Some(el_matches_ws(el, node)),
+            Node::Text(_) => Some(node.node_type() == web_sys::Node::TEXT_NODE),
+            Node::Empty | Node::NoChange | Node::Lazy(_) | Node::Portal(_) => None,
+        })
+        .unwrap_or_default()
+}
+
+fn skip_comments_and_whitespace(
+    parent: &web_sys::Node,
+    mut cursor: Option<web_sys::Node>,
+    remove_whitespace: bool,
+) -> Option<web_sys::Node> {
+    while let Some(node) = cursor {
+        if node.node_type() == web_sys::Node::COMMENT_NODE {
+            cursor = node.next_sibling();
+        } else if remove_whitespace && is_whitespace_text(&node) {
+            cursor = node.next_sibling();
+            virtual_dom_bridge::remove_node(&node, parent);
+        } else {
+            return Some(node);
+        }
+    }
+    None
+}
+
+fn is_whitespace_text(node: &web_sys::Node) -> bool {
+    node.dyn_ref::<web_sys::Text>()
+        .is_some_and(|text| text.data().trim().is_empty())
+}
+
+/// Returns `true` if `parent` has other children than comments and whitespace.
+fn has_prerendered_content(parent: &web_sys::Node) -> bool {
+    let mut cursor = parent.first_child();
+    while let Some(node) = cursor {
+        if node.node_type() != web_sys::Node::COMMENT_NODE && !is_whitespace_text(&node) {
+            return true;
+        }
+        cursor = node.next_sibling();
+    }
+    false
+}
+
+fn describe_ws(node: &web_sys::Node) -> String {
+    if let Some(element) = node.dyn_ref::<web_sys::Element>() {
+        format!("element `{}`", element.local_name())
+    } else if let Some(text) = node.dyn_ref::<web_sys::Text>() {
+        format!("text {:?}", text.data())
+    } else {
+        node.node_name()
+    }
+}
+
+#[allow(unused_variables)]
+fn report_mismatch(message: impl FnOnce() -> String) {
+    #[cfg(debug_assertions)]
+    crate::error(format!("Hydration mismatch: {}", message()));
+}
@@ -11,6 +11,8 @@
 //! - If the new node is empty, the old node is deleted.
 //! - All remaining new nodes are added to the end.
 //! - All remaining old nodes are deleted.
+//! - Portals don't have a position in the parent, so their children are just appended
+//!   to the portal target or patched there.
 //!
 //! As soon as the old or new node has a key, the algorithm switches to the key mode.
 //!
@@ -22,58 +24,32 @@
 //! new: [a] [d] [e] [b] [c] [x] [f] [y]
 //! ```
 //!
-//! The algorithm starts by taking nodes from the source iterators one by one in turn (new and old)
-//! and putting them in the corresponding queues:
-//! ```text
-//! old: [a]
-//! new: [a]
-//! ```
-//!
-//! Now we have the matching key in queues. The algorithm takes nodes from the queues and yields
-//! `patch [a] by [a]` command.
-//!
-//! But then nodes become diverse:
-//! ```text
-//! old: [b] [c]
-//! new: [d] [e] [b]
-//! ```
+//! The algorithm takes all remaining nodes and pairs every new node with the first
+//! not yet paired old node with the same key (and the same tag and namespace).
+//! Old nodes without a pair - `[g]` and `[h]` - are removed first.
 //!
-//! As soon as the algorithm finds the matching key `[b]` it yields three commands:
-//! `insert [d] before old [b]`, `insert [e] before old [b]` and then `patch [b] by [b]`.
-//! Old node `[c]` remains in queue.
-//!
-//! The algorithm continues to fill queues and stops with matching nodes `[c]`. Then it issues `patch`
-//! command:
-//! ```text
-//! old: [c]
-//! new: [c]
-//! ```
-//!
-//! Then nodes again become diverse:
+//! Then it computes the longest increasing subsequence of the paired old nodes' positions
+//! in the new order:
 //! ```text
-//! old: [d] [e] [f]
-//! new: [x] [f] [y]
+//! new:           [a] [d] [e] [b] [c] [x] [f] [y]
+//! old positions:  0   3   4   1   2   -   5   -
+//! stable:        [a]         [b] [c]     [f]
 //! ```
 //!
-//! The algorithm stops when finds the matching key `[f]` and yields three commands:
-//! `replace [d] by [x]`, `remove [e]`, `patch [f] by [f]`.
+//! Stable nodes stay where they are and they're only patched. All other nodes are placed
+//! in the new order before the next stable node: `move [d] before [b]`, `patch [d] by [d]`,
+//! `move [e] before [b]`, `patch [e] by [e]`, ..., `insert [x] before [f]`.
+//! Nodes after the last stable node are appended: `append [y]`.
 //!
-//! At this point the source iterator for the new nodes has been exhausted and the algorithm
-//! continues to take only old nodes.
-//! ```text
-//! old: [g] [h]
-//! new: [y]
-//! ```
-//!
-//! At this point both source iterators are exhausted and the algorithm yields:
-//! `replace [g] by [y]` and `remove [h]`.
-//! The append command means append as a last children.
+//! The longest increasing subsequence is the largest set of nodes that are already in the
+//! correct order, so the algorithm emits the minimal number of moves. Moved nodes keep
+//! their DOM nodes - focus, scroll positions and `ElRef`s survive reordering.
 //!
 
 use crate::browser::dom::Namespace;
-use crate::virtual_dom::{El, ElKey, Node, Tag, Text};
+use crate::virtual_dom::{El, ElKey, Node, Portal, Tag, Text};
 use std::borrow::Borrow;
-use std::collections::{BTreeSet, VecDeque};
+use std::collections::{BTreeMap, VecDeque};
 use std::iter::Peekable;
 
 #[allow(clippy::large_enum_variant)]
@@ -92,6 +68,22 @@ pub enum PatchCommand<'a, Ms: 'static> {
         text_new: &'a mut Text,
         next_node: web_sys::Node,
     },
+    /// Move the existing DOM node before `next_node` or to the end.
+    MoveNode {
+        node: web_sys::Node,
+        next_node: Option<web_sys::Node>,
+    },
+    /// Portals don't have a position in the parent, their children are appended to the target.
+    AppendPortal {
+        portal_new: &'a mut Portal<Ms>,
+    },
+    PatchPortal {
+        portal_old: Portal<Ms>,
+        portal_new: &'a mut Portal<Ms>,
+    },
+    RemovePortal {
+        portal_old: Portal<Ms>,
+    },
     PatchEl {
         el_old: El<Ms>,
         el_new: &'a mut El<Ms>,
@@ -131,6 +123,10 @@ enum PatchKey {
         el_key: Option<ElKey>,
     },
     Text,
+    Lazy {
+        key: ElKey,
+    },
+    Portal,
 }
 
 impl PatchKey {
@@ -142,6 +138,10 @@ impl PatchKey {
                 el_key: el.key.clone(),
             }),
             Node::Text(_) => Some(PatchKey::Text),
+            Node::Lazy(lazy) => Some(PatchKey::Lazy {
+                key: lazy.key.clone(),
+            }),
+            Node::Portal(_) => Some(PatchKey::Portal),
             Node::Empty | Node::NoChange => None,
         }
     }
@@ -158,11 +158,12 @@ where
     old_children_iter: Peekable<OI>,
     new_children_iter: Peekable<NI>,
     old_children: VecDeque<Node<Ms>>,
-    new_children: VecDeque<&'a mut Node<Ms>>,
-    matching_child_old: Option<OI::Item>,
-    matching_child_new: Option<NI::Item>,
-    matching_key: Option<PatchKey>,
-    keyed_mode: bool,
+    /// Old children without a new pair in keyed mode.
+    removed_children: Vec<Node<Ms>>,
+    /// `Some` after the switch to keyed mode.
+    keyed_children: Option<std::vec::IntoIter<KeyedChild<'a, Ms>>>,
+    /// The pair to patch after the previous command - e.g. after `MoveNode`.
+    pending_pair: Option<(Node<Ms>, &'a mut Node<Ms>)>,
 }
 
 impl<'a, Ms, OI, NI> PatchGen<'a, Ms, OI, NI>
@@ -177,42 +178,27 @@ where
             old_children_iter: old_children_iter.peekable(),
             new_children_iter: new_children_iter.peekable(),
             old_children: VecDeque::new(),
-            new_children: VecDeque::new(),
-            matching_child_old: None,
-            matching_child_new: None,
-            matching_key: None,
-            keyed_mode: false,
+            removed_children: Vec::new(),
+            keyed_children: None,
+            pending_pair: None,
         }
     }
 
     /// Decides what command to produce according to the internal state.
     fn next_command(&mut self) -> Option<PatchCommand<'a, Ms>> {
-        if !self.keyed_mode {
-            return self.yield_keyless();
-        }
-        // Matching `PatchKey` has been already found.
-        if self.matching_key.is_some() {
-            return self.yield_keyed();
+        if let Some((child_old, child_new)) = self.pending_pair.take() {
+            return self.patch_or_replace(child_old, child_new);
         }
-        // Try to find matching key if at least one iterator has some nodes.
-        if self.old_children_iter.peek().is_some() || self.new_children_iter.peek().is_some() {
-            self.matching_key = find_matching(
-                &mut self.old_children_iter,
-                &mut self.old_children,
-                &mut self.new_children_iter,
-                &mut self.new_children,
-            );
-            // Matching key has been found.
-            if self.matching_key.is_some() {
-                return self.yield_keyed();
-            }
+        if self.keyed_children.is_none() {
+            return self.yield_keyless();
         }
-        self.yield_remaining()
+        self.yield_keyed()
     }
 
     /// Takes a pair of old and new children from source iterators and decides how to update the
     /// old child by the new one.
-    /// Sets `keyed_mode` to true and calls `yield_keyed` as soon as any child has an element key.
+    /// Pairs all remaining children by keys and calls `yield_keyed` as soon as any child
+    /// has an element key.
     fn yield_keyless(&mut self) -> Option<PatchCommand<'a, Ms>> {
         // Take a pair of old/new children but skip if both are `Some(Node::Empty)`.
         let (child_old, child_new) = loop {
@@ -238,19 +224,15 @@ where
                 }
 
                 // Permanent switch to keyed mode.
-                self.keyed_mode = true;
+                let old_children = std::iter::once(child_old)
+                    .chain(self.old_children.drain(..).rev())
+                    .chain(self.old_children_iter.by_ref());
+                let new_children =
+                    std::iter::once(child_new).chain(self.new_children_iter.by_ref());
+                let (removed_children, keyed_children) = pair_by_keys(old_children, new_children);
 
-                let key_old = PatchKey::new(&child_old);
-                let key_new = PatchKey::new(child_new);
-                if key_old == key_new {
-                    self.matching_key = key_new;
-                }
-                if !child_old.is_empty() {
-                    self.old_children.push_back(child_old);
-                }
-                if !child_new.is_empty() {
-                    self.new_children.push_back(child_new);
-                }
+                self.removed_children = removed_children;
+                self.keyed_children = Some(keyed_children.into_iter());
                 self.next_command()
             }
             (None, Some(child_new)) => self.append(child_new),
@@ -259,106 +241,43 @@ where
         }
     }
 
-    /// Produces commands from children stored in the `old_children` and the `new_children` queues
-    /// until the child key is equal to `matching_key`, then returns the `PatchEl` or `ReplaceElByEl`
-    /// command.
+    /// Removes old children without a pair and then produces commands for the paired children
+    /// in the new order.
     ///
-    /// `self.matching_key` has to be set before calling this method.
+    /// `self.keyed_children` has to be set before calling this method.
     fn yield_keyed(&mut self) -> Option<PatchCommand<'a, Ms>> {
-        // `self.matching_child_old` and `self.matching_child_new` are set only in this method.
-        // Therefore the first matching arm is always `(None, None)`.
-        match (
-            self.matching_child_old.as_ref(),
-            self.matching_child_new.as_ref(),
-        ) {
-            // No nodes with the matching key have been found.
-            (None, None) => {
-                // If the matching key is set then both the old and the new children queues
-                // have a node with this key.
-                let child_old = self
-                    .old_children
-                    .pop_back()
-                    .expect("old child from the queue");
-                let child_new = self
-                    .new_children
-                    .pop_back()
-                    .expect("new child from the queue");
-
-                let key_old = PatchKey::new(&child_old);
-                let key_new = PatchKey::new(child_new);
-
-                if key_old == self.matching_key && key_new == self.matching_key {
-                    self.matching_child_old = Some(child_old);
-                    self.matching_child_new = Some(child_new);
-                    return self.yield_keyed();
-                }
-                if key_old == self.matching_key {
-                    let next_node = child_old.node_ws().unwrap().clone();
-                    self.matching_child_old = Some(child_old);
-                    return self.insert(child_new, next_node);
-                }
-                if key_new == self.matching_key {
-                    self.matching_child_new = Some(child_new);
-                    return self.remove(child_old);
-                }
-                self.patch_or_replace(child_old, child_new)
-            }
-            // An old node with the matching key has been found in the queue.
-            (Some(child_old), None) => {
-                let child_new = self
-                    .new_children
-                    .pop_back()
-                    .expect("node with a matching key");
-
-                if PatchKey::new(child_new) == self.matching_key {
-                    self.matching_child_new = Some(child_new);
-                    return self.yield_keyed();
-                }
-                let next_node = child_old
-                    .node_ws()
-                    .expect("old node connected to web_sys node")
-                    .clone();
-                self.insert(child_new, next_node)
-            }
-            // A new node with the matching key has been found in the queue.
-            (None, Some(_)) => {
-                let child_old = self
-                    .old_children
-                    .pop_back()
-                    .expect("node with a matching key");
-
-                if PatchKey::new(&child_old) == self.matching_key {
-                    self.matching_child_old = Some(child_old);
-                    return self.yield_keyed();
-                }
-                self.remove(child_old)
-            }
-            // An old and a new node with the matching key have been found in queues.
-            (Some(_), Some(_)) => {
-                // We have found the matching node pair, we no longer need the key.
-                self.matching_key = None;
-                let child_old = self.matching_child_old.take().unwrap();
-                let child_new = self.matching_child_new.take().unwrap();
-                self.patch_or_replace(child_old, child_new)
-            }
+        if let Some(child_old) = self.removed_children.pop() {
+            return self.remove(child_old);
         }
-    }
+        let KeyedChild {
+            child_new,
+            child_old,
+            stable,
+            next_node,
+        } = self.keyed_children.as_mut()?.next()?;
 
-    /// Takes a pair of the remaining children stored in the queues and returns the command.
-    fn yield_remaining(&mut self) -> Option<PatchCommand<'a, Ms>> {
-        match (self.old_children.pop_back(), self.new_children.pop_back()) {
-            (Some(child_old), Some(child_new)) => self.patch_or_replace(child_old, child_new),
-            (Some(child_old), None) => self.remove(child_old),
-            (None, Some(child_new)) => self.append(child_new),
-            (None, None) => None,
+        match child_old {
+            None => match next_node {
+                Some(next_node) => self.insert(child_new, next_node),
+                None => self.append(child_new),
+            },
+            Some(child_old) if stable => self.patch_or_replace(child_old, child_new),
+            Some(child_old) => match child_old.node_ws().cloned() {
+                Some(node) => {
+                    self.pending_pair = Some((child_old, child_new));
+                    Some(PatchCommand::MoveNode { node, next_node })
+                }
+                None => self.patch_or_replace(child_old, child_new),
+            },
         }
     }
 
     fn append(&mut self, child_new: &'a mut Node<Ms>) -> Option<PatchCommand<'a, Ms>> {
-        Some(match child_new {
+        Some(match child_new.unlazy_mut() {
             Node::Element(el_new) => PatchCommand::AppendEl { el_new },
             Node::Text(text_new) => PatchCommand::AppendText { text_new },
-            Node::Empty | Node::NoChange => return self.next_command(),
+            Node::Portal(portal_new) => PatchCommand::AppendPortal { portal_new },
+            Node::Empty | Node::NoChange | Node::Lazy(_) => return self.next_command(),
         })
     }
 
@@ -367,23 +286,37 @@ where
         child_new: &'a mut Node<Ms>,
         next_node: web_sys::Node,
     ) -> Option<PatchCommand<'a, Ms>> {
-        Some(match child_new {
+        Some(match child_new.unlazy_mut() {
             Node::Element(el_new) => PatchCommand::InsertEl { el_new, next_node },
             Node::Text(text_new) => PatchCommand::InsertText {
                 text_new,
                 next_node,
             },
-            Node::Empty | Node::NoChange => return self.next_command(),
+            Node::Portal(portal_new) => PatchCommand::AppendPortal { portal_new },
+            Node::Empty | Node::NoChange | Node::Lazy(_) => return self.next_command(),
         })
     }
 
     #[allow(clippy::option_if_let_else)]
     fn patch_or_replace(
         &mut self,
-        child_old: Node<Ms>,
+        mut child_old: Node<Ms>,
         child_new: &'a mut Node<Ms>,
     ) -> Option<PatchCommand<'a, Ms>> {
-        Some(match child_old {
+        let child_new = match child_new {
+            Node::Lazy(lazy_new) => {
+                // Reuse the old subtree without calling `view` and diffing.
+                if let Node::Lazy(lazy_old) = &mut child_old {
+                    if lazy_old.is_reusable_by(lazy_new) {
+                        lazy_new.node = lazy_old.node.take();
+                        return self.next_command();
+                    }
+                }
+                return self.patch_or_replace(child_old, lazy_new.render());
+            }
+            child_new => child_new,
+        };
+        Some(match child_old.into_unlazy() {
             Node::Element(el_old) => match child_new {
                 Node::Element(el_new) => {
                     if el_can_be_patched(&el_old, el_new) {
@@ -398,6 +331,11 @@ where
                     *child_new = Node::Element(el_old);
                     return self.next_command();
                 }
+                Node::Portal(_) => {
+                    self.pending_pair = Some((Node::Empty, child_new));
+                    PatchCommand::RemoveEl { el_old }
+                }
+                Node::Lazy(_) => unreachable!("new node is rendered"),
             },
             Node::Text(text_old) => match child_new {
                 Node::Element(el_new) => PatchCommand::ReplaceTextByEl { text_old, el_new },
@@ -407,6 +345,11 @@ where
                     *child_new = Node::Text(text_old);
                     return self.next_command();
                 }
+                Node::Portal(_) => {
+                    self.pending_pair = Some((Node::Empty, child_new));
+                    PatchCommand::RemoveText { text_old }
+                }
+                Node::Lazy(_) => unreachable!("new node is rendered"),
             },
             Node::Empty => match child_new {
                 Node::Element(el_new) => {
@@ -432,19 +375,39 @@ where
                 }
                 Node::Empty => return self.next_command(),
                 Node::NoChange => {
-                    *child_new = child_old;
+                    *child_new = Node::Empty;
+                    return self.next_command();
+                }
+                Node::Portal(portal_new) => PatchCommand::AppendPortal { portal_new },
+                Node::Lazy(_) => unreachable!("new node is rendered"),
+            },
+            Node::Portal(portal_old) => match child_new {
+                Node::Portal(portal_new) => PatchCommand::PatchPortal {
+                    portal_old,
+                    portal_new,
+                },
+                Node::NoChange => {
+                    *child_new = Node::Portal(portal_old);
                     return self.next_command();
                 }
+                Node::Lazy(_) => unreachable!("new node is rendered"),
+                // The new child takes the place of an empty node.
+                Node::Element(_) | Node::Text(_) | Node::Empty => {
+                    self.pending_pair = Some((Node::Empty, child_new));
+                    PatchCommand::RemovePortal { portal_old }
+                }
             },
             Node::NoChange => panic!("Node::NoChange cannot be an old VDOM node!"),
+            Node::Lazy(_) => unreachable!("old node is rendered"),
         })
     }
 
     fn remove(&mut self, child_old: Node<Ms>) -> Option<PatchCommand<'a, Ms>> {
-        Some(match child_old {
+        Some(match child_old.into_unlazy() {
             Node::Element(el_old) => PatchCommand::RemoveEl { el_old },
             Node::Text(text_old) => PatchCommand::RemoveText { text_old },
-            Node::Empty | Node::NoChange => return self.next_command(),
+            Node::Portal(portal_old) => PatchCommand::RemovePortal { portal_old },
+            Node::Empty | Node::NoChange | Node::Lazy(_) => return self.next_command(),
         })
     }
 }
@@ -467,63 +430,116 @@ pub fn el_can_be_patched<Ms>(el_old: &El<Ms>, el_new: &El<Ms>) -> bool {
     el_old.namespace == el_new.namespace && el_old.tag == el_new.tag && el_old.key == el_new.key
 }
 
-/// Takes children from source iterators (new and old) and puts them in the
-/// corresponding queues.
+/// A new child with its old pair in keyed mode.
+struct KeyedChild<'a, Ms: 'static> {
+    child_new: &'a mut Node<Ms>,
+    child_old: Option<Node<Ms>>,
+    /// The old child is in the longest increasing subsequence - it stays in place.
+    stable: bool,
+    /// The DOM node of the next stable child; `None` means the end of the parent.
+    next_node: Option<web_sys::Node>,
+}
+
+/// Pairs every new child with the first unpaired old child with the same `PatchKey`.
 ///
-/// Stops when:
-/// - The key of the new child matches to any key of the previously seen old children.
-/// - The key of the old child matches to any key of the previously seen new children.
-fn find_matching<OI, NI, ON, NN, Ms>(
-    old_children_iter: &mut Peekable<OI>,
-    old_children: &mut VecDeque<ON>,
-    new_children_iter: &mut Peekable<NI>,
-    new_children: &mut VecDeque<NN>,
-) -> Option<PatchKey>
-where
-    OI: Iterator<Item = ON>,
-    NI: Iterator<Item = NN>,
-    ON: Borrow<Node<Ms>>,
-    NN: Borrow<Node<Ms>>,
-    Ms: 'static,
-{
-    // First store all seen keys to the sets.
-    // One for the old children.
-    let mut seen_old_keys: BTreeSet<_> = old_children
+/// Returns old children without a pair and new children with their old pairs
+/// and positions in the DOM.
+fn pair_by_keys<'a, Ms: 'static>(
+    old_children: impl Iterator<Item = Node<Ms>>,
+    new_children: impl Iterator<Item = &'a mut Node<Ms>>,
+) -> (Vec<Node<Ms>>, Vec<KeyedChild<'a, Ms>>) {
+    // Empty old children aren't in the DOM.
+    let mut old_children: Vec<_> = old_children
+        .filter(|child| !child.is_empty())
+        .map(Some)
+        .collect();
+
+    let mut old_indices_by_key = BTreeMap::<_, VecDeque<_>>::new();
+    for (index, child) in old_children.iter().enumerate() {
+        if let Some(key) = child.as_ref().and_then(PatchKey::new) {
+            old_indices_by_key.entry(key).or_default().push_back(index);
+        }
+    }
+
+    let pairs: Vec<_> = new_children
+        .map(|child_new| {
+            // There is no old child to keep for a keyed `NoChange`.
+            if let Node::NoChange = child_new {
+                *child_new = Node::Empty;
+            }
+            let old_index = PatchKey::new(child_new)
+                .and_then(|key| old_indices_by_key.get_mut(&key)?.pop_front());
+            (child_new, old_index)
+        })
+        .collect();
+
+    // Portals don't have a position in the parent - they're never moved.
+    let mut stable_old_indices: Vec<_> = old_children
         .iter()
-        .filter_map(|node| PatchKey::new(node.borrow()))
+        .map(|child| matches!(child.as_ref().map(Node::unlazy), Some(Node::Portal(_))))
         .collect();
-    // And one for the new children.
-    let mut seen_new_keys: BTreeSet<_> = new_children
+    let old_indices: Vec<_> = pairs
         .iter()
-        .filter_map(|node| PatchKey::new(node.borrow()))
+        .filter_map(|(_, index)| *index)
+        .filter(|index| !stable_old_indices[*index])
         .collect();
+    for position in longest_increasing_subsequence(&old_indices) {
+        stable_old_indices[old_indices[position]] = true;
+    }
 
-    while old_children_iter.peek().is_some() || new_children_iter.peek().is_some() {
-        // Fill the old/new children queues and keep the same queue lengths.
-        let should_pick_old_child = old_children_iter.peek().is_some()
-            && (new_children_iter.peek().is_none() || new_children.len() > old_children.len());
-
-        if should_pick_old_child {
-            if let Some(key) = fetch_next_item(old_children_iter, old_children)
-                .and_then(|child| PatchKey::new(child.borrow()))
-            {
-                if seen_new_keys.contains(&key) {
-                    return Some(key);
-                }
-                seen_old_keys.insert(key);
-            }
-        } else if new_children_iter.peek().is_some() {
-            if let Some(key) = fetch_next_item(new_children_iter, new_children)
-                .and_then(|child| PatchKey::new(child.borrow()))
-            {
-                if seen_old_keys.contains(&key) {
-                    return Some(key);
-                }
-                seen_new_keys.insert(key);
+    let mut keyed_children: Vec<_> = pairs
+        .into_iter()
+        .map(|(child_new, old_index)| KeyedChild {
+            child_new,
+            child_old: old_index.and_then(|index| old_children[index].take()),
+            stable: old_index.is_some_and(|index| stable_old_indices[index]),
+            next_node: None,
+        })
+        .collect();
+
+    // Children are placed before the next stable child.
+    let mut next_node = None;
+    for child in keyed_children.iter_mut().rev() {
+        child.next_node = next_node.clone();
+        if child.stable {
+            if let Some(node_ws) = child.child_old.as_ref().and_then(Node::node_ws) {
+                next_node = Some(node_ws.clone());
             }
         }
     }
-    None
+
+    let removed_children = old_children.into_iter().flatten().collect();
+    (removed_children, keyed_children)
+}
+
+/// Returns the indices of the longest strictly increasing subsequence of `sequence`.
+///
+/// It runs in `O(n log n)` time.
+fn longest_increasing_subsequence(sequence: &[usize]) -> Vec<usize> {
+    // `tails[length - 1]` is the index of the smallest tail of all subsequences of `length`.
+    let mut tails: Vec<usize> = Vec::new();
+    let mut predecessors = vec![None; sequence.len()];
+
+    for (index, value) in sequence.iter().enumerate() {
+        let length = tails.partition_point(|tail| sequence[*tail] < *value);
+        if length > 0 {
+            predecessors[index] = Some(tails[length - 1]);
+        }
+        if length == tails.len() {
+            tails.push(index);
+        } else {
+            tails[length] = index;
+        }
+    }
+
+    let mut subsequence = Vec::with_capacity(tails.len());
+    let mut index = tails.last().copied();
+    while let Some(current) = index {
+        subsequence.push(current);
+        index = predecessors[current];
+    }
+    subsequence.reverse();
+    subsequence
 }
 
 /// Searches for the next node with set `web_sys::Node` and returns a clone of that
@@ -561,3 +577,25 @@ where
         queue.front()
     })
 }
+
+// ------ ------ Tests ------ ------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wasm_bindgen_test::*;
+
+    #[wasm_bindgen_test]
+    fn longest_increasing_subsequence_indices() {
+        assert!(longest_increasing_subsequence(&[]).is_empty());
+        assert_eq!(longest_increasing_subsequence(&[0, 1, 2]), vec![0, 1, 2]);
+        assert_eq!(longest_increasing_subsequence(&[2, 1, 0]).len(), 1);
+        // The last item moved to the front.
+        assert_eq!(longest_increasing_subsequence(&[3, 0, 1, 2]), vec![1, 2, 3]);
+        // The example from the module documentation.
+        assert_eq!(
+            longest_increasing_subsequence(&[0, 3, 4, 1, 2, 5]),
+            vec![0, 3, 4, 5]
+        );
+    }
+}
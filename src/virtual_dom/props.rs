@@ -0,0 +1,140 @@
+use indexmap::IndexMap;
+use serde::Serialize;
+use serde_wasm_bindgen as swb;
+use std::borrow::Cow;
+use wasm_bindgen::JsValue;
+
+// ------ Props ------
+
+/// JS properties of the element - e.g. `indeterminate`, `scrollTop`, `srcObject`
+/// or objects and arrays passed to custom elements. See `props!`.
+///
+/// Unlike `Attrs`, they are set by `Reflect.set` and they aren't rendered to HTML.
+/// A property removed from `Props` is set to `undefined`.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Props {
+    pub vals: IndexMap<Cow<'static, str>, PropValue>,
+}
+
+impl Props {
+    pub const fn new(vals: IndexMap<Cow<'static, str>, PropValue>) -> Self {
+        Self { vals }
+    }
+
+    pub fn empty() -> Self {
+        Self {
+            vals: IndexMap::new(),
+        }
+    }
+
+    /// Add a new name, value pair
+    pub fn add(&mut self, name: impl Into<Cow<'static, str>>, value: impl IntoPropValue) {
+        self.vals.insert(name.into(), value.into_prop_value());
+    }
+
+    /// Combine with another Props. Values of `other` win.
+    pub fn merge(&mut self, other: Self) {
+        self.vals.extend(other.vals);
+    }
+
+    /// Returns properties that have to be set to transform `self` into `new`.
+    /// Removed properties have the value `None`.
+    pub(crate) fn diff<'a>(&'a self, new: &'a Self) -> Vec<(&'a str, Option<&'a JsValue>)> {
+        let set = new
+            .vals
+            .iter()
+            .filter(move |(name, value)| self.vals.get(*name) != Some(value))
+            .map(|(name, value)| (name.as_ref(), Some(&value.value)));
+        let removed = self
+            .vals
+            .keys()
+            .filter(move |name| !new.vals.contains_key(*name))
+            .map(|name| (name.as_ref(), None));
+        set.chain(removed).collect()
+    }
+}
+
+// ------ PropValue ------
+
+/// A value of the element's property.
+///
+/// `JsValue`s are compared with `===` - i.e. objects are set again only when
+/// a different object is passed. Serialized values (see `PropValue::serde`)
+/// are compared by their content, so they can be created in `view`.
+#[derive(Clone, Debug)]
+pub struct PropValue {
+    value: JsValue,
+    /// JSON of the serialized value.
+    json: Option<String>,
+}
+
+impl PropValue {
+    pub fn new(value: impl Into<JsValue>) -> Self {
+        Self {
+            value: value.into(),
+            json: None,
+        }
+    }
+
+    /// Convert a Rust value to a JS one by `serde`. Maps are converted to plain objects.
+    ///
+    /// The value is `undefined` when the serialization fails - the error is logged.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    ///custom![
+    ///    Tag::from("data-grid"),
+    ///    props! {
+    ///        "rows" => PropValue::serde(&model.rows),
+    ///    },
+    ///]
+    /// ```
+    pub fn serde(value: &impl Serialize) -> Self {
+        let serializer = swb::Serializer::new().serialize_maps_as_objects(true);
+        match value.serialize(&serializer) {
+            Ok(value) => {
+                let json = js_sys::JSON::stringify(&value)
+                    .ok()
+                    .and_then(|json| json.as_string());
+                Self { value, json }
+            }
+            Err(error) => {
+                crate::error(format!("cannot serialize property value: {}", error));
+                Self::new(JsValue::UNDEFINED)
+            }
+        }
+    }
+
+    pub const fn value(&self) -> &JsValue {
+        &self.value
+    }
+}
+
+impl PartialEq for PropValue {
+    fn eq(&self, other: &Self) -> bool {
+        match (&self.json, &other.json) {
+            (Some(json), Some(other_json)) => json == other_json,
+            _ => self.value == other.value,
+        }
+    }
+}
+
+// ------ IntoPropValue ------
+
+/// Values accepted by `props!` and `Props::add`.
+pub trait IntoPropValue {
+    fn into_prop_value(self) -> PropValue;
+}
+
+impl IntoPropValue for PropValue {
+    fn into_prop_value(self) -> PropValue {
+        self
+    }
+}
+
+impl<T: Into<JsValue>> IntoPropValue for T {
+    fn into_prop_value(self) -> PropValue {
+        PropValue::new(self)
+    }
+}
@@ -4,6 +4,10 @@ use std::fmt;
 
 /// Handle Style separately from Attrs, since it commonly involves multiple parts,
 /// and has a different semantic meaning.
+///
+/// Only changed properties are updated in the DOM, so inline styles set by other code
+/// (e.g. JS libraries) are kept. Custom properties (`"--accent" => "teal"`) are supported
+/// and values ending with `!important` are set with the `important` priority.
 #[derive(Clone, Debug, PartialEq)]
 pub struct Style {
     pub vals: IndexMap<St, CSSValue>,
@@ -28,6 +32,89 @@ impl Style {
     pub fn merge(&mut self, other: Self) {
         self.vals.extend(other.vals.into_iter());
     }
+
+    /// Parse the `style` attribute value - e.g. `"display: flex; font-size: 1.5em"`.
+    pub(crate) fn from_declarations(declarations: &str) -> Self {
+        let mut style = Self::empty();
+        for declaration in declarations.split(';') {
+            if let Some((name, value)) = declaration.split_once(':') {
+                style.add(name.trim().to_owned(), value.trim());
+            }
+        }
+        style
+    }
+
+    /// Property changes that turn `self` into `new`.
+    ///
+    /// Removed properties are removed first. Then changed properties are set in the declaration
+    /// order and all properties after them are set again - a shorthand property (`margin`)
+    /// overrides its longhands (`margin-top`) and removing it removes them.
+    pub(crate) fn diff<'a>(&'a self, new: &'a Self) -> Vec<StyleChange<'a>> {
+        let mut changes = Vec::new();
+        for (name, old_value) in &self.vals {
+            if let CSSValue::Some(_) = old_value {
+                if !matches!(new.vals.get(name), Some(CSSValue::Some(_))) {
+                    changes.push(StyleChange::Remove {
+                        name: name.as_str(),
+                    });
+                }
+            }
+        }
+
+        let mut changed = !changes.is_empty();
+        // The greatest old index of the preceding properties - to detect reordered properties.
+        let mut last_old_index = None;
+        for (name, new_value) in &new.vals {
+            let value = match new_value {
+                CSSValue::Some(value) => value,
+                CSSValue::Ignored => continue,
+            };
+            let old_index = self.vals.get_index_of(name);
+            if old_index < last_old_index || self.vals.get(name) != Some(new_value) {
+                changed = true;
+            }
+            last_old_index = last_old_index.max(old_index);
+            if changed {
+                let (value, priority) = split_priority(value);
+                changes.push(StyleChange::Set {
+                    name: name.as_str(),
+                    value,
+                    priority,
+                });
+            }
+        }
+        changes
+    }
+}
+
+// ------ StyleChange ------
+
+/// See `Style::diff`.
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) enum StyleChange<'a> {
+    /// `style.setProperty(name, value, priority)`
+    Set {
+        name: &'a str,
+        value: &'a str,
+        /// `"important"` or `""`.
+        priority: &'a str,
+    },
+    /// `style.removeProperty(name)`
+    Remove { name: &'a str },
+}
+
+/// Splits `"red !important"` into `("red", "important")`.
+fn split_priority(value: &str) -> (&str, &str) {
+    const IMPORTANT: &str = "!important";
+
+    let value = value.trim_end();
+    let split_index = value.len().saturating_sub(IMPORTANT.len());
+    match value.get(split_index..) {
+        Some(suffix) if suffix.eq_ignore_ascii_case(IMPORTANT) => {
+            (value[..split_index].trim_end(), "important")
+        }
+        _ => (value, ""),
+    }
 }
 
 /// Output style as a string, as would be set in the DOM as the attribute value
@@ -49,3 +136,104 @@ impl fmt::Display for Style {
         write!(f, "{}", string)
     }
 }
+
+// ------ ------ Tests ------ ------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wasm_bindgen_test::*;
+
+    fn style(vals: &[(&'static str, CSSValue)]) -> Style {
+        let mut style = Style::empty();
+        for (name, value) in vals {
+            style.add(*name, value.clone());
+        }
+        style
+    }
+
+    #[wasm_bindgen_test]
+    fn diff_sets_changed_and_following_properties() {
+        let old = style(&[
+            ("color", "red".into()),
+            ("--accent", "teal".into()),
+            ("margin-top", "0".into()),
+        ]);
+        let new = style(&[
+            ("color", "red".into()),
+            ("--accent", "navy".into()),
+            ("margin-top", "0".into()),
+            ("display", "block !important".into()),
+        ]);
+        assert_eq!(
+            old.diff(&new),
+            vec![
+                StyleChange::Set {
+                    name: "--accent",
+                    value: "navy",
+                    priority: ""
+                },
+                StyleChange::Set {
+                    name: "margin-top",
+                    value: "0",
+                    priority: ""
+                },
+                StyleChange::Set {
+                    name: "display",
+                    value: "block",
+                    priority: "important"
+                },
+            ]
+        );
+        assert!(new.diff(&new).is_empty());
+    }
+
+    #[wasm_bindgen_test]
+    fn diff_removes_properties_first_and_sets_remaining_ones() {
+        let old = style(&[
+            ("color", "red".into()),
+            ("margin", "0".into()),
+            ("padding", "1px".into()),
+        ]);
+        let new = style(&[("color", "red".into()), ("margin", CSSValue::Ignored)]);
+        assert_eq!(
+            old.diff(&new),
+            vec![
+                StyleChange::Remove { name: "margin" },
+                StyleChange::Remove { name: "padding" },
+                StyleChange::Set {
+                    name: "color",
+                    value: "red",
+                    priority: ""
+                },
+            ]
+        );
+    }
+
+    #[wasm_bindgen_test]
+    fn diff_sets_reordered_properties() {
+        let old = style(&[("margin", "0".into()), ("margin-top", "5px".into())]);
+        let new = style(&[("margin-top", "5px".into()), ("margin", "0".into())]);
+        assert_eq!(
+            old.diff(&new),
+            vec![StyleChange::Set {
+                name: "margin",
+                value: "0",
+                priority: ""
+            }]
+        );
+    }
+
+    #[wasm_bindgen_test]
+    fn parse_declarations() {
+        let parsed = Style::from_declarations("display: flex; background:url(a.png);");
+        assert_eq!(
+            parsed,
+            style(&[
+                ("display", "flex".into()),
+                ("background", "url(a.png)".into())
+            ])
+        );
+        assert_eq!(Style::from_declarations(&parsed.to_string()), parsed);
+    }
+}
@@ -0,0 +1,442 @@
+//! Render VDOM nodes to HTML - e.g. to prerender pages on the server.
+//!
+//! The renderer doesn't touch `web_sys` at all, so it can be used on non-wasm targets.
+//! Text and attribute values are escaped, void elements don't have closing tags,
+//! SVG / MathML subtrees get their `xmlns` and `Style` is merged into the `style` attribute.
+
+use super::{At, AtValue, Attrs, CSSValue, El, Node, Style, Tag, Text};
+use crate::browser::dom::Namespace;
+use std::fmt;
+use std::io;
+
+/// HTML void elements - they can't have children and they don't have a closing tag.
+///
+/// [HTML spec](https://html.spec.whatwg.org/multipage/syntax.html#void-elements)
+const VOID_ELEMENTS: [&str; 14] = [
+    "area", "base", "br", "col", "embed", "hr", "img", "input", "link", "meta", "param", "source",
+    "track", "wbr",
+];
+
+/// Raw text elements - their content isn't escaped.
+///
+/// [HTML spec](https://html.spec.whatwg.org/multipage/syntax.html#raw-text-elements)
+const RAW_TEXT_ELEMENTS: [&str; 2] = ["script", "style"];
+
+// ------ ToHtml ------
+
+/// Renders VDOM entities to HTML.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// let html = view(&model).to_html();
+/// // or stream a large page directly into the response
+/// view(&model).write_html_io(&mut response_body)?;
+/// ```
+///
+/// _Note:_ Event handlers, `ElRef`s, portals and `Node::NoChange` are ignored.
+pub trait ToHtml {
+    /// Writes HTML into the given `fmt::Write` (e.g. `String`).
+    ///
+    /// # Errors
+    ///
+    /// Returns error when the writer fails.
+    fn write_html(&self, out: &mut impl fmt::Write) -> fmt::Result;
+
+    /// Writes HTML into the given `io::Write` (e.g. `File`, `Vec<u8>` or a socket).
+    ///
+    /// # Errors
+    ///
+    /// Returns error when the writer fails.
+    fn write_html_io(&self, out: &mut impl io::Write) -> io::Result<()> {
+        let mut adapter = IoAdapter { out, error: None };
+        match self.write_html(&mut adapter) {
+            Ok(()) => Ok(()),
+            Err(fmt::Error) => Err(adapter
+                .error
+                .unwrap_or_else(|| io::Error::other("formatter error"))),
+        }
+    }
+
+    /// Renders HTML into a new `String`.
+    fn to_html(&self) -> String {
+        let mut html = String::new();
+        self.write_html(&mut html)
+            .expect("writing into `String` never fails");
+        html
+    }
+}
+
+impl<Ms> ToHtml for Node<Ms> {
+    fn write_html(&self, out: &mut impl fmt::Write) -> fmt::Result {
+        write_node(out, self, None)
+    }
+}
+
+impl<Ms> ToHtml for El<Ms> {
+    fn write_html(&self, out: &mut impl fmt::Write) -> fmt::Result {
+        write_el(out, self, None)
+    }
+}
+
+impl ToHtml for Text {
+    fn write_html(&self, out: &mut impl fmt::Write) -> fmt::Result {
+        write_escaped_text(out, &self.text)
+    }
+}
+
+impl<Ms> ToHtml for [Node<Ms>] {
+    fn write_html(&self, out: &mut impl fmt::Write) -> fmt::Result {
+        self.iter().try_for_each(|node| write_node(out, node, None))
+    }
+}
+
+impl<Ms> ToHtml for Vec<Node<Ms>> {
+    fn write_html(&self, out: &mut impl fmt::Write) -> fmt::Result {
+        self.as_slice().write_html(out)
+    }
+}
+
+// ------ Writers ------
+
+fn write_node<Ms>(
+    out: &mut impl fmt::Write,
+    node: &Node<Ms>,
+    parent_namespace: Option<&Namespace>,
+) -> fmt::Result {
+    match node {
+        Node::Element(el) => write_el(out, el, parent_namespace),
+        Node::Text(text) => write_escaped_text(out, &text.text),
+        Node::Lazy(lazy) => write_node(out, &lazy.rendered(), parent_namespace),
+        // Portal content doesn't belong to the parent.
+        Node::Empty | Node::NoChange | Node::Portal(_) => Ok(()),
+    }
+}
+
+fn write_el<Ms>(
+    out: &mut impl fmt::Write,
+    el: &El<Ms>,
+    parent_namespace: Option<&Namespace>,
+) -> fmt::Result {
+    let namespace = el.namespace.as_ref().filter(|ns| **ns != Namespace::Html);
+    let is_html = namespace.is_none();
+
+    let tag = if is_html {
+        el.tag.as_str().to_ascii_lowercase()
+    } else {
+        // Foreign (e.g. SVG) tag names are case-sensitive - `linearGradient`.
+        el.tag.as_str().to_owned()
+    };
+    if !is_valid_name(&tag) {
+        return Ok(());
+    }
+
+    write!(out, "<{}", tag)?;
+
+    // `xmlns` is required only at the boundary between namespaces (e.g. the root `svg` element).
+    if let Some(namespace) = namespace {
+        if parent_namespace != Some(namespace) {
+            write_attr(out, "xmlns", Some(namespace.as_str()))?;
+        }
+    }
+
+    let is_textarea = is_html && tag == "textarea";
+    let has_style = has_visible_style(&el.style);
+
+    for (at, at_value) in &el.attrs.vals {
+        // `Style` overrides the `style` attribute - the same way as `virtual_dom_bridge` does it.
+        if *at == At::Style && has_style {
+            continue;
+        }
+        // Textarea's value is rendered as its content.
+        if *at == At::Value && is_textarea {
+            continue;
+        }
+        match at_value {
+            AtValue::Ignored => (),
+            AtValue::None => write_attr(out, at.as_str(), None)?,
+            AtValue::Some(value) => write_attr(out, at.as_str(), Some(value))?,
+        }
+    }
+
+    if has_style {
+        write_style_attr(out, &el.style)?;
+    }
+
+    if is_html && VOID_ELEMENTS.contains(&tag.as_str()) {
+        return out.write_char('>');
+    }
+
+    if !is_html && el.children.is_empty() {
+        return out.write_str("/>");
+    }
+
+    out.write_char('>')?;
+
+    if is_textarea && el.children.is_empty() {
+        if let Some(AtValue::Some(value)) = el.attrs.vals.get(&At::Value) {
+            write_escaped_text(out, value)?;
+        }
+    }
+
+    if is_html && RAW_TEXT_ELEMENTS.contains(&tag.as_str()) {
+        for child in &el.children {
+            if let Node::Text(text) = child {
+                write_raw_text(out, &text.text, &tag)?;
+            }
+        }
+    } else {
+        for child in &el.children {
+            write_node(out, child, namespace)?;
+        }
+    }
+
+    write!(out, "</{}>", tag)
+}
+
+fn has_visible_style(style: &Style) -> bool {
+    style
+        .vals
+        .values()
+        .any(|value| matches!(value, CSSValue::Some(_)))
+}
+
+fn write_style_attr(out: &mut impl fmt::Write, style: &Style) -> fmt::Result {
+    out.write_str(" style=\"")?;
+    let mut first = true;
+    for (name, value) in &style.vals {
+        if let CSSValue::Some(value) = value {
+            if !first {
+                out.write_char(';')?;
+            }
+            first = false;
+            write_escaped_attr_value(out, name.as_str())?;
+            out.write_char(':')?;
+            write_escaped_attr_value(out, value)?;
+        }
+    }
+    out.write_char('"')
+}
+
+/// Writes attributes separated by spaces, without the leading one - see `Display for Attrs`.
+pub(super) fn write_attrs(out: &mut impl fmt::Write, attrs: &Attrs) -> fmt::Result {
+    let mut html = String::new();
+    for (at, at_value) in &attrs.vals {
+        match at_value {
+            AtValue::Ignored => (),
+            AtValue::None => write_attr(&mut html, at.as_str(), None)?,
+            AtValue::Some(value) => write_attr(&mut html, at.as_str(), Some(value))?,
+        }
+    }
+    out.write_str(html.strip_prefix(' ').unwrap_or(&html))
+}
+
+fn write_attr(out: &mut impl fmt::Write, name: &str, value: Option<&str>) -> fmt::Result {
+    // Invalid names would break the markup; browsers refuse to set them anyway.
+    if !is_valid_name(name) {
+        return Ok(());
+    }
+    out.write_char(' ')?;
+    out.write_str(name)?;
+    if let Some(value) = value {
+        out.write_str("=\"")?;
+        write_escaped_attr_value(out, value)?;
+        out.write_char('"')?;
+    }
+    Ok(())
+}
+
+/// [HTML spec](https://html.spec.whatwg.org/multipage/parsing.html#escapingString)
+fn write_escaped_text(out: &mut impl fmt::Write, text: &str) -> fmt::Result {
+    write_escaped(out, text, |character| match character {
+        '&' => Some("&amp;"),
+        '<' => Some("&lt;"),
+        '>' => Some("&gt;"),
+        '\u{a0}' => Some("&nbsp;"),
+        _ => None,
+    })
+}
+
+/// [HTML spec](https://html.spec.whatwg.org/multipage/parsing.html#escapingString)
+fn write_escaped_attr_value(out: &mut impl fmt::Write, value: &str) -> fmt::Result {
+    write_escaped(out, value, |character| match character {
+        '&' => Some("&amp;"),
+        '"' => Some("&quot;"),
+        '<' => Some("&lt;"),
+        '>' => Some("&gt;"),
+        '\u{a0}' => Some("&nbsp;"),
+        _ => None,
+    })
+}
+
+fn write_escaped(
+    out: &mut impl fmt::Write,
+    text: &str,
+    escape: impl Fn(char) -> Option<&'static str>,
+) -> fmt::Result {
+    let mut last_end = 0;
+    for (index, character) in text.char_indices() {
+        if let Some(entity) = escape(character) {
+            out.write_str(&text[last_end..index])?;
+            out.write_str(entity)?;
+            last_end = index + character.len_utf8();
+        }
+    }
+    out.write_str(&text[last_end..])
+}
+
+/// Content of raw text elements can't be escaped, but it mustn't close the element prematurely.
+fn write_raw_text(out: &mut impl fmt::Write, text: &str, tag: &str) -> fmt::Result {
+    let closing_tag = format!("</{}", tag);
+    let lowercase_text = text.to_ascii_lowercase();
+    let mut last_end = 0;
+    for (index, _) in lowercase_text.match_indices(&closing_tag) {
+        out.write_str(&text[last_end..index])?;
+        out.write_str("<\\/")?;
+        last_end = index + 2;
+    }
+    out.write_str(&text[last_end..])
+}
+
+/// Checks tag and attribute names.
+///
+/// [HTML spec](https://html.spec.whatwg.org/multipage/syntax.html#attributes-2)
+fn is_valid_name(name: &str) -> bool {
+    !name.is_empty()
+        && name.chars().all(|character| {
+            !character.is_whitespace()
+                && !character.is_control()
+                && !matches!(character, '"' | '\'' | '>' | '<' | '/' | '=')
+        })
+}
+
+// ------ IoAdapter ------
+
+/// Allows to write into `io::Write` through `fmt::Write` and keeps the original `io::Error`.
+struct IoAdapter<'a, W: io::Write> {
+    out: &'a mut W,
+    error: Option<io::Error>,
+}
+
+impl<'a, W: io::Write> fmt::Write for IoAdapter<'a, W> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.out.write_all(s.as_bytes()).map_err(|error| {
+            self.error = Some(error);
+            fmt::Error
+        })
+    }
+}
+
+impl Tag {
+    /// Returns `true` for HTML void elements like `br` or `img`.
+    pub fn is_void(&self) -> bool {
+        VOID_ELEMENTS.contains(&self.as_str().to_ascii_lowercase().as_str())
+    }
+}
+
+// ------ ------ Tests ------ ------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::*;
+
+    #[derive(Clone, Debug)]
+    enum Msg {
+        Clicked,
+    }
+
+    #[test]
+    fn escapes_text_and_attributes() {
+        let node: Node<Msg> = div![
+            attrs! {At::Title => r#"a "quoted" <title> & more"#},
+            "<script>alert('xss')</script> & co",
+        ];
+        assert_eq!(
+            node.to_html(),
+            "<div title=\"a &quot;quoted&quot; &lt;title&gt; &amp; more\">\
+             &lt;script&gt;alert('xss')&lt;/script&gt; &amp; co</div>"
+        );
+    }
+
+    #[test]
+    fn void_elements_and_boolean_attributes() {
+        let node: Node<Msg> = div![
+            input![attrs! {At::Disabled => AtValue::None, At::Value => "a"}],
+            br![],
+            input![attrs! {At::Checked => false.as_at_value()}],
+        ];
+        assert_eq!(
+            node.to_html(),
+            "<div><input disabled value=\"a\"><br><input></div>"
+        );
+    }
+
+    #[test]
+    fn svg_namespace() {
+        let node: Node<Msg> = div![svg![
+            attrs! {At::ViewBox => "0 0 10 10"},
+            linearGradient![],
+            path![attrs! {At::D => "M0 0"}],
+        ]];
+        assert_eq!(
+            node.to_html(),
+            "<div><svg xmlns=\"http://www.w3.org/2000/svg\" viewBox=\"0 0 10 10\">\
+             <linearGradient/><path d=\"M0 0\"/></svg></div>"
+        );
+    }
+
+    #[test]
+    fn style_overrides_style_attribute() {
+        let node: Node<Msg> = span![
+            attrs! {At::Style => "color: blue"},
+            style! {St::Color => "red", St::Display => CSSValue::Ignored, St::FontFamily => r#""A" & B"#},
+            ev(Ev::Click, |_| Msg::Clicked),
+        ];
+        assert_eq!(
+            node.to_html(),
+            "<span style=\"color:red;font-family:&quot;A&quot; &amp; B\"></span>"
+        );
+    }
+
+    #[test]
+    fn raw_text_and_textarea() {
+        let nodes: Vec<Node<Msg>> = vec![
+            Script![r#"if (a < b) { document.write("</script>") }"#],
+            textarea![attrs! {At::Value => "</textarea><b>"}],
+        ];
+        assert_eq!(
+            nodes.to_html(),
+            "<script>if (a < b) { document.write(\"<\\/script>\") }</script>\
+             <textarea>&lt;/textarea&gt;&lt;b&gt;</textarea>"
+        );
+    }
+
+    #[test]
+    fn invalid_names_are_skipped() {
+        let mut node: Node<Msg> = div!["a"];
+        node.add_attr("onclick=\"alert(1)\"", "x")
+            .add_attr("id", "b");
+        assert_eq!(node.to_html(), "<div id=\"b\">a</div>");
+    }
+
+    #[test]
+    fn write_into_io() {
+        let node: Node<Msg> = p!["příliš žluťoučký"];
+        let mut bytes = Vec::new();
+        node.write_html_io(&mut bytes).unwrap();
+        assert_eq!(String::from_utf8(bytes).unwrap(), "<p>příliš žluťoučký</p>");
+    }
+
+    #[test]
+    fn display_escapes_text_and_attributes() {
+        let text = Text::new("<b> & co");
+        assert_eq!(text.to_string(), "&lt;b&gt; &amp; co");
+
+        let attrs = attrs! {At::Title => r#""x" <y>"#, At::Disabled => AtValue::None};
+        assert_eq!(
+            attrs.to_string(),
+            "title=\"&quot;x&quot; &lt;y&gt;\" disabled"
+        );
+    }
+}
@@ -1,4 +1,4 @@
-use super::{Attrs, El, ElKey, ElRef, EventHandler, Node, Style, Tag, Text};
+use super::{Attrs, El, ElKey, ElRef, EventHandler, LifecycleHook, Node, Props, Style, Tag, Text};
 
 // ------ Traits ------
 
@@ -42,6 +42,12 @@ impl<Ms> UpdateEl<Ms> for Style {
     }
 }
 
+impl<Ms> UpdateEl<Ms> for Props {
+    fn update_el(self, el: &mut El<Ms>) {
+        el.props.merge(self);
+    }
+}
+
 impl<Ms> UpdateEl<Ms> for EventHandler<Ms> {
     fn update_el(self, el: &mut El<Ms>) {
         el.event_handler_manager.add_event_handlers(vec![self]);
@@ -73,6 +79,12 @@ impl<Ms, E: Clone> UpdateEl<Ms> for ElRef<E> {
     }
 }
 
+impl<Ms> UpdateEl<Ms> for LifecycleHook<Ms> {
+    fn update_el(self, el: &mut El<Ms>) {
+        el.hooks.push(self);
+    }
+}
+
 impl<Ms> UpdateEl<Ms> for ElKey {
     fn update_el(self, el: &mut El<Ms>) {
         el.key = Some(self);